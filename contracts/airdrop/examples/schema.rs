@@ -5,6 +5,7 @@ use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use anchor_token::airdrop::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, LatestStageResponse, MerkleRootResponse, QueryMsg,
+    StageSolvencyResponse,
 };
 
 fn main() {
@@ -19,4 +20,5 @@ fn main() {
     export_schema(&schema_for!(LatestStageResponse), &out_dir);
     export_schema(&schema_for!(MerkleRootResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(StageSolvencyResponse), &out_dir);
 }
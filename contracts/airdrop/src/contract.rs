@@ -2,20 +2,23 @@
 use cosmwasm_std::entry_point;
 
 use crate::error::ContractError;
+use crate::querier::query_anc_balance;
 use crate::state::{
-    read_claimed, read_config, read_latest_stage, read_merkle_root, store_claimed, store_config,
-    store_latest_stage, store_merkle_root, Config,
+    read_claimed, read_config, read_latest_stage, read_merkle_root, read_stage_amount,
+    read_stage_claimed, read_stage_expiration, store_claimed, store_config, store_latest_stage,
+    store_merkle_root, store_stage_amount, store_stage_claimed, store_stage_expiration, Config,
 };
 
 use anchor_token::airdrop::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
-    MerkleRootResponse, MigrateMsg, QueryMsg,
+    MerkleRootResponse, MigrateMsg, QueryMsg, StageSolvencyResponse,
 };
+use bech32::{ToBase32, Variant};
 use cosmwasm_std::{
-    to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
-    WasmMsg,
+    to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    Storage, Uint128, WasmMsg,
 };
-use cw20::Cw20ExecuteMsg;
+use cw20::{Cw20ExecuteMsg, Expiration};
 use sha3::Digest;
 use std::convert::TryInto;
 
@@ -31,6 +34,8 @@ pub fn instantiate(
         &Config {
             owner: deps.api.addr_canonicalize(&msg.owner)?,
             anchor_token: deps.api.addr_canonicalize(&msg.anchor_token)?,
+            sunset_time: None,
+            swept_amount: None,
         },
     )?;
 
@@ -43,27 +48,54 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
-        ExecuteMsg::RegisterMerkleRoot { merkle_root } => {
-            register_merkle_root(deps, info, merkle_root)
+        ExecuteMsg::UpdateConfig { owner, sunset_time } => {
+            update_config(deps, env, info, owner, sunset_time)
         }
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root,
+            total_amount,
+            expiration,
+        } => register_merkle_root(deps, env, info, merkle_root, total_amount, expiration),
         ExecuteMsg::Claim {
             stage,
             amount,
             proof,
-        } => claim(deps, info, stage, amount, proof),
+        } => claim(deps, env, info, stage, amount, proof),
+        ExecuteMsg::ClaimFor {
+            stage,
+            amount,
+            proof,
+            original_address,
+            new_address,
+            pubkey,
+            signature,
+        } => claim_for(
+            deps,
+            env,
+            stage,
+            amount,
+            proof,
+            original_address,
+            new_address,
+            pubkey,
+            signature,
+        ),
+        ExecuteMsg::ReclaimExpired { stage } => reclaim_expired(deps, env, info, stage),
+        ExecuteMsg::SweepUnclaimed { recipient } => sweep_unclaimed(deps, env, info, recipient),
     }
 }
 
 pub fn update_config(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     owner: Option<String>,
+    sunset_time: Option<u64>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
@@ -74,14 +106,32 @@ pub fn update_config(
         config.owner = deps.api.addr_canonicalize(&owner)?;
     }
 
+    if let Some(sunset_time) = sunset_time {
+        // a passed sunset is final, claims never reopen
+        if is_sunset(&config, &env) {
+            return Err(ContractError::AirdropSunset {});
+        }
+
+        if sunset_time <= env.block.time.seconds()
+            || matches!(config.sunset_time, Some(prev) if sunset_time < prev)
+        {
+            return Err(ContractError::InvalidSunsetTime {});
+        }
+
+        config.sunset_time = Some(sunset_time);
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::new().add_attributes(vec![("action", "update_config")]))
 }
 
 pub fn register_merkle_root(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     merkle_root: String,
+    total_amount: Uint128,
+    expiration: Option<Expiration>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
@@ -97,18 +147,83 @@ pub fn register_merkle_root(
     let latest_stage: u8 = read_latest_stage(deps.storage)?;
     let stage = latest_stage + 1;
 
+    // the balance must cover the new stage besides the unclaimed amounts of prior stages
+    let mut outstanding_amount = Uint128::zero();
+    for prior_stage in 1..stage {
+        if let Some(stage_amount) = read_stage_amount(deps.storage, prior_stage)? {
+            outstanding_amount += stage_amount
+                .checked_sub(read_stage_claimed(deps.storage, prior_stage)?)
+                .unwrap_or_default();
+        }
+    }
+
+    let balance = query_anc_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.anchor_token)?,
+        env.contract.address,
+    )?;
+    if outstanding_amount + total_amount > balance {
+        return Err(ContractError::InsufficientBalance {});
+    }
+
     store_merkle_root(deps.storage, stage, merkle_root.to_string())?;
+    store_stage_amount(deps.storage, stage, total_amount)?;
     store_latest_stage(deps.storage, stage)?;
+    if let Some(expiration) = expiration {
+        store_stage_expiration(deps.storage, stage, &expiration)?;
+    }
 
     Ok(Response::new().add_attributes(vec![
         ("action", "register_merkle_root"),
         ("stage", &stage.to_string()),
         ("merkle_root", &merkle_root),
+        ("total_amount", &total_amount.to_string()),
     ]))
 }
 
+/// Accumulate the claimed amount of the stage,
+/// which can never exceed the stage allocation
+fn record_stage_claim(
+    storage: &mut dyn Storage,
+    stage: u8,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let claimed_amount = read_stage_claimed(storage, stage)? + amount;
+    if let Some(stage_amount) = read_stage_amount(storage, stage)? {
+        if claimed_amount > stage_amount {
+            return Err(ContractError::StageAllocationExceeded {});
+        }
+    }
+
+    store_stage_claimed(storage, stage, claimed_amount)?;
+    Ok(())
+}
+
+fn is_sunset(config: &Config, env: &Env) -> bool {
+    matches!(config.sunset_time, Some(sunset_time) if env.block.time.seconds() >= sunset_time)
+}
+
+fn assert_stage_not_expired(
+    storage: &dyn Storage,
+    env: &Env,
+    stage: u8,
+) -> Result<(), ContractError> {
+    if is_sunset(&read_config(storage)?, env) {
+        return Err(ContractError::AirdropSunset {});
+    }
+
+    if let Some(expiration) = read_stage_expiration(storage, stage)? {
+        if expiration.is_expired(&env.block) {
+            return Err(ContractError::StageExpired {});
+        }
+    }
+
+    Ok(())
+}
+
 pub fn claim(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     stage: u8,
     amount: Uint128,
@@ -117,6 +232,8 @@ pub fn claim(
     let config: Config = read_config(deps.storage)?;
     let merkle_root: String = read_merkle_root(deps.storage, stage)?;
 
+    assert_stage_not_expired(deps.storage, &env, stage)?;
+
     let user_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
 
     // If user claimed target stage, return err
@@ -124,7 +241,193 @@ pub fn claim(
         return Err(ContractError::AlreadyClaimed {});
     }
 
-    let user_input: String = info.sender.to_string() + &amount.to_string();
+    verify_merkle_proof(&merkle_root, info.sender.as_str(), amount, proof)?;
+
+    // Update claim index to the current stage
+    store_claimed(deps.storage, &user_raw, stage)?;
+    record_stage_claim(deps.storage, stage, amount)?;
+
+    Ok(Response::new()
+        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+        })])
+        .add_attributes(vec![
+            ("action", "claim"),
+            ("stage", &stage.to_string()),
+            ("address", info.sender.as_str()),
+            ("amount", &amount.to_string()),
+        ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn claim_for(
+    deps: DepsMut,
+    env: Env,
+    stage: u8,
+    amount: Uint128,
+    proof: Vec<String>,
+    original_address: String,
+    new_address: String,
+    pubkey: Binary,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let merkle_root: String = read_merkle_root(deps.storage, stage)?;
+
+    assert_stage_not_expired(deps.storage, &env, stage)?;
+
+    let user_raw = deps.api.addr_canonicalize(&original_address)?;
+    let new_address = deps.api.addr_validate(&new_address)?;
+
+    // If user claimed target stage, return err
+    if read_claimed(deps.storage, &user_raw, stage)? {
+        return Err(ContractError::AlreadyClaimed {});
+    }
+
+    // The public key must belong to the original address
+    if pubkey_to_address(&pubkey)? != original_address {
+        return Err(ContractError::PubkeyMismatch {});
+    }
+
+    // bind the signature to this chain and contract so it can not be replayed elsewhere
+    let message = format!(
+        "{}:{}:{}:{}:{}",
+        env.block.chain_id, env.contract.address, stage, amount, new_address
+    );
+    let message_hash = sha2::Sha256::digest(message.as_bytes());
+    if !deps
+        .api
+        .secp256k1_verify(&message_hash, &signature, &pubkey)
+        .unwrap_or(false)
+    {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    verify_merkle_proof(&merkle_root, &original_address, amount, proof)?;
+
+    // Update claim index to the current stage
+    store_claimed(deps.storage, &user_raw, stage)?;
+    record_stage_claim(deps.storage, stage, amount)?;
+
+    Ok(Response::new()
+        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: new_address.to_string(),
+                amount,
+            })?,
+        })])
+        .add_attributes(vec![
+            ("action", "claim_for"),
+            ("stage", &stage.to_string()),
+            ("address", &original_address),
+            ("recipient", new_address.as_str()),
+            ("amount", &amount.to_string()),
+        ]))
+}
+
+pub fn reclaim_expired(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match read_stage_expiration(deps.storage, stage)? {
+        Some(expiration) if expiration.is_expired(&env.block) => {}
+        _ => return Err(ContractError::StageNotExpired {}),
+    }
+
+    let stage_amount = read_stage_amount(deps.storage, stage)?.unwrap_or_default();
+    let reclaim_amount = stage_amount
+        .checked_sub(read_stage_claimed(deps.storage, stage)?)
+        .unwrap_or_default();
+    if reclaim_amount.is_zero() {
+        return Err(ContractError::NothingToReclaim {});
+    }
+
+    // close the stage, so the reclaimed amount is no longer outstanding
+    store_stage_claimed(deps.storage, stage, stage_amount)?;
+
+    let owner = deps.api.addr_humanize(&config.owner)?;
+    Ok(Response::new()
+        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: owner.to_string(),
+                amount: reclaim_amount,
+            })?,
+        })])
+        .add_attributes(vec![
+            ("action", "reclaim_expired"),
+            ("stage", &stage.to_string()),
+            ("amount", &reclaim_amount.to_string()),
+        ]))
+}
+
+pub fn sweep_unclaimed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !is_sunset(&config, &env) {
+        return Err(ContractError::SunsetNotReached {});
+    }
+
+    if config.swept_amount.is_some() {
+        return Err(ContractError::AlreadySwept {});
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let anchor_token = deps.api.addr_humanize(&config.anchor_token)?;
+    let amount = query_anc_balance(&deps.querier, anchor_token.clone(), env.contract.address)?;
+
+    config.swept_amount = Some(amount);
+    store_config(deps.storage, &config)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: anchor_token.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "sweep_unclaimed"),
+        ("recipient", recipient.as_str()),
+        ("amount", &amount.to_string()),
+    ]))
+}
+
+fn verify_merkle_proof(
+    merkle_root: &str,
+    address: &str,
+    amount: Uint128,
+    proof: Vec<String>,
+) -> Result<(), ContractError> {
+    let user_input: String = address.to_string() + &amount.to_string();
     let mut hash: [u8; 32] = sha3::Keccak256::digest(user_input.as_bytes())
         .as_slice()
         .try_into()
@@ -156,24 +459,17 @@ pub fn claim(
         return Err(ContractError::MerkleVerification {});
     }
 
-    // Update claim index to the current stage
-    store_claimed(deps.storage, &user_raw, stage)?;
+    Ok(())
+}
 
-    Ok(Response::new()
-        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount,
-            })?,
-        })])
-        .add_attributes(vec![
-            ("action", "claim"),
-            ("stage", &stage.to_string()),
-            ("address", info.sender.as_str()),
-            ("amount", &amount.to_string()),
-        ]))
+/// Terra account address of a compressed secp256k1 public key,
+/// bech32("terra", ripemd160(sha256(pubkey)))
+fn pubkey_to_address(pubkey: &Binary) -> StdResult<String> {
+    let sha_hash = sha2::Sha256::digest(pubkey.as_slice());
+    let rip_hash = ripemd160::Ripemd160::digest(&sha_hash);
+
+    bech32::encode("terra", rip_hash.to_base32(), Variant::Bech32)
+        .map_err(|_| StdError::generic_err("Failed to encode pubkey address"))
 }
 
 fn bytes_cmp(a: [u8; 32], b: [u8; 32]) -> std::cmp::Ordering {
@@ -190,7 +486,7 @@ fn bytes_cmp(a: [u8; 32], b: [u8; 32]) -> std::cmp::Ordering {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::MerkleRoot { stage } => to_binary(&query_merkle_root(deps, stage)?),
@@ -198,6 +494,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::IsClaimed { stage, address } => {
             to_binary(&query_is_claimed(deps, stage, address)?)
         }
+        QueryMsg::StageSolvency { stage } => to_binary(&query_stage_solvency(deps, env, stage)?),
     }
 }
 
@@ -206,6 +503,8 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let resp = ConfigResponse {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
         anchor_token: deps.api.addr_humanize(&state.anchor_token)?.to_string(),
+        sunset_time: state.sunset_time,
+        swept_amount: state.swept_amount,
     };
 
     Ok(resp)
@@ -234,6 +533,23 @@ pub fn query_is_claimed(deps: Deps, stage: u8, address: String) -> StdResult<IsC
     Ok(resp)
 }
 
+pub fn query_stage_solvency(deps: Deps, env: Env, stage: u8) -> StdResult<StageSolvencyResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let resp = StageSolvencyResponse {
+        stage,
+        total_amount: read_stage_amount(deps.storage, stage)?,
+        claimed_amount: read_stage_claimed(deps.storage, stage)?,
+        balance: query_anc_balance(
+            &deps.querier,
+            deps.api.addr_humanize(&config.anchor_token)?,
+            env.contract.address,
+        )?,
+        expiration: read_stage_expiration(deps.storage, stage)?,
+    };
+
+    Ok(resp)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     Ok(Response::default())
@@ -15,9 +15,42 @@ pub enum ContractError {
     #[error("Invalid hex encoded merkle root")]
     InvalidHexMerkle {},
 
+    #[error("Insufficient balance to cover the stage allocation")]
+    InsufficientBalance {},
+
+    #[error("Invalid signature")]
+    InvalidSignature {},
+
     #[error("Merkle verification failed")]
     MerkleVerification {},
 
+    #[error("Claims exceed the stage allocation")]
+    StageAllocationExceeded {},
+
+    #[error("Stage has expired")]
+    StageExpired {},
+
+    #[error("Stage has not expired")]
+    StageNotExpired {},
+
+    #[error("Nothing to reclaim")]
+    NothingToReclaim {},
+
+    #[error("Airdrop has ended")]
+    AirdropSunset {},
+
+    #[error("Airdrop has not ended")]
+    SunsetNotReached {},
+
+    #[error("Sunset time must be in the future and cannot be lowered")]
+    InvalidSunsetTime {},
+
+    #[error("Unclaimed tokens have already been swept")]
+    AlreadySwept {},
+
+    #[error("Public key does not match the original address")]
+    PubkeyMismatch {},
+
     #[error("Unauthorized")]
     Unauthorized {},
 }
@@ -1,6 +1,10 @@
 pub mod contract;
 pub mod error;
+pub mod querier;
 pub mod state;
 
+#[cfg(test)]
+mod mock_querier;
+
 #[cfg(test)]
 mod tests;
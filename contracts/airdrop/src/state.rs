@@ -1,19 +1,25 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
+use cosmwasm_std::{CanonicalAddr, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use cw20::Expiration;
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_LATEST_STAGE: &[u8] = b"latest_stage";
 
 static PREFIX_MERKLE_ROOT: &[u8] = b"merkle_root";
 static PREFIX_CLAIM_INDEX: &[u8] = b"claim_index";
+static PREFIX_STAGE_AMOUNT: &[u8] = b"stage_amount";
+static PREFIX_STAGE_CLAIMED: &[u8] = b"stage_claimed";
+static PREFIX_STAGE_EXPIRATION: &[u8] = b"stage_expiration";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: CanonicalAddr,
     pub anchor_token: CanonicalAddr,
+    pub sunset_time: Option<u64>,
+    pub swept_amount: Option<Uint128>,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -62,3 +68,43 @@ pub fn read_claimed(storage: &dyn Storage, user: &CanonicalAddr, stage: u8) -> S
         None => Ok(false),
     }
 }
+
+pub fn store_stage_amount(storage: &mut dyn Storage, stage: u8, amount: Uint128) -> StdResult<()> {
+    let mut stage_amount_bucket: Bucket<Uint128> = Bucket::new(storage, PREFIX_STAGE_AMOUNT);
+    stage_amount_bucket.save(&[stage], &amount)
+}
+
+/// Total allocation of the stage, not recorded for stages registered before
+pub fn read_stage_amount(storage: &dyn Storage, stage: u8) -> StdResult<Option<Uint128>> {
+    let stage_amount_bucket: ReadonlyBucket<Uint128> =
+        ReadonlyBucket::new(storage, PREFIX_STAGE_AMOUNT);
+    stage_amount_bucket.may_load(&[stage])
+}
+
+pub fn store_stage_claimed(storage: &mut dyn Storage, stage: u8, amount: Uint128) -> StdResult<()> {
+    let mut stage_claimed_bucket: Bucket<Uint128> = Bucket::new(storage, PREFIX_STAGE_CLAIMED);
+    stage_claimed_bucket.save(&[stage], &amount)
+}
+
+pub fn read_stage_claimed(storage: &dyn Storage, stage: u8) -> StdResult<Uint128> {
+    let stage_claimed_bucket: ReadonlyBucket<Uint128> =
+        ReadonlyBucket::new(storage, PREFIX_STAGE_CLAIMED);
+    Ok(stage_claimed_bucket.may_load(&[stage])?.unwrap_or_default())
+}
+
+pub fn store_stage_expiration(
+    storage: &mut dyn Storage,
+    stage: u8,
+    expiration: &Expiration,
+) -> StdResult<()> {
+    let mut stage_expiration_bucket: Bucket<Expiration> =
+        Bucket::new(storage, PREFIX_STAGE_EXPIRATION);
+    stage_expiration_bucket.save(&[stage], expiration)
+}
+
+/// Expiration of the stage, stages without one never expire
+pub fn read_stage_expiration(storage: &dyn Storage, stage: u8) -> StdResult<Option<Expiration>> {
+    let stage_expiration_bucket: ReadonlyBucket<Expiration> =
+        ReadonlyBucket::new(storage, PREFIX_STAGE_EXPIRATION);
+    stage_expiration_bucket.may_load(&[stage])
+}
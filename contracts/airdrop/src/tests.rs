@@ -1,12 +1,16 @@
 use crate::contract::{execute, instantiate, query};
 use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
 use anchor_token::airdrop::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
-    MerkleRootResponse, QueryMsg,
+    MerkleRootResponse, QueryMsg, StageSolvencyResponse,
 };
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{attr, from_binary, to_binary, CosmosMsg, SubMsg, Uint128, WasmMsg};
-use cw20::Cw20ExecuteMsg;
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Addr, Binary, CosmosMsg, SubMsg, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Expiration};
+use sha3::Digest;
 
 #[test]
 fn proper_initialization() {
@@ -49,6 +53,7 @@ fn update_config() {
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
         owner: Some("owner0001".to_string()),
+        sunset_time: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -61,7 +66,10 @@ fn update_config() {
 
     // Unauthorzied err
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig { owner: None };
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        sunset_time: None,
+    };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
@@ -82,10 +90,15 @@ fn register_merkle_root() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::new(10000000u128))]);
+
     // register new merkle root
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::RegisterMerkleRoot {
         merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        total_amount: Uint128::new(10000000u128),
+        expiration: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -97,7 +110,8 @@ fn register_merkle_root() {
             attr(
                 "merkle_root",
                 "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37"
-            )
+            ),
+            attr("total_amount", "10000000"),
         ]
     );
 
@@ -132,16 +146,23 @@ fn claim() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::new(20000000u128))]);
+
     // Register merkle roots
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::RegisterMerkleRoot {
         merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        total_amount: Uint128::new(10000000u128),
+        expiration: None,
     };
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::RegisterMerkleRoot {
         merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        total_amount: Uint128::new(10000000u128),
+        expiration: None,
     };
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -241,3 +262,513 @@ fn claim() {
         ]
     );
 }
+
+#[test]
+fn claim_for() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        anchor_token: "anchor0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // address of the compressed pubkey below
+    let original_address = "terra10czyev3wv8sm99d8l8h4w70g6p8xt5lg6zmpwy";
+    let pubkey =
+        hex::decode("0243f8c41498980a79e07b2f93aca5fb513b29ab1ad1dd328044e16d8eb883ba42").unwrap();
+    // signature of "cosmos-testnet-14002:cosmos2contract:1:1000001:addr0001"
+    let signature = hex::decode(
+        "437caff8337e624ff8c4853e809a0e901bc6a3a1588adc7290bcf5afb84c0de6\
+         1bbe323ce2c15a00ff7850a27224f8559652c09ddec715c74c6af76d57630e1d",
+    )
+    .unwrap();
+
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::new(1000001u128))]);
+
+    // Register a single leaf merkle root
+    let leaf = sha3::Keccak256::digest(format!("{}{}", original_address, 1000001u128).as_bytes());
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: hex::encode(leaf),
+        total_amount: Uint128::new(1000001u128),
+        expiration: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("addr0002", &[]);
+
+    // pubkey of another key
+    let mut other_pubkey = pubkey.clone();
+    other_pubkey[0] = 0x03;
+    let msg = ExecuteMsg::ClaimFor {
+        stage: 1u8,
+        amount: Uint128::new(1000001u128),
+        proof: vec![],
+        original_address: original_address.to_string(),
+        new_address: "addr0001".to_string(),
+        pubkey: Binary::from(other_pubkey),
+        signature: Binary::from(signature.clone()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::PubkeyMismatch {}) => {}
+        _ => panic!("Must return PubkeyMismatch error"),
+    }
+
+    // signature was made for another recipient
+    let msg = ExecuteMsg::ClaimFor {
+        stage: 1u8,
+        amount: Uint128::new(1000001u128),
+        proof: vec![],
+        original_address: original_address.to_string(),
+        new_address: "addr0002".to_string(),
+        pubkey: Binary::from(pubkey.clone()),
+        signature: Binary::from(signature.clone()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidSignature {}) => {}
+        _ => panic!("Must return InvalidSignature error"),
+    }
+
+    // signature was made for another deployment
+    let msg = ExecuteMsg::ClaimFor {
+        stage: 1u8,
+        amount: Uint128::new(1000001u128),
+        proof: vec![],
+        original_address: original_address.to_string(),
+        new_address: "addr0001".to_string(),
+        pubkey: Binary::from(pubkey.clone()),
+        signature: Binary::from(signature.clone()),
+    };
+    let mut env = mock_env();
+    env.contract.address = Addr::unchecked("airdrop0001");
+    let res = execute(deps.as_mut(), env, info.clone(), msg.clone());
+    match res {
+        Err(ContractError::InvalidSignature {}) => {}
+        _ => panic!("Must return InvalidSignature error"),
+    }
+
+    let mut env = mock_env();
+    env.block.chain_id = "other-chain".to_string();
+    let res = execute(deps.as_mut(), env, info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidSignature {}) => {}
+        _ => panic!("Must return InvalidSignature error"),
+    }
+
+    let msg = ExecuteMsg::ClaimFor {
+        stage: 1u8,
+        amount: Uint128::new(1000001u128),
+        proof: vec![],
+        original_address: original_address.to_string(),
+        new_address: "addr0001".to_string(),
+        pubkey: Binary::from(pubkey),
+        signature: Binary::from(signature),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "anchor0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::new(1000001u128),
+            })
+            .unwrap(),
+            funds: vec![]
+        }))]
+    );
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_for"),
+            attr("stage", "1"),
+            attr("address", original_address),
+            attr("recipient", "addr0001"),
+            attr("amount", "1000001")
+        ]
+    );
+
+    assert!(
+        from_binary::<IsClaimedResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::IsClaimed {
+                    stage: 1,
+                    address: original_address.to_string(),
+                }
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .is_claimed
+    );
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::AlreadyClaimed {}) => {}
+        _ => panic!("Must return AlreadyClaimed error"),
+    }
+}
+
+#[test]
+fn stage_solvency() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        anchor_token: "anchor0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::new(3000000u128))]);
+
+    // the allocation cannot exceed the balance
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        total_amount: Uint128::new(3000001u128),
+        expiration: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InsufficientBalance {}) => {}
+        _ => panic!("Must return InsufficientBalance error"),
+    }
+
+    // bad root, the stage allocation is lower than the leaf amounts
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        total_amount: Uint128::new(1000000u128),
+        expiration: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the unclaimed allocation of stage 1 is not available to stage 2
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        total_amount: Uint128::new(2000001u128),
+        expiration: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InsufficientBalance {}) => {}
+        _ => panic!("Must return InsufficientBalance error"),
+    }
+
+    let msg = ExecuteMsg::Claim {
+        amount: Uint128::new(1000001u128),
+        stage: 1u8,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::StageAllocationExceeded {}) => {}
+        _ => panic!("Must return StageAllocationExceeded error"),
+    }
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::StageSolvency { stage: 1 },
+    )
+    .unwrap();
+    let solvency: StageSolvencyResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        solvency,
+        StageSolvencyResponse {
+            stage: 1,
+            total_amount: Some(Uint128::new(1000000u128)),
+            claimed_amount: Uint128::zero(),
+            balance: Uint128::new(3000000u128),
+            expiration: None,
+        }
+    );
+}
+
+#[test]
+fn claim_expired_stage() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        anchor_token: "anchor0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::new(20000000u128))]);
+
+    // Register two stages with the same merkle root, expiring in 100 blocks
+    let mut env = mock_env();
+    let expiration = Expiration::AtHeight(env.block.height + 100);
+    for _ in 0..2 {
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+                .to_string(),
+            total_amount: Uint128::new(10000000u128),
+            expiration: Some(expiration),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    }
+
+    let proof = vec![
+        "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+        "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+        "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+        "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+    ];
+    let claimer_info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+
+    // claim before the expiration
+    let msg = ExecuteMsg::Claim {
+        amount: Uint128::new(1000001u128),
+        stage: 1u8,
+        proof: proof.clone(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), claimer_info.clone(), msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::ReclaimExpired { stage: 1u8 };
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::StageNotExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // claim after the expiration
+    env.block.height += 100;
+    let msg = ExecuteMsg::Claim {
+        amount: Uint128::new(1000001u128),
+        stage: 2u8,
+        proof,
+    };
+    let res = execute(deps.as_mut(), env.clone(), claimer_info, msg);
+    match res {
+        Err(ContractError::StageExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::ReclaimExpired { stage: 1u8 };
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::ReclaimExpired { stage: 1u8 };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "anchor0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "owner0000".to_string(),
+                amount: Uint128::new(8999999u128),
+            })
+            .unwrap(),
+            funds: vec![]
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "reclaim_expired"),
+            attr("stage", "1"),
+            attr("amount", "8999999")
+        ]
+    );
+
+    // the stage is closed after the reclaim
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::NothingToReclaim {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(deps.as_ref(), env, QueryMsg::StageSolvency { stage: 1u8 }).unwrap();
+    assert_eq!(
+        from_binary::<StageSolvencyResponse>(&res).unwrap(),
+        StageSolvencyResponse {
+            stage: 1,
+            total_amount: Some(Uint128::new(10000000u128)),
+            claimed_amount: Uint128::new(10000000u128),
+            balance: Uint128::new(20000000u128),
+            expiration: Some(expiration),
+        }
+    );
+}
+
+#[test]
+fn sweep_unclaimed_after_sunset() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        anchor_token: "anchor0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::new(10000000u128))]);
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        total_amount: Uint128::new(10000000u128),
+        expiration: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the sunset must be in the future
+    let mut env = mock_env();
+    let sunset_time = env.block.time.seconds() + 100;
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        sunset_time: Some(env.block.time.seconds()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidSunsetTime {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        sunset_time: Some(sunset_time),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // the sunset cannot be lowered
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        sunset_time: Some(sunset_time - 1),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidSunsetTime {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let claim_msg = ExecuteMsg::Claim {
+        amount: Uint128::new(1000001u128),
+        stage: 1u8,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+    };
+
+    // claim before the sunset
+    let claimer_info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        claimer_info.clone(),
+        claim_msg.clone(),
+    )
+    .unwrap();
+
+    let sweep_msg = ExecuteMsg::SweepUnclaimed {
+        recipient: "community0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), sweep_msg.clone());
+    match res {
+        Err(ContractError::SunsetNotReached {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // claims fail after the sunset, regardless of the stage
+    env.block.time = env.block.time.plus_seconds(100);
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::new(8999999u128))]);
+    let res = execute(deps.as_mut(), env.clone(), claimer_info, claim_msg);
+    match res {
+        Err(ContractError::AirdropSunset {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        sweep_msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), sweep_msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "anchor0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "community0000".to_string(),
+                amount: Uint128::new(8999999u128),
+            })
+            .unwrap(),
+            funds: vec![]
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sweep_unclaimed"),
+            attr("recipient", "community0000"),
+            attr("amount", "8999999")
+        ]
+    );
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.sunset_time, Some(sunset_time));
+    assert_eq!(config.swept_amount, Some(Uint128::new(8999999u128)));
+
+    // the sweep happens only once
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), sweep_msg);
+    match res {
+        Err(ContractError::AlreadySwept {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the passed sunset cannot be postponed
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        sunset_time: Some(env.block.time.seconds() + 100),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::AirdropSunset {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
@@ -1,7 +1,11 @@
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use anchor_token::collector::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use anchor_token::collector::{
+    ConfigResponse, ExecuteMsg, FailedSwapsResponse, InstantiateMsg, QueryMsg,
+    RegisteredAssetsResponse, RoutesResponse, SimulateBuybackResponse, StatsResponse,
+    TotalStatsResponse,
+};
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 fn main() {
@@ -14,4 +18,10 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(StatsResponse), &out_dir);
+    export_schema(&schema_for!(TotalStatsResponse), &out_dir);
+    export_schema(&schema_for!(RegisteredAssetsResponse), &out_dir);
+    export_schema(&schema_for!(FailedSwapsResponse), &out_dir);
+    export_schema(&schema_for!(SimulateBuybackResponse), &out_dir);
+    export_schema(&schema_for!(RoutesResponse), &out_dir);
 }
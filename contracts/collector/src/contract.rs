@@ -2,17 +2,38 @@
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    attr, to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
-    Reply, Response, StdError, StdResult, SubMsg, WasmMsg,
+    attr, to_binary, Addr, Binary, Coin, ContractResult, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg, SubMsgExecutionResponse,
+    Uint128, WasmMsg,
 };
+use std::convert::TryFrom;
 
-use crate::state::{read_config, store_config, Config};
+use crate::state::{
+    read_config, read_epoch_stats, read_epoch_stats_range, read_failed_swap, read_failed_swaps,
+    read_native_pair, read_native_pairs, read_pending_gov_contract, read_route, read_routes,
+    read_tmp_sweep_denom, read_total_stats, remove_failed_swap, remove_pending_gov_contract,
+    remove_route, store_config, store_epoch_stats, store_failed_swap, store_native_pair,
+    store_pending_gov_contract, store_route, store_tmp_sweep_denom, store_total_stats, Config,
+    EpochStats, RouteHop,
+};
 
 use crate::migration::migrate_config;
-use anchor_token::collector::{ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use anchor_token::collector::{
+    ConfigResponse, EpochStatsResponse, ExecuteMsg, FailedSwapResponse, FailedSwapsResponse,
+    InstantiateMsg, MigrateMsg, PairHop, QueryMsg, RegisteredAssetResponse,
+    RegisteredAssetsResponse, RouteResponse, RoutesResponse, SimulateBuybackResponse,
+    StatsResponse, TotalStatsResponse,
+};
 use astroport::asset::{Asset, AssetInfo, PairInfo};
-use astroport::pair::ExecuteMsg as AstroportExecuteMsg;
-use astroport::querier::{query_balance, query_pair_info, query_token_balance};
+use astroport::pair::{
+    Cw20HookMsg as AstroportCw20HookMsg, ExecuteMsg as AstroportExecuteMsg,
+    QueryMsg as AstroportQueryMsg,
+};
+use astroport::querier::{query_balance, query_pair_info, query_token_balance, simulate};
+use astroport::router::{
+    ExecuteMsg as RouterExecuteMsg, QueryMsg as RouterQueryMsg, SimulateSwapOperationsResponse,
+    SwapOperation,
+};
 use cw20::Cw20ExecuteMsg;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -22,6 +43,11 @@ pub fn instantiate(
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
+    let router = msg
+        .router
+        .map(|router| deps.api.addr_canonicalize(&router))
+        .transpose()?;
+
     store_config(
         deps.storage,
         &Config {
@@ -30,8 +56,10 @@ pub fn instantiate(
             anchor_token: deps.api.addr_canonicalize(&msg.anchor_token)?,
             reward_factor: msg.reward_factor,
             max_spread: msg.max_spread,
+            router,
         },
     )?;
+    store_total_stats(deps.storage, &EpochStats::default())?;
 
     Ok(Response::default())
 }
@@ -44,6 +72,7 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             gov_contract,
             astroport_factory,
             max_spread,
+            router,
         } => update_config(
             deps,
             info,
@@ -51,8 +80,18 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             gov_contract,
             astroport_factory,
             max_spread,
+            router,
         ),
+        ExecuteMsg::ProposeNewOwner { owner } => propose_new_owner(deps, info, owner),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, info),
+        ExecuteMsg::DropOwnershipProposal {} => drop_ownership_proposal(deps, info),
         ExecuteMsg::Sweep { denom } => sweep(deps, env, denom),
+        ExecuteMsg::RegisterNativePair { denom, pair } => {
+            register_native_pair(deps, info, denom, pair)
+        }
+        ExecuteMsg::RetryFailedSwaps {} => retry_failed_swaps(deps, env),
+        ExecuteMsg::RegisterRoute { asset, hops } => register_route(deps, info, asset, hops),
+        ExecuteMsg::RemoveRoute { asset } => remove_registered_route(deps, info, asset),
     }
 }
 
@@ -63,6 +102,7 @@ pub fn update_config(
     gov_contract: Option<String>,
     astroport_factory: Option<String>,
     max_spread: (bool, Option<Decimal>),
+    router: Option<String>,
 ) -> StdResult<Response> {
     let mut config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.gov_contract {
@@ -75,6 +115,8 @@ pub fn update_config(
 
     if let Some(gov_contract) = gov_contract {
         config.gov_contract = deps.api.addr_canonicalize(gov_contract.as_str())?;
+        // a proposal made by the previous gov contract is void
+        remove_pending_gov_contract(deps.storage);
     }
     if let Some(astroport_factory) = astroport_factory {
         config.astroport_factory = deps.api.addr_canonicalize(astroport_factory.as_str())?;
@@ -84,33 +126,193 @@ pub fn update_config(
         config.max_spread = max_spread.1
     }
 
+    if let Some(router) = router {
+        config.router = Some(deps.api.addr_canonicalize(&router)?);
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::default())
 }
 
+/// ProposeNewOwner
+/// Gov contract can propose a new gov contract,
+/// which takes over once it accepts the ownership
+pub fn propose_new_owner(deps: DepsMut, info: MessageInfo, owner: String) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.gov_contract {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    store_pending_gov_contract(deps.storage, &deps.api.addr_canonicalize(&owner)?)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "propose_new_owner"),
+        ("pending_owner", owner.as_str()),
+    ]))
+}
+
+pub fn accept_ownership(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if read_pending_gov_contract(deps.storage)? != Some(sender_raw.clone()) {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let mut config: Config = read_config(deps.storage)?;
+    config.gov_contract = sender_raw;
+    store_config(deps.storage, &config)?;
+    remove_pending_gov_contract(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "accept_ownership"),
+        ("owner", info.sender.as_str()),
+    ]))
+}
+
+/// DropOwnershipProposal
+/// Gov contract can drop the pending gov contract proposal
+pub fn drop_ownership_proposal(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.gov_contract {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    remove_pending_gov_contract(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![("action", "drop_ownership_proposal")]))
+}
+
+/// RegisterNativePair
+/// Gov contract can register the astroport pair of a native denom,
+/// which is used instead of the factory pair or the router to sweep it
+pub fn register_native_pair(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    pair: String,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.gov_contract {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    store_native_pair(deps.storage, &denom, &deps.api.addr_canonicalize(&pair)?)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "register_native_pair"),
+        ("denom", denom.as_str()),
+        ("pair", pair.as_str()),
+    ]))
+}
+
+/// RegisterRoute
+/// Gov contract can register the astroport pairs an asset is swept
+/// through when it has no ANC pair. The assets of each hop are read
+/// from its pair, the last hop must receive ANC
+pub fn register_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    hops: Vec<PairHop>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.gov_contract {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if hops.is_empty() || hops.len() > MAX_ROUTE_HOPS {
+        return Err(StdError::generic_err(format!(
+            "route must have 1 to {} hops",
+            MAX_ROUTE_HOPS
+        )));
+    }
+
+    let mut offer_asset = asset.clone();
+    let mut route_hops: Vec<RouteHop> = vec![];
+    for hop in hops.iter() {
+        let pair_addr = deps.api.addr_validate(&hop.pair)?;
+        let pair_info: PairInfo = deps
+            .querier
+            .query_wasm_smart(pair_addr.clone(), &AstroportQueryMsg::Pair {})?;
+
+        let [asset_info_0, asset_info_1] = pair_info.asset_infos;
+        let (offer_asset_info, ask_asset_info) = if asset_info_0.to_string() == offer_asset {
+            (asset_info_0, asset_info_1)
+        } else if asset_info_1.to_string() == offer_asset {
+            (asset_info_1, asset_info_0)
+        } else {
+            return Err(StdError::generic_err(format!(
+                "pair {} does not swap {}",
+                hop.pair, offer_asset
+            )));
+        };
+
+        route_hops.push(RouteHop {
+            pair: deps.api.addr_canonicalize(pair_addr.as_str())?,
+            offer_asset_info,
+        });
+        offer_asset = ask_asset_info.to_string();
+    }
+
+    if offer_asset != deps.api.addr_humanize(&config.anchor_token)?.as_str() {
+        return Err(StdError::generic_err("route must end in ANC"));
+    }
+
+    store_route(deps.storage, &asset, &route_hops)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "register_route"),
+        ("asset", asset.as_str()),
+        ("hops", &route_hops.len().to_string()),
+    ]))
+}
+
+/// RemoveRoute
+/// Gov contract can remove the registered route of an asset
+pub fn remove_registered_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.gov_contract {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if read_route(deps.storage, &asset)?.is_none() {
+        return Err(StdError::generic_err(format!("no route for {}", asset)));
+    }
+
+    remove_route(deps.storage, &asset);
+
+    Ok(Response::new().add_attributes(vec![("action", "remove_route"), ("asset", asset.as_str())]))
+}
+
 const SWEEP_REPLY_ID: u64 = 1;
 
+/// Reply of the first hop of a two hop route
+const ROUTE_HOP_REPLY_ID: u64 = 2;
+
+/// Maximum number of hops of a registered route
+const MAX_ROUTE_HOPS: usize = 2;
+
+/// Denom of the ANC pair that the router swaps other native denoms through
+const ROUTER_BASE_DENOM: &str = "uusd";
+
+/// Length of a statistics epoch, one week in seconds
+const EPOCH_LENGTH: u64 = 7 * 24 * 60 * 60;
+
 /// Sweep
 /// Anyone can execute sweep function to swap
 /// asset token => ANC token and distribute
-/// result ANC token to gov contract
+/// result ANC token to gov contract.
+/// The swap goes through the route of the denom, see swap_route
 pub fn sweep(deps: DepsMut, env: Env, denom: String) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
-    let anchor_token = deps.api.addr_humanize(&config.anchor_token)?;
-    let astroport_factory_addr = deps.api.addr_humanize(&config.astroport_factory)?;
+    if let Some(hops) = read_route(deps.storage, &denom)? {
+        return sweep_route(deps, env, &config, denom, hops);
+    }
 
-    let pair_info: PairInfo = query_pair_info(
-        &deps.querier,
-        astroport_factory_addr,
-        &[
-            AssetInfo::NativeToken {
-                denom: denom.to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: Addr::unchecked(anchor_token),
-            },
-        ],
-    )?;
+    let anchor_token = deps.api.addr_humanize(&config.anchor_token)?;
 
     let amount = query_balance(&deps.querier, env.contract.address, denom.to_string())?;
 
@@ -123,24 +325,42 @@ pub fn sweep(deps: DepsMut, env: Env, denom: String) -> StdResult<Response> {
 
     // deduct tax first
     let amount = (swap_asset.deduct_tax(&deps.querier)?).amount;
-    Ok(Response::new()
-        .add_submessage(SubMsg::reply_on_success(
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: pair_info.contract_addr.into_string(),
-                msg: to_binary(&AstroportExecuteMsg::Swap {
-                    offer_asset: Asset {
-                        amount,
-                        ..swap_asset
-                    },
-                    max_spread: config.max_spread,
-                    belief_price: None,
-                    to: None,
-                })?,
-                funds: vec![Coin {
-                    denom: denom.to_string(),
+    let funds = vec![Coin {
+        denom: denom.to_string(),
+        amount,
+    }];
+
+    // the reply of the swap records its outcome for this denom
+    store_tmp_sweep_denom(deps.storage, &denom)?;
+
+    let swap_msg = match swap_route(deps.as_ref(), &config, &denom)? {
+        SwapRoute::Router(router) => WasmMsg::Execute {
+            contract_addr: router.into_string(),
+            msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                operations: router_operations(&denom, anchor_token),
+                minimum_receive: None,
+                to: None,
+            })?,
+            funds,
+        },
+        SwapRoute::Pair(pair_addr) => WasmMsg::Execute {
+            contract_addr: pair_addr.into_string(),
+            msg: to_binary(&AstroportExecuteMsg::Swap {
+                offer_asset: Asset {
                     amount,
-                }],
-            }),
+                    ..swap_asset
+                },
+                max_spread: config.max_spread,
+                belief_price: None,
+                to: None,
+            })?,
+            funds,
+        },
+    };
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_always(
+            CosmosMsg::Wasm(swap_msg),
             SWEEP_REPLY_ID,
         ))
         .add_attributes(vec![
@@ -152,16 +372,263 @@ pub fn sweep(deps: DepsMut, env: Env, denom: String) -> StdResult<Response> {
         ]))
 }
 
+/// Sweep the balance of an asset through the hops of its registered route,
+/// the second hop is swapped from the reply of the first one
+fn sweep_route(
+    deps: DepsMut,
+    env: Env,
+    config: &Config,
+    denom: String,
+    hops: Vec<RouteHop>,
+) -> StdResult<Response> {
+    let amount = match &hops[0].offer_asset_info {
+        AssetInfo::NativeToken { denom } => {
+            query_balance(&deps.querier, env.contract.address, denom.to_string())?
+        }
+        AssetInfo::Token { contract_addr } => {
+            query_token_balance(&deps.querier, contract_addr.clone(), env.contract.address)?
+        }
+    };
+
+    // the replies of the swaps record their outcome for this denom
+    store_tmp_sweep_denom(deps.storage, &denom)?;
+
+    let (swap_msg, amount) = hop_swap_msg(deps.as_ref(), config, &hops[0], amount)?;
+    let reply_id = if hops.len() > 1 {
+        ROUTE_HOP_REPLY_ID
+    } else {
+        SWEEP_REPLY_ID
+    };
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_always(CosmosMsg::Wasm(swap_msg), reply_id))
+        .add_attributes(vec![
+            attr("action", "sweep"),
+            attr(
+                "collected_rewards",
+                format!("{:?}{:?}", amount.to_string(), denom),
+            ),
+        ]))
+}
+
+/// Swap the output of the first hop of the route of the denom
+/// through its second hop, the output is read from the swap events
+fn swap_next_hop(
+    deps: DepsMut,
+    denom: String,
+    res: SubMsgExecutionResponse,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let hops = read_route(deps.storage, &denom)?
+        .ok_or_else(|| StdError::generic_err(format!("no route for {}", denom)))?;
+
+    let return_amount = res
+        .events
+        .iter()
+        .filter(|event| event.ty == "wasm")
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == "return_amount")
+        .ok_or_else(|| StdError::generic_err("no return_amount in the swap events"))?;
+    let return_amount = Uint128::try_from(return_amount.value.as_str())?;
+
+    let (swap_msg, amount) = hop_swap_msg(deps.as_ref(), &config, &hops[1], return_amount)?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_always(
+            CosmosMsg::Wasm(swap_msg),
+            SWEEP_REPLY_ID,
+        ))
+        .add_attributes(vec![
+            ("action", "swap_hop"),
+            ("denom", denom.as_str()),
+            ("offer_amount", &amount.to_string()),
+        ]))
+}
+
+/// Swap message of the amount offered to the pair of the hop,
+/// returned with the offered amount once native tax is deducted
+fn hop_swap_msg(
+    deps: Deps,
+    config: &Config,
+    hop: &RouteHop,
+    amount: Uint128,
+) -> StdResult<(WasmMsg, Uint128)> {
+    let pair_addr = deps.api.addr_humanize(&hop.pair)?;
+    let offer_asset = deduct_hop_tax(
+        deps,
+        Asset {
+            info: hop.offer_asset_info.clone(),
+            amount,
+        },
+    )?;
+
+    let swap_msg = match &offer_asset.info {
+        AssetInfo::NativeToken { denom } => WasmMsg::Execute {
+            contract_addr: pair_addr.into_string(),
+            msg: to_binary(&AstroportExecuteMsg::Swap {
+                offer_asset: offer_asset.clone(),
+                max_spread: config.max_spread,
+                belief_price: None,
+                to: None,
+            })?,
+            funds: vec![Coin {
+                denom: denom.to_string(),
+                amount: offer_asset.amount,
+            }],
+        },
+        AssetInfo::Token { contract_addr } => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: pair_addr.into_string(),
+                amount: offer_asset.amount,
+                msg: to_binary(&AstroportCw20HookMsg::Swap {
+                    belief_price: None,
+                    max_spread: config.max_spread,
+                    to: None,
+                })?,
+            })?,
+            funds: vec![],
+        },
+    };
+
+    Ok((swap_msg, offer_asset.amount))
+}
+
+/// Deduct the tax of sending the asset when it is native
+fn deduct_hop_tax(deps: Deps, asset: Asset) -> StdResult<Asset> {
+    match asset.info {
+        AssetInfo::NativeToken { .. } => Ok(Asset {
+            amount: asset.deduct_tax(&deps.querier)?.amount,
+            ..asset
+        }),
+        AssetInfo::Token { .. } => Ok(asset),
+    }
+}
+
+/// Contract a denom is swapped through to ANC
+enum SwapRoute {
+    Router(Addr),
+    Pair(Addr),
+}
+
+/// Denoms with a registered pair are swapped through it,
+/// otherwise when a router is configured, denoms other than
+/// the router base denom are swapped through the router,
+/// and the rest through their ANC pair of the factory
+fn swap_route(deps: Deps, config: &Config, denom: &str) -> StdResult<SwapRoute> {
+    let native_pair = read_native_pair(deps.storage, denom)?;
+    match (&config.router, native_pair) {
+        (_, Some(pair)) => Ok(SwapRoute::Pair(deps.api.addr_humanize(&pair)?)),
+        (Some(router), None) if denom != ROUTER_BASE_DENOM => {
+            Ok(SwapRoute::Router(deps.api.addr_humanize(router)?))
+        }
+        _ => {
+            let pair_info: PairInfo = query_pair_info(
+                &deps.querier,
+                deps.api.addr_humanize(&config.astroport_factory)?,
+                &[
+                    AssetInfo::NativeToken {
+                        denom: denom.to_string(),
+                    },
+                    AssetInfo::Token {
+                        contract_addr: deps.api.addr_humanize(&config.anchor_token)?,
+                    },
+                ],
+            )?;
+
+            Ok(SwapRoute::Pair(pair_info.contract_addr))
+        }
+    }
+}
+
+/// Router operations swapping the denom to ANC through the router base denom
+fn router_operations(denom: &str, anchor_token: Addr) -> Vec<SwapOperation> {
+    vec![
+        SwapOperation::NativeSwap {
+            offer_denom: denom.to_string(),
+            ask_denom: ROUTER_BASE_DENOM.to_string(),
+        },
+        SwapOperation::AstroSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: ROUTER_BASE_DENOM.to_string(),
+            },
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: anchor_token,
+            },
+        },
+    ]
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
+    if msg.id == ROUTE_HOP_REPLY_ID {
+        let denom = read_tmp_sweep_denom(deps.storage)?
+            .ok_or_else(|| StdError::generic_err("no sweep in progress"))?;
+        return match msg.result {
+            ContractResult::Ok(res) => swap_next_hop(deps, denom, res),
+            ContractResult::Err(err) => fail_swap(deps, denom, err),
+        };
+    }
+
     if msg.id == SWEEP_REPLY_ID {
-        // send tokens on successful callback
-        return distribute(deps, env);
+        let denom = read_tmp_sweep_denom(deps.storage)?;
+        return match msg.result {
+            // send tokens on successful callback
+            ContractResult::Ok(_) => {
+                if let Some(denom) = denom {
+                    remove_failed_swap(deps.storage, &denom);
+                }
+
+                distribute(deps, env)
+            }
+            ContractResult::Err(err) => match denom {
+                Some(denom) => fail_swap(deps, denom, err),
+                None => Err(StdError::generic_err(err)),
+            },
+        };
     }
 
     Err(StdError::generic_err("not supported reply"))
 }
 
+/// Record the failed swap of the denom so that it can be retried,
+/// the swept balance stays in the contract
+fn fail_swap(deps: DepsMut, denom: String, error: String) -> StdResult<Response> {
+    let mut failed_swap = read_failed_swap(deps.storage, &denom)?;
+    failed_swap.failed_attempts += 1;
+    failed_swap.last_error = error;
+    store_failed_swap(deps.storage, &denom, &failed_swap)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "fail_swap"),
+        ("denom", denom.as_str()),
+        ("failed_attempts", &failed_swap.failed_attempts.to_string()),
+    ]))
+}
+
+/// RetryFailedSwaps
+/// Anyone can sweep again the denoms whose last swap failed,
+/// each denom is swept in its own message
+pub fn retry_failed_swaps(deps: DepsMut, env: Env) -> StdResult<Response> {
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut denoms: Vec<String> = vec![];
+    for (denom, _) in read_failed_swaps(deps.storage)? {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::Sweep {
+                denom: denom.clone(),
+            })?,
+            funds: vec![],
+        }));
+        denoms.push(denom);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "retry_failed_swaps"),
+        ("denoms", &denoms.join(",")),
+    ]))
+}
+
 // Only contract itself can execute distribute function
 pub fn distribute(deps: DepsMut, env: Env) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
@@ -174,6 +641,14 @@ pub fn distribute(deps: DepsMut, env: Env) -> StdResult<Response> {
     let distribute_amount = amount * config.reward_factor;
     let left_amount = amount.checked_sub(distribute_amount)?;
 
+    record_stats(
+        deps.storage,
+        env.block.time.seconds() / EPOCH_LENGTH,
+        amount,
+        distribute_amount,
+        left_amount,
+    )?;
+
     let mut messages: Vec<CosmosMsg> = vec![];
 
     if !distribute_amount.is_zero() {
@@ -205,10 +680,41 @@ pub fn distribute(deps: DepsMut, env: Env) -> StdResult<Response> {
     ]))
 }
 
+/// Accumulate the distributed amounts into the epoch and total statistics
+fn record_stats(
+    storage: &mut dyn Storage,
+    epoch: u64,
+    swept_in: Uint128,
+    anc_to_gov: Uint128,
+    anc_burned: Uint128,
+) -> StdResult<()> {
+    let mut epoch_stats = read_epoch_stats(storage, epoch)?;
+    epoch_stats.swept_in += swept_in;
+    epoch_stats.anc_to_gov += anc_to_gov;
+    epoch_stats.anc_burned += anc_burned;
+    store_epoch_stats(storage, epoch, &epoch_stats)?;
+
+    let mut total_stats = read_total_stats(storage)?;
+    total_stats.swept_in += swept_in;
+    total_stats.anc_to_gov += anc_to_gov;
+    total_stats.anc_burned += anc_burned;
+    store_total_stats(storage, &total_stats)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Stats { start_after, limit } => {
+            to_binary(&query_stats(deps, start_after, limit)?)
+        }
+        QueryMsg::TotalStats {} => to_binary(&query_total_stats(deps)?),
+        QueryMsg::RegisteredAssets {} => to_binary(&query_registered_assets(deps)?),
+        QueryMsg::FailedSwaps {} => to_binary(&query_failed_swaps(deps)?),
+        QueryMsg::SimulateBuyback { denom, amount } => {
+            to_binary(&query_simulate_buyback(deps, denom, amount)?)
+        }
+        QueryMsg::Routes {} => to_binary(&query_routes(deps)?),
     }
 }
 
@@ -223,11 +729,182 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         anchor_token: deps.api.addr_humanize(&state.anchor_token)?.to_string(),
         reward_factor: state.reward_factor,
         max_spread: state.max_spread,
+        router: state
+            .router
+            .map(|router| deps.api.addr_humanize(&router).map(|addr| addr.to_string()))
+            .transpose()?,
+        pending_gov_contract: read_pending_gov_contract(deps.storage)?
+            .map(|addr| deps.api.addr_humanize(&addr).map(|addr| addr.to_string()))
+            .transpose()?,
     };
 
     Ok(resp)
 }
 
+pub fn query_stats(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<StatsResponse> {
+    let stats = read_epoch_stats_range(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(epoch, stats)| EpochStatsResponse {
+            epoch,
+            swept_in: stats.swept_in,
+            anc_to_gov: stats.anc_to_gov,
+            anc_burned: stats.anc_burned,
+        })
+        .collect();
+
+    Ok(StatsResponse { stats })
+}
+
+pub fn query_total_stats(deps: Deps) -> StdResult<TotalStatsResponse> {
+    let stats = read_total_stats(deps.storage)?;
+    let resp = TotalStatsResponse {
+        swept_in: stats.swept_in,
+        anc_to_gov: stats.anc_to_gov,
+        anc_burned: stats.anc_burned,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_registered_assets(deps: Deps) -> StdResult<RegisteredAssetsResponse> {
+    let assets = read_native_pairs(deps.storage)?
+        .into_iter()
+        .map(|(denom, pair)| {
+            Ok(RegisteredAssetResponse {
+                denom,
+                pair: deps.api.addr_humanize(&pair)?.to_string(),
+            })
+        })
+        .collect::<StdResult<Vec<RegisteredAssetResponse>>>()?;
+
+    Ok(RegisteredAssetsResponse { assets })
+}
+
+pub fn query_failed_swaps(deps: Deps) -> StdResult<FailedSwapsResponse> {
+    let failed_swaps = read_failed_swaps(deps.storage)?
+        .into_iter()
+        .map(|(denom, failed_swap)| FailedSwapResponse {
+            denom,
+            failed_attempts: failed_swap.failed_attempts,
+            last_error: failed_swap.last_error,
+        })
+        .collect();
+
+    Ok(FailedSwapsResponse { failed_swaps })
+}
+
+/// Simulate the sweep swap of the amount of denom through its route,
+/// the amount is taxed as the swept balance would be
+pub fn query_simulate_buyback(
+    deps: Deps,
+    denom: String,
+    amount: Uint128,
+) -> StdResult<SimulateBuybackResponse> {
+    if let Some(hops) = read_route(deps.storage, &denom)? {
+        return simulate_route(deps, &hops, amount);
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let route = swap_route(deps, &config, &denom)
+        .map_err(|_| StdError::generic_err(format!("no swap route for {}", denom)))?;
+
+    let swap_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        },
+        amount,
+    };
+    let offer_asset = Asset {
+        amount: swap_asset.deduct_tax(&deps.querier)?.amount,
+        ..swap_asset
+    };
+
+    let resp = match route {
+        SwapRoute::Router(router) => {
+            let res: SimulateSwapOperationsResponse = deps.querier.query_wasm_smart(
+                router,
+                &RouterQueryMsg::SimulateSwapOperations {
+                    offer_amount: offer_asset.amount,
+                    operations: router_operations(
+                        &denom,
+                        deps.api.addr_humanize(&config.anchor_token)?,
+                    ),
+                },
+            )?;
+
+            // the router does not report the spread of its operations
+            SimulateBuybackResponse {
+                expected_return: res.amount,
+                spread: None,
+            }
+        }
+        SwapRoute::Pair(pair) => {
+            let res = simulate(&deps.querier, pair, &offer_asset)?;
+            SimulateBuybackResponse {
+                expected_return: res.return_amount,
+                spread: Some(res.spread_amount),
+            }
+        }
+    };
+
+    Ok(resp)
+}
+
+/// Simulate the hops of a registered route one after the other,
+/// their spreads are in different assets and are not reported
+fn simulate_route(
+    deps: Deps,
+    hops: &[RouteHop],
+    amount: Uint128,
+) -> StdResult<SimulateBuybackResponse> {
+    let mut amount = amount;
+    for hop in hops.iter() {
+        let offer_asset = deduct_hop_tax(
+            deps,
+            Asset {
+                info: hop.offer_asset_info.clone(),
+                amount,
+            },
+        )?;
+        amount = simulate(
+            &deps.querier,
+            deps.api.addr_humanize(&hop.pair)?,
+            &offer_asset,
+        )?
+        .return_amount;
+    }
+
+    Ok(SimulateBuybackResponse {
+        expected_return: amount,
+        spread: None,
+    })
+}
+
+pub fn query_routes(deps: Deps) -> StdResult<RoutesResponse> {
+    let routes = read_routes(deps.storage)?
+        .into_iter()
+        .map(|(asset, hops)| {
+            Ok(RouteResponse {
+                asset,
+                hops: hops
+                    .iter()
+                    .map(|hop| {
+                        Ok(PairHop {
+                            pair: deps.api.addr_humanize(&hop.pair)?.to_string(),
+                        })
+                    })
+                    .collect::<StdResult<Vec<PairHop>>>()?,
+            })
+        })
+        .collect::<StdResult<Vec<RouteResponse>>>()?;
+
+    Ok(RoutesResponse { routes })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
     //migrate config
@@ -237,5 +914,8 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response>
         msg.max_spread,
     )?;
 
+    // start collecting statistics from the migration
+    store_total_stats(deps.storage, &EpochStats::default())?;
+
     Ok(Response::default())
 }
@@ -33,6 +33,7 @@ pub fn migrate_config(
             anchor_token: legacy_config.anchor_token,
             reward_factor: legacy_config.reward_factor,
             max_spread: Some(max_spread),
+            router: None,
         },
     )
 }
@@ -10,8 +10,10 @@ use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg};
 
 use std::collections::HashMap;
 
-use astroport::asset::{AssetInfo, PairInfo};
+use astroport::asset::{Asset, AssetInfo, PairInfo};
 use astroport::factory::PairType;
+use astroport::pair::SimulationResponse;
+use astroport::router::{SimulateSwapOperationsResponse, SwapOperation};
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -34,6 +36,7 @@ pub struct WasmMockQuerier {
     token_querier: TokenQuerier,
     tax_querier: TaxQuerier,
     astroport_factory_querier: AstroportFactoryQuerier,
+    swap_simulation_querier: SwapSimulationQuerier,
 }
 
 #[derive(Clone, Default)]
@@ -92,12 +95,15 @@ pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint1
 #[derive(Clone, Default)]
 pub struct AstroportFactoryQuerier {
     pairs: HashMap<String, String>,
+    // assets of each pair contract
+    pair_infos: HashMap<String, [AssetInfo; 2]>,
 }
 
 impl AstroportFactoryQuerier {
     pub fn new(pairs: &[(&String, &String)]) -> Self {
         AstroportFactoryQuerier {
             pairs: pairs_to_map(pairs),
+            pair_infos: HashMap::new(),
         }
     }
 }
@@ -110,6 +116,25 @@ pub(crate) fn pairs_to_map(pairs: &[(&String, &String)]) -> HashMap<String, Stri
     pairs_map
 }
 
+#[derive(Clone, Default)]
+pub struct SwapSimulationQuerier {
+    // price and spread amount of each pair or router
+    simulations: HashMap<String, (Decimal, Uint128)>,
+}
+
+impl SwapSimulationQuerier {
+    pub fn new(simulations: &[(&String, &Decimal, &Uint128)]) -> Self {
+        let mut simulations_map: HashMap<String, (Decimal, Uint128)> = HashMap::new();
+        for (contract_addr, price, spread_amount) in simulations.iter() {
+            simulations_map.insert(contract_addr.to_string(), (**price, **spread_amount));
+        }
+
+        SwapSimulationQuerier {
+            simulations: simulations_map,
+        }
+    }
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
@@ -129,7 +154,17 @@ impl Querier for WasmMockQuerier {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    Pair { asset_infos: [AssetInfo; 2] },
+    /// Pair of the factory when asset_infos are given, else info of the pair contract
+    Pair {
+        asset_infos: Option<[AssetInfo; 2]>,
+    },
+    Simulation {
+        offer_asset: Asset,
+    },
+    SimulateSwapOperations {
+        offer_amount: Uint128,
+        operations: Vec<SwapOperation>,
+    },
 }
 
 impl WasmMockQuerier {
@@ -161,7 +196,25 @@ impl WasmMockQuerier {
                 }
             }
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => match from_binary(msg) {
-                Ok(QueryMsg::Pair { asset_infos }) => {
+                Ok(QueryMsg::Pair { asset_infos: None }) => {
+                    match self.astroport_factory_querier.pair_infos.get(contract_addr) {
+                        Some(asset_infos) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
+                                contract_addr: Addr::unchecked(contract_addr),
+                                liquidity_token: Addr::unchecked("liquidity".to_string()),
+                                asset_infos: asset_infos.clone(),
+                                pair_type: PairType::Xyk {},
+                            })))
+                        }
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No pair info exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    }
+                }
+                Ok(QueryMsg::Pair {
+                    asset_infos: Some(asset_infos),
+                }) => {
                     let key = asset_infos[0].to_string() + asset_infos[1].to_string().as_str();
                     match self.astroport_factory_querier.pairs.get(&key) {
                         Some(v) => SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
@@ -183,6 +236,34 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                Ok(QueryMsg::Simulation { offer_asset }) => {
+                    match self.swap_simulation_querier.simulations.get(contract_addr) {
+                        Some((price, spread_amount)) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(&SimulationResponse {
+                                return_amount: offer_asset.amount * *price,
+                                spread_amount: *spread_amount,
+                                commission_amount: Uint128::zero(),
+                            })))
+                        }
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No simulation exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    }
+                }
+                Ok(QueryMsg::SimulateSwapOperations { offer_amount, .. }) => {
+                    match self.swap_simulation_querier.simulations.get(contract_addr) {
+                        Some((price, _)) => SystemResult::Ok(ContractResult::from(to_binary(
+                            &SimulateSwapOperationsResponse {
+                                amount: offer_amount * *price,
+                            },
+                        ))),
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No simulation exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    }
+                }
                 _ => match from_binary(msg).unwrap() {
                     Cw20QueryMsg::Balance { address } => {
                         let balances: &HashMap<String, Uint128> =
@@ -230,6 +311,7 @@ impl WasmMockQuerier {
             token_querier: TokenQuerier::default(),
             tax_querier: TaxQuerier::default(),
             astroport_factory_querier: AstroportFactoryQuerier::default(),
+            swap_simulation_querier: SwapSimulationQuerier::default(),
         }
     }
 
@@ -247,4 +329,18 @@ impl WasmMockQuerier {
     pub fn with_astroport_pairs(&mut self, pairs: &[(&String, &String)]) {
         self.astroport_factory_querier = AstroportFactoryQuerier::new(pairs);
     }
+
+    // configure the assets of pair contracts
+    pub fn with_pair_infos(&mut self, pair_infos: &[(&String, &[AssetInfo; 2])]) {
+        for (pair, asset_infos) in pair_infos.iter() {
+            self.astroport_factory_querier
+                .pair_infos
+                .insert(pair.to_string(), (*asset_infos).clone());
+        }
+    }
+
+    // configure the swap simulations of pairs and routers
+    pub fn with_swap_simulations(&mut self, simulations: &[(&String, &Decimal, &Uint128)]) {
+        self.swap_simulation_querier = SwapSimulationQuerier::new(simulations);
+    }
 }
@@ -1,10 +1,20 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, Decimal, StdResult, Storage};
-use cosmwasm_storage::{singleton, singleton_read};
+use astroport::asset::AssetInfo;
+use cosmwasm_std::{CanonicalAddr, Decimal, Order, StdResult, Storage, Uint128};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use std::convert::TryInto;
 
 pub static KEY_CONFIG: &[u8] = b"config";
+static KEY_TOTAL_STATS: &[u8] = b"total_stats";
+static KEY_PENDING_GOV_CONTRACT: &[u8] = b"pending_gov_contract";
+static KEY_TMP_SWEEP_DENOM: &[u8] = b"tmp_sweep_denom";
+
+static PREFIX_EPOCH_STATS: &[u8] = b"epoch_stats";
+static PREFIX_NATIVE_PAIR: &[u8] = b"native_pair";
+static PREFIX_FAILED_SWAP: &[u8] = b"failed_swap";
+static PREFIX_ROUTE: &[u8] = b"route";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -13,6 +23,7 @@ pub struct Config {
     pub anchor_token: CanonicalAddr,      // anchor token address
     pub reward_factor: Decimal, // reward distribution rate to gov contract, left rewards sent back to distributor contract
     pub max_spread: Option<Decimal>, // max spread for buybacks
+    pub router: Option<CanonicalAddr>, // astroport router for multi-hop buybacks
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -22,3 +33,191 @@ pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()>
 pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
+
+pub fn store_pending_gov_contract(
+    storage: &mut dyn Storage,
+    pending_gov_contract: &CanonicalAddr,
+) -> StdResult<()> {
+    singleton(storage, KEY_PENDING_GOV_CONTRACT).save(pending_gov_contract)
+}
+
+pub fn remove_pending_gov_contract(storage: &mut dyn Storage) {
+    singleton::<CanonicalAddr>(storage, KEY_PENDING_GOV_CONTRACT).remove()
+}
+
+pub fn read_pending_gov_contract(storage: &dyn Storage) -> StdResult<Option<CanonicalAddr>> {
+    singleton_read(storage, KEY_PENDING_GOV_CONTRACT).may_load()
+}
+
+pub fn store_tmp_sweep_denom(storage: &mut dyn Storage, denom: &str) -> StdResult<()> {
+    singleton(storage, KEY_TMP_SWEEP_DENOM).save(&denom.to_string())
+}
+
+pub fn read_tmp_sweep_denom(storage: &dyn Storage) -> StdResult<Option<String>> {
+    singleton_read(storage, KEY_TMP_SWEEP_DENOM).may_load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct FailedSwap {
+    pub failed_attempts: u64, // consecutive failed swaps
+    pub last_error: String,
+}
+
+pub fn store_failed_swap(
+    storage: &mut dyn Storage,
+    denom: &str,
+    failed_swap: &FailedSwap,
+) -> StdResult<()> {
+    let mut failed_swap_bucket: Bucket<FailedSwap> = Bucket::new(storage, PREFIX_FAILED_SWAP);
+    failed_swap_bucket.save(denom.as_bytes(), failed_swap)
+}
+
+pub fn remove_failed_swap(storage: &mut dyn Storage, denom: &str) {
+    let mut failed_swap_bucket: Bucket<FailedSwap> = Bucket::new(storage, PREFIX_FAILED_SWAP);
+    failed_swap_bucket.remove(denom.as_bytes())
+}
+
+pub fn read_failed_swap(storage: &dyn Storage, denom: &str) -> StdResult<FailedSwap> {
+    let failed_swap_bucket: ReadonlyBucket<FailedSwap> =
+        ReadonlyBucket::new(storage, PREFIX_FAILED_SWAP);
+    Ok(failed_swap_bucket
+        .may_load(denom.as_bytes())?
+        .unwrap_or_default())
+}
+
+pub fn read_failed_swaps(storage: &dyn Storage) -> StdResult<Vec<(String, FailedSwap)>> {
+    let failed_swap_bucket: ReadonlyBucket<FailedSwap> =
+        ReadonlyBucket::new(storage, PREFIX_FAILED_SWAP);
+    failed_swap_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((String::from_utf8(k)?, v))
+        })
+        .collect()
+}
+
+pub fn store_native_pair(
+    storage: &mut dyn Storage,
+    denom: &str,
+    pair: &CanonicalAddr,
+) -> StdResult<()> {
+    let mut native_pair_bucket: Bucket<CanonicalAddr> = Bucket::new(storage, PREFIX_NATIVE_PAIR);
+    native_pair_bucket.save(denom.as_bytes(), pair)
+}
+
+pub fn read_native_pair(storage: &dyn Storage, denom: &str) -> StdResult<Option<CanonicalAddr>> {
+    let native_pair_bucket: ReadonlyBucket<CanonicalAddr> =
+        ReadonlyBucket::new(storage, PREFIX_NATIVE_PAIR);
+    native_pair_bucket.may_load(denom.as_bytes())
+}
+
+pub fn read_native_pairs(storage: &dyn Storage) -> StdResult<Vec<(String, CanonicalAddr)>> {
+    let native_pair_bucket: ReadonlyBucket<CanonicalAddr> =
+        ReadonlyBucket::new(storage, PREFIX_NATIVE_PAIR);
+    native_pair_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((String::from_utf8(k)?, v))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RouteHop {
+    pub pair: CanonicalAddr,         // astroport pair of the hop
+    pub offer_asset_info: AssetInfo, // asset offered to the pair
+}
+
+pub fn store_route(storage: &mut dyn Storage, asset: &str, hops: &[RouteHop]) -> StdResult<()> {
+    let mut route_bucket: Bucket<Vec<RouteHop>> = Bucket::new(storage, PREFIX_ROUTE);
+    route_bucket.save(asset.as_bytes(), &hops.to_vec())
+}
+
+pub fn remove_route(storage: &mut dyn Storage, asset: &str) {
+    let mut route_bucket: Bucket<Vec<RouteHop>> = Bucket::new(storage, PREFIX_ROUTE);
+    route_bucket.remove(asset.as_bytes())
+}
+
+pub fn read_route(storage: &dyn Storage, asset: &str) -> StdResult<Option<Vec<RouteHop>>> {
+    let route_bucket: ReadonlyBucket<Vec<RouteHop>> = ReadonlyBucket::new(storage, PREFIX_ROUTE);
+    route_bucket.may_load(asset.as_bytes())
+}
+
+pub fn read_routes(storage: &dyn Storage) -> StdResult<Vec<(String, Vec<RouteHop>)>> {
+    let route_bucket: ReadonlyBucket<Vec<RouteHop>> = ReadonlyBucket::new(storage, PREFIX_ROUTE);
+    route_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((String::from_utf8(k)?, v))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct EpochStats {
+    pub swept_in: Uint128,   // ANC collected for distribution
+    pub anc_to_gov: Uint128, // ANC sent to gov contract
+    pub anc_burned: Uint128, // ANC burned
+}
+
+pub fn store_total_stats(storage: &mut dyn Storage, stats: &EpochStats) -> StdResult<()> {
+    singleton(storage, KEY_TOTAL_STATS).save(stats)
+}
+
+pub fn read_total_stats(storage: &dyn Storage) -> StdResult<EpochStats> {
+    Ok(singleton_read(storage, KEY_TOTAL_STATS)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_epoch_stats(
+    storage: &mut dyn Storage,
+    epoch: u64,
+    stats: &EpochStats,
+) -> StdResult<()> {
+    let mut epoch_stats_bucket: Bucket<EpochStats> = Bucket::new(storage, PREFIX_EPOCH_STATS);
+    epoch_stats_bucket.save(&epoch.to_be_bytes(), stats)
+}
+
+pub fn read_epoch_stats(storage: &dyn Storage, epoch: u64) -> StdResult<EpochStats> {
+    let epoch_stats_bucket: ReadonlyBucket<EpochStats> =
+        ReadonlyBucket::new(storage, PREFIX_EPOCH_STATS);
+    Ok(epoch_stats_bucket
+        .may_load(&epoch.to_be_bytes())?
+        .unwrap_or_default())
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_epoch_stats_range(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, EpochStats)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    let epoch_stats_bucket: ReadonlyBucket<EpochStats> =
+        ReadonlyBucket::new(storage, PREFIX_EPOCH_STATS);
+    epoch_stats_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((u64::from_be_bytes(k.as_slice().try_into().unwrap()), v))
+        })
+        .collect()
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<u64>) -> Option<Vec<u8>> {
+    start_after.map(|epoch| {
+        let mut v = epoch.to_be_bytes().to_vec();
+        v.push(1);
+        v
+    })
+}
@@ -1,12 +1,20 @@
-use crate::contract::{execute, instantiate, query_config, reply};
+use crate::contract::{
+    execute, instantiate, query_config, query_failed_swaps, query_registered_assets, query_routes,
+    query_simulate_buyback, query_stats, query_total_stats, reply,
+};
 use crate::mock_querier::mock_dependencies;
-use anchor_token::collector::{ConfigResponse, ExecuteMsg, InstantiateMsg};
+use anchor_token::collector::{
+    ConfigResponse, EpochStatsResponse, ExecuteMsg, FailedSwapResponse, FailedSwapsResponse,
+    InstantiateMsg, PairHop, RegisteredAssetResponse, RegisteredAssetsResponse, RouteResponse,
+    RoutesResponse, SimulateBuybackResponse, StatsResponse, TotalStatsResponse,
+};
 use astroport::asset::{Asset, AssetInfo};
-use astroport::pair::ExecuteMsg as AstroportExecuteMsg;
+use astroport::pair::{Cw20HookMsg as AstroportCw20HookMsg, ExecuteMsg as AstroportExecuteMsg};
+use astroport::router::{ExecuteMsg as RouterExecuteMsg, SwapOperation};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    to_binary, Coin, ContractResult, CosmosMsg, Decimal, Reply, ReplyOn, StdError, SubMsg,
-    SubMsgExecutionResponse, Uint128, WasmMsg,
+    attr, to_binary, Addr, Coin, ContractResult, CosmosMsg, Decimal, Event, Reply, ReplyOn,
+    StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 
@@ -20,6 +28,7 @@ fn proper_initialization() {
         anchor_token: "tokenANC".to_string(),
         reward_factor: Decimal::percent(90),
         max_spread: Default::default(),
+        router: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -42,6 +51,7 @@ fn update_config() {
         anchor_token: "tokenANC".to_string(),
         reward_factor: Decimal::percent(90),
         max_spread: Default::default(),
+        router: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -54,6 +64,7 @@ fn update_config() {
         gov_contract: Some("new_gov".to_string()),
         astroport_factory: Some("new_astroport_factory".to_string()),
         max_spread: (true, Some(Decimal::percent(10))),
+        router: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -73,6 +84,7 @@ fn update_config() {
         gov_contract: None,
         astroport_factory: None,
         max_spread: (true, None),
+        router: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -92,8 +104,84 @@ fn update_config() {
         gov_contract: Some("new_gov".to_string()),
         astroport_factory: Some("new_astroport_factory".to_string()),
         max_spread: (false, None),
+        router: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+}
+
+#[test]
+fn transfer_ownership() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: "astroportfactory".to_string(),
+        gov_contract: "gov".to_string(),
+        anchor_token: "tokenANC".to_string(),
+        reward_factor: Decimal::percent(90),
+        max_spread: Default::default(),
+        router: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "new_gov".to_string(),
     };
+    let info = mock_info("new_gov", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("gov", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let value = query_config(deps.as_ref()).unwrap();
+    assert_eq!(value.gov_contract, "gov".to_string());
+    assert_eq!(value.pending_gov_contract, Some("new_gov".to_string()));
+
+    // only the proposed gov contract can accept
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("new_gov", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap();
 
+    let value = query_config(deps.as_ref()).unwrap();
+    assert_eq!(value.gov_contract, "new_gov".to_string());
+    assert_eq!(value.pending_gov_contract, None);
+
+    // the previous gov contract lost its privileges
+    let info = mock_info("gov", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        reward_factor: Some(Decimal::percent(80)),
+        gov_contract: None,
+        astroport_factory: None,
+        max_spread: (false, None),
+        router: None,
+    };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
         Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
@@ -101,6 +189,97 @@ fn update_config() {
     }
 }
 
+#[test]
+fn drop_ownership_proposal() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: "astroportfactory".to_string(),
+        gov_contract: "gov".to_string(),
+        anchor_token: "tokenANC".to_string(),
+        reward_factor: Decimal::percent(90),
+        max_spread: Default::default(),
+        router: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let propose_msg = ExecuteMsg::ProposeNewOwner {
+        owner: "new_gov".to_string(),
+    };
+    let info = mock_info("gov", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, propose_msg.clone()).unwrap();
+
+    // the gov contract changes directly
+    let info = mock_info("gov", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        reward_factor: None,
+        gov_contract: Some("other_gov".to_string()),
+        astroport_factory: None,
+        max_spread: (false, None),
+        router: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the stale proposal cannot be accepted
+    let value = query_config(deps.as_ref()).unwrap();
+    assert_eq!(value.pending_gov_contract, None);
+
+    let info = mock_info("new_gov", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // only the gov contract can drop a proposal
+    let info = mock_info("other_gov", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, propose_msg).unwrap();
+
+    let info = mock_info("gov", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DropOwnershipProposal {},
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("other_gov", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DropOwnershipProposal {},
+    )
+    .unwrap();
+
+    let info = mock_info("new_gov", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let value = query_config(deps.as_ref()).unwrap();
+    assert_eq!(value.gov_contract, "other_gov".to_string());
+    assert_eq!(value.pending_gov_contract, None);
+}
+
 #[test]
 fn test_sweep() {
     let mut deps = mock_dependencies(&[Coin {
@@ -122,6 +301,7 @@ fn test_sweep() {
         anchor_token: "tokenANC".to_string(),
         reward_factor: Decimal::percent(90),
         max_spread: Some(Decimal::percent(10)),
+        router: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -159,9 +339,612 @@ fn test_sweep() {
             .into(),
             gas_limit: None,
             id: 1,
-            reply_on: ReplyOn::Success,
+            reply_on: ReplyOn::Always,
+        }]
+    );
+}
+
+#[test]
+fn test_sweep_with_router() {
+    let mut deps = mock_dependencies(&[
+        Coin {
+            denom: "ukrw".to_string(),
+            amount: Uint128::from(100u128),
+        },
+        Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100u128),
+        },
+    ]);
+
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[
+            (&"ukrw".to_string(), &Uint128::from(1000000u128)),
+            (&"uusd".to_string(), &Uint128::from(1000000u128)),
+        ],
+    );
+
+    deps.querier
+        .with_astroport_pairs(&[(&"uusdtokenANC".to_string(), &"pairANC".to_string())]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: "astroportfactory".to_string(),
+        gov_contract: "gov".to_string(),
+        anchor_token: "tokenANC".to_string(),
+        reward_factor: Decimal::percent(90),
+        max_spread: Some(Decimal::percent(10)),
+        router: Some("router".to_string()),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let config: ConfigResponse = query_config(deps.as_ref()).unwrap();
+    assert_eq!(config.router, Some("router".to_string()));
+
+    // ukrw has no ANC pair, so it is swapped through uusd by the router
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::Sweep {
+        denom: "ukrw".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // tax deduct 100 => 99
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            msg: WasmMsg::Execute {
+                contract_addr: "router".to_string(),
+                msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                    operations: vec![
+                        SwapOperation::NativeSwap {
+                            offer_denom: "ukrw".to_string(),
+                            ask_denom: "uusd".to_string(),
+                        },
+                        SwapOperation::AstroSwap {
+                            offer_asset_info: AssetInfo::NativeToken {
+                                denom: "uusd".to_string()
+                            },
+                            ask_asset_info: AssetInfo::Token {
+                                contract_addr: Addr::unchecked("tokenANC"),
+                            },
+                        },
+                    ],
+                    minimum_receive: None,
+                    to: None,
+                })
+                .unwrap(),
+                funds: vec![Coin {
+                    denom: "ukrw".to_string(),
+                    amount: Uint128::from(99u128),
+                }],
+            }
+            .into(),
+            gas_limit: None,
+            id: 1,
+            reply_on: ReplyOn::Always,
+        }]
+    );
+
+    // uusd keeps using the direct pair
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::Sweep {
+        denom: "uusd".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => {
+            assert_eq!(contract_addr, "pairANC")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn test_sweep_with_native_pair() {
+    let ibc_denom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+    let mut deps = mock_dependencies(&[Coin {
+        denom: ibc_denom.to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+
+    deps.querier.with_tax(Decimal::percent(1), &[]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: "astroportfactory".to_string(),
+        gov_contract: "gov".to_string(),
+        anchor_token: "tokenANC".to_string(),
+        reward_factor: Decimal::percent(90),
+        max_spread: Some(Decimal::percent(10)),
+        router: Some("router".to_string()),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only the gov contract can register pairs
+    let msg = ExecuteMsg::RegisterNativePair {
+        denom: ibc_denom.to_string(),
+        pair: "pairIBC".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap();
+
+    let res = query_registered_assets(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        RegisteredAssetsResponse {
+            assets: vec![RegisteredAssetResponse {
+                denom: ibc_denom.to_string(),
+                pair: "pairIBC".to_string(),
+            }],
+        }
+    );
+
+    // the registered pair is used instead of the router
+    let msg = ExecuteMsg::Sweep {
+        denom: ibc_denom.to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            msg: WasmMsg::Execute {
+                contract_addr: "pairIBC".to_string(),
+                msg: to_binary(&AstroportExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: ibc_denom.to_string()
+                        },
+                        amount: Uint128::from(100u128),
+                    },
+                    max_spread: Some(Decimal::percent(10)),
+                    belief_price: None,
+                    to: None,
+                })
+                .unwrap(),
+                funds: vec![Coin {
+                    denom: ibc_denom.to_string(),
+                    amount: Uint128::from(100u128),
+                }],
+            }
+            .into(),
+            gas_limit: None,
+            id: 1,
+            reply_on: ReplyOn::Always,
+        }]
+    );
+}
+
+#[test]
+fn test_simulate_buyback() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[
+            (&"ukrw".to_string(), &Uint128::from(1000000u128)),
+            (&"uusd".to_string(), &Uint128::from(1000000u128)),
+        ],
+    );
+
+    deps.querier
+        .with_astroport_pairs(&[(&"uusdtokenANC".to_string(), &"pairANC".to_string())]);
+    deps.querier.with_swap_simulations(&[
+        (
+            &"pairANC".to_string(),
+            &Decimal::percent(200),
+            &Uint128::from(3u128),
+        ),
+        (
+            &"router".to_string(),
+            &Decimal::percent(10),
+            &Uint128::zero(),
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: "astroportfactory".to_string(),
+        gov_contract: "gov".to_string(),
+        anchor_token: "tokenANC".to_string(),
+        reward_factor: Decimal::percent(90),
+        max_spread: Some(Decimal::percent(10)),
+        router: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // tax deduct 100 => 99, swapped at 2 ANC per uusd
+    let res =
+        query_simulate_buyback(deps.as_ref(), "uusd".to_string(), Uint128::from(100u128)).unwrap();
+    assert_eq!(
+        res,
+        SimulateBuybackResponse {
+            expected_return: Uint128::from(198u128),
+            spread: Some(Uint128::from(3u128)),
+        }
+    );
+
+    // ukrw has neither a pair nor a router
+    match query_simulate_buyback(deps.as_ref(), "ukrw".to_string(), Uint128::from(100u128)) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "no swap route for ukrw"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        reward_factor: None,
+        gov_contract: None,
+        astroport_factory: None,
+        max_spread: (false, None),
+        router: Some("router".to_string()),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap();
+
+    // ukrw is simulated through the router, which reports no spread
+    let res =
+        query_simulate_buyback(deps.as_ref(), "ukrw".to_string(), Uint128::from(100u128)).unwrap();
+    assert_eq!(
+        res,
+        SimulateBuybackResponse {
+            expected_return: Uint128::from(9u128),
+            spread: None,
+        }
+    );
+}
+
+#[test]
+fn test_sweep_with_route() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    deps.querier.with_token_balances(&[(
+        &"tokenMINOR".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000u128))],
+    )]);
+
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    deps.querier.with_pair_infos(&[
+        (
+            &"pairMINOR".to_string(),
+            &[
+                AssetInfo::Token {
+                    contract_addr: Addr::unchecked("tokenMINOR"),
+                },
+                uusd.clone(),
+            ],
+        ),
+        (
+            &"pairANC".to_string(),
+            &[
+                uusd.clone(),
+                AssetInfo::Token {
+                    contract_addr: Addr::unchecked("tokenANC"),
+                },
+            ],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: "astroportfactory".to_string(),
+        gov_contract: "gov".to_string(),
+        anchor_token: "tokenANC".to_string(),
+        reward_factor: Decimal::percent(90),
+        max_spread: Some(Decimal::percent(10)),
+        router: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let hops = vec![
+        PairHop {
+            pair: "pairMINOR".to_string(),
+        },
+        PairHop {
+            pair: "pairANC".to_string(),
+        },
+    ];
+
+    // only the gov contract can register routes
+    let msg = ExecuteMsg::RegisterRoute {
+        asset: "tokenMINOR".to_string(),
+        hops: hops.clone(),
+    };
+    match execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::RegisterRoute {
+        asset: "tokenMINOR".to_string(),
+        hops: vec![hops[0].clone(), hops[1].clone(), hops[1].clone()],
+    };
+    match execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "route must have 1 to 2 hops"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::RegisterRoute {
+        asset: "tokenMINOR".to_string(),
+        hops: vec![hops[0].clone()],
+    };
+    match execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "route must end in ANC"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::RegisterRoute {
+        asset: "tokenMINOR".to_string(),
+        hops: vec![hops[1].clone()],
+    };
+    match execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "pair pairANC does not swap tokenMINOR")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::RegisterRoute {
+        asset: "tokenMINOR".to_string(),
+        hops: hops.clone(),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg).unwrap();
+
+    let res = query_routes(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        RoutesResponse {
+            routes: vec![RouteResponse {
+                asset: "tokenMINOR".to_string(),
+                hops,
+            }],
+        }
+    );
+
+    // the first hop sends the token to its pair
+    let msg = ExecuteMsg::Sweep {
+        denom: "tokenMINOR".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            msg: WasmMsg::Execute {
+                contract_addr: "tokenMINOR".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "pairMINOR".to_string(),
+                    amount: Uint128::from(1000u128),
+                    msg: to_binary(&AstroportCw20HookMsg::Swap {
+                        belief_price: None,
+                        max_spread: Some(Decimal::percent(10)),
+                        to: None,
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }
+            .into(),
+            gas_limit: None,
+            id: 2,
+            reply_on: ReplyOn::Always,
+        }]
+    );
+
+    // the output of the first hop is swapped by the second one, tax deduct 101 => 100
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 2,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![Event::new("wasm")
+                    .add_attribute("action", "swap")
+                    .add_attribute("return_amount", "101")],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            msg: WasmMsg::Execute {
+                contract_addr: "pairANC".to_string(),
+                msg: to_binary(&AstroportExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: uusd,
+                        amount: Uint128::from(100u128),
+                    },
+                    max_spread: Some(Decimal::percent(10)),
+                    belief_price: None,
+                    to: None,
+                })
+                .unwrap(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100u128),
+                }],
+            }
+            .into(),
+            gas_limit: None,
+            id: 1,
+            reply_on: ReplyOn::Always,
         }]
     );
+
+    // a failed first hop is recorded for the swept asset
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 2,
+            result: ContractResult::Err("pool is paused".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "fail_swap"),
+            attr("denom", "tokenMINOR"),
+            attr("failed_attempts", "1"),
+        ]
+    );
+
+    let msg = ExecuteMsg::RemoveRoute {
+        asset: "tokenMINOR".to_string(),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        query_routes(deps.as_ref()).unwrap(),
+        RoutesResponse { routes: vec![] }
+    );
+    match execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "no route for tokenMINOR"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn test_retry_failed_swaps() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    deps.querier
+        .with_astroport_pairs(&[(&"uusdtokenANC".to_string(), &"pairANC".to_string())]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: "astroportfactory".to_string(),
+        gov_contract: "gov".to_string(),
+        anchor_token: "tokenANC".to_string(),
+        reward_factor: Decimal::percent(90),
+        max_spread: Some(Decimal::percent(10)),
+        router: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let sweep_msg = ExecuteMsg::Sweep {
+        denom: "uusd".to_string(),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        sweep_msg.clone(),
+    )
+    .unwrap();
+
+    // the swap fails, e.g. the pool is paused
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1,
+            result: ContractResult::Err("pool is paused".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "fail_swap"),
+            attr("denom", "uusd"),
+            attr("failed_attempts", "1"),
+        ]
+    );
+    assert!(res.messages.is_empty());
+
+    let res = query_failed_swaps(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        FailedSwapsResponse {
+            failed_swaps: vec![FailedSwapResponse {
+                denom: "uusd".to_string(),
+                failed_attempts: 1,
+                last_error: "pool is paused".to_string(),
+            }],
+        }
+    );
+
+    // anyone can retry the failed swaps
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::RetryFailedSwaps {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&sweep_msg).unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // the retried swap succeeds
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        sweep_msg,
+    )
+    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &"tokenANC".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+
+    let res = query_failed_swaps(deps.as_ref()).unwrap();
+    assert_eq!(res.failed_swaps, vec![]);
 }
 
 #[test]
@@ -179,6 +962,7 @@ fn test_distribute() {
         anchor_token: "tokenANC".to_string(),
         reward_factor: Decimal::percent(90),
         max_spread: Some(Decimal::percent(10)),
+        router: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -216,3 +1000,82 @@ fn test_distribute() {
         ]
     )
 }
+
+#[test]
+fn test_distribute_stats() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: "astroportfactory".to_string(),
+        gov_contract: "gov".to_string(),
+        anchor_token: "tokenANC".to_string(),
+        reward_factor: Decimal::percent(90),
+        max_spread: Some(Decimal::percent(10)),
+        router: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    // first week
+    deps.querier.with_token_balances(&[(
+        &"tokenANC".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    let env = mock_env();
+    let _res = reply(deps.as_mut(), env.clone(), reply_msg.clone()).unwrap();
+
+    // next week
+    deps.querier.with_token_balances(&[(
+        &"tokenANC".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200u128))],
+    )]);
+    let mut env = env;
+    env.block.time = env.block.time.plus_seconds(7 * 24 * 60 * 60);
+    let _res = reply(deps.as_mut(), env.clone(), reply_msg).unwrap();
+
+    let epoch = mock_env().block.time.seconds() / (7 * 24 * 60 * 60);
+    assert_eq!(
+        query_stats(deps.as_ref(), None, None).unwrap(),
+        StatsResponse {
+            stats: vec![
+                EpochStatsResponse {
+                    epoch,
+                    swept_in: Uint128::from(100u128),
+                    anc_to_gov: Uint128::from(90u128),
+                    anc_burned: Uint128::from(10u128),
+                },
+                EpochStatsResponse {
+                    epoch: epoch + 1,
+                    swept_in: Uint128::from(200u128),
+                    anc_to_gov: Uint128::from(180u128),
+                    anc_burned: Uint128::from(20u128),
+                },
+            ]
+        }
+    );
+    assert_eq!(
+        query_stats(deps.as_ref(), Some(epoch), None)
+            .unwrap()
+            .stats
+            .len(),
+        1
+    );
+
+    assert_eq!(
+        query_total_stats(deps.as_ref()).unwrap(),
+        TotalStatsResponse {
+            swept_in: Uint128::from(300u128),
+            anc_to_gov: Uint128::from(270u128),
+            anc_burned: Uint128::from(30u128),
+        }
+    );
+}
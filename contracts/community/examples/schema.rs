@@ -1,7 +1,10 @@
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use anchor_token::community::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use anchor_token::community::{
+    BalanceResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, SpendHistoryResponse,
+    StreamResponse,
+};
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 fn main() {
@@ -14,4 +17,7 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(StreamResponse), &out_dir);
+    export_schema(&schema_for!(BalanceResponse), &out_dir);
+    export_schema(&schema_for!(SpendHistoryResponse), &out_dir);
 }
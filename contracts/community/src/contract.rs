@@ -1,14 +1,23 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
-use crate::state::{read_config, store_config, Config};
+use crate::querier::query_token_balance;
+use crate::state::{
+    read_committed_amount, read_config, read_spends, read_stream, read_stream_count,
+    store_committed_amount, store_config, store_spend, store_stream, store_stream_count, Config,
+    SpendRecord, Stream,
+};
 
 use cosmwasm_std::{
-    to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
-    Uint128, WasmMsg,
+    to_binary, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Uint128, WasmMsg,
 };
 
-use anchor_token::community::{ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use anchor_token::common::OrderBy;
+use anchor_token::community::{
+    BalanceResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    SpendHistoryResponse, SpendRecordResponse, StreamResponse,
+};
 
 use cw20::Cw20ExecuteMsg;
 
@@ -32,15 +41,21 @@ pub fn instantiate(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(
-    deps: DepsMut,
-    _env: Env,
-    info: MessageInfo,
-    msg: ExecuteMsg,
-) -> StdResult<Response> {
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
     match msg {
         ExecuteMsg::UpdateConfig { spend_limit } => update_config(deps, info, spend_limit),
-        ExecuteMsg::Spend { recipient, amount } => spend(deps, info, recipient, amount),
+        ExecuteMsg::Spend {
+            recipient,
+            amount,
+            poll_hint,
+        } => spend(deps, env, info, recipient, amount, poll_hint),
+        ExecuteMsg::CreateStream {
+            recipient,
+            amount,
+            start,
+            end,
+        } => create_stream(deps, env, info, recipient, amount, start, end),
+        ExecuteMsg::ClaimStream { stream_id } => claim_stream(deps, env, stream_id),
     }
 }
 
@@ -68,9 +83,11 @@ pub fn update_config(
 /// `amount` of ANC token to `recipient` for community purpose
 pub fn spend(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     recipient: String,
     amount: Uint128,
+    poll_hint: Option<u64>,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
     if config.gov_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
@@ -81,6 +98,19 @@ pub fn spend(
         return Err(StdError::generic_err("Cannot spend more than spend_limit"));
     }
 
+    assert_uncommitted_balance(deps.as_ref(), &env, &config, amount)?;
+
+    let spend_id = store_spend(
+        deps.storage,
+        &SpendRecord {
+            recipient: deps.api.addr_canonicalize(&recipient)?,
+            amount,
+            asset: config.anchor_token.clone(),
+            block_height: env.block.height,
+            poll_hint,
+        },
+    )?;
+
     let anchor_token = deps.api.addr_humanize(&config.anchor_token)?.to_string();
     Ok(Response::new()
         .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
@@ -93,15 +123,152 @@ pub fn spend(
         })])
         .add_attributes(vec![
             ("action", "spend"),
+            ("spend_id", &spend_id.to_string()),
             ("recipient", recipient.as_str()),
             ("amount", &amount.to_string()),
         ]))
 }
 
+/// CreateStream
+/// Owner can stream `amount` of ANC token to `recipient`,
+/// released linearly from `start` until `end`
+pub fn create_stream(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+    start: u64,
+    end: u64,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.gov_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if config.spend_limit < amount {
+        return Err(StdError::generic_err("Cannot spend more than spend_limit"));
+    }
+
+    if start >= end {
+        return Err(StdError::generic_err(
+            "Stream start must be earlier than its end",
+        ));
+    }
+
+    assert_uncommitted_balance(deps.as_ref(), &env, &config, amount)?;
+    let committed_amount = read_committed_amount(deps.storage)?;
+    store_committed_amount(deps.storage, &(committed_amount + amount))?;
+
+    let stream_id = read_stream_count(deps.storage)? + 1;
+    store_stream_count(deps.storage, stream_id)?;
+    store_stream(
+        deps.storage,
+        stream_id,
+        &Stream {
+            recipient: deps.api.addr_canonicalize(&recipient)?,
+            amount,
+            claimed: Uint128::zero(),
+            start,
+            end,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "create_stream"),
+        ("stream_id", &stream_id.to_string()),
+        ("recipient", recipient.as_str()),
+        ("amount", &amount.to_string()),
+    ]))
+}
+
+/// ClaimStream
+/// Anyone can send the vested but unclaimed portion
+/// of a stream to its recipient
+pub fn claim_stream(deps: DepsMut, env: Env, stream_id: u64) -> StdResult<Response> {
+    let current_time = env.block.time.seconds();
+    let config: Config = read_config(deps.storage)?;
+    let mut stream: Stream = read_stream(deps.storage, stream_id)?;
+
+    let claim_amount = compute_vested_amount(current_time, &stream).checked_sub(stream.claimed)?;
+    if claim_amount.is_zero() {
+        return Err(StdError::generic_err("Nothing to claim"));
+    }
+
+    stream.claimed += claim_amount;
+    store_stream(deps.storage, stream_id, &stream)?;
+
+    let committed_amount = read_committed_amount(deps.storage)?;
+    store_committed_amount(deps.storage, &committed_amount.checked_sub(claim_amount)?)?;
+
+    let recipient = deps.api.addr_humanize(&stream.recipient)?.to_string();
+    let anchor_token = deps.api.addr_humanize(&config.anchor_token)?.to_string();
+    Ok(Response::new()
+        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: anchor_token,
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.clone(),
+                amount: claim_amount,
+            })?,
+        })])
+        .add_attributes(vec![
+            ("action", "claim_stream"),
+            ("stream_id", &stream_id.to_string()),
+            ("recipient", recipient.as_str()),
+            ("amount", &claim_amount.to_string()),
+        ]))
+}
+
+/// Fails when `amount` exceeds the ANC balance not committed to streams
+fn assert_uncommitted_balance(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    amount: Uint128,
+) -> StdResult<()> {
+    let balance = query_token_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.anchor_token)?,
+        env.contract.address.clone(),
+    )?;
+    let committed_amount = read_committed_amount(deps.storage)?;
+    if balance.saturating_sub(committed_amount) < amount {
+        return Err(StdError::generic_err(
+            "Cannot spend more than the balance not committed to streams",
+        ));
+    }
+
+    Ok(())
+}
+
+fn compute_vested_amount(current_time: u64, stream: &Stream) -> Uint128 {
+    if current_time <= stream.start {
+        return Uint128::zero();
+    }
+
+    if current_time >= stream.end {
+        return stream.amount;
+    }
+
+    let passed_time = current_time - stream.start;
+    let time_period = stream.end - stream.start;
+    let release_amount_per_time: Decimal = Decimal::from_ratio(stream.amount, time_period);
+
+    Uint128::from(passed_time as u128) * release_amount_per_time
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Stream { stream_id } => to_binary(&query_stream(deps, stream_id)?),
+        QueryMsg::Balance {} => to_binary(&query_balance(deps, env)?),
+        QueryMsg::SpendHistory {
+            start_after,
+            limit,
+            order_by,
+        } => to_binary(&query_spend_history(deps, start_after, limit, order_by)?),
     }
 }
 
@@ -116,6 +283,50 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     Ok(resp)
 }
 
+pub fn query_stream(deps: Deps, stream_id: u64) -> StdResult<StreamResponse> {
+    let stream = read_stream(deps.storage, stream_id)?;
+    Ok(StreamResponse {
+        id: stream_id,
+        recipient: deps.api.addr_humanize(&stream.recipient)?.to_string(),
+        amount: stream.amount,
+        claimed: stream.claimed,
+        start: stream.start,
+        end: stream.end,
+    })
+}
+
+pub fn query_balance(deps: Deps, env: Env) -> StdResult<BalanceResponse> {
+    let config = read_config(deps.storage)?;
+    let anchor_token = deps.api.addr_humanize(&config.anchor_token)?;
+    Ok(BalanceResponse {
+        balance: query_token_balance(&deps.querier, anchor_token.clone(), env.contract.address)?,
+        anchor_token: anchor_token.to_string(),
+    })
+}
+
+pub fn query_spend_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<SpendHistoryResponse> {
+    let spends = read_spends(deps.storage, start_after, limit, order_by)?
+        .into_iter()
+        .map(|(id, spend)| {
+            Ok(SpendRecordResponse {
+                id,
+                recipient: deps.api.addr_humanize(&spend.recipient)?.to_string(),
+                amount: spend.amount,
+                asset: deps.api.addr_humanize(&spend.asset)?.to_string(),
+                block_height: spend.block_height,
+                poll_hint: spend.poll_hint,
+            })
+        })
+        .collect::<StdResult<Vec<SpendRecordResponse>>>()?;
+
+    Ok(SpendHistoryResponse { spends })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
     Ok(Response::default())
@@ -0,0 +1,18 @@
+use cosmwasm_std::{to_binary, Addr, QuerierWrapper, QueryRequest, StdResult, Uint128, WasmQuery};
+use cw20::{BalanceResponse, Cw20QueryMsg};
+
+/// Query the cw20 token balance of the given address
+pub fn query_token_balance(
+    querier: &QuerierWrapper,
+    token: Addr,
+    address: Addr,
+) -> StdResult<Uint128> {
+    let res: BalanceResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: token.to_string(),
+        msg: to_binary(&Cw20QueryMsg::Balance {
+            address: address.to_string(),
+        })?,
+    }))?;
+
+    Ok(res.balance)
+}
@@ -1,10 +1,17 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use anchor_token::common::OrderBy;
 use cosmwasm_std::{CanonicalAddr, StdResult, Storage, Uint128};
-use cosmwasm_storage::{singleton, singleton_read};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read, ReadonlyBucket};
 
 static KEY_CONFIG: &[u8] = b"config";
+static KEY_STREAM_COUNT: &[u8] = b"stream_count";
+static KEY_SPEND_COUNT: &[u8] = b"spend_count";
+static KEY_COMMITTED_AMOUNT: &[u8] = b"committed_amount";
+
+static PREFIX_STREAM: &[u8] = b"stream";
+static PREFIX_SPEND: &[u8] = b"spend";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -20,3 +27,103 @@ pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()>
 pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Stream {
+    pub recipient: CanonicalAddr,
+    pub amount: Uint128,  // total amount released over the stream
+    pub claimed: Uint128, // amount already sent to the recipient
+    pub start: u64,
+    pub end: u64,
+}
+
+pub fn store_stream_count(storage: &mut dyn Storage, stream_count: u64) -> StdResult<()> {
+    singleton(storage, KEY_STREAM_COUNT).save(&stream_count)
+}
+
+pub fn read_stream_count(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(singleton_read(storage, KEY_STREAM_COUNT)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+/// Amount of ANC streamed but not claimed yet, summed over all streams
+pub fn store_committed_amount(storage: &mut dyn Storage, amount: &Uint128) -> StdResult<()> {
+    singleton(storage, KEY_COMMITTED_AMOUNT).save(amount)
+}
+
+pub fn read_committed_amount(storage: &dyn Storage) -> StdResult<Uint128> {
+    Ok(singleton_read(storage, KEY_COMMITTED_AMOUNT)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_stream(storage: &mut dyn Storage, stream_id: u64, stream: &Stream) -> StdResult<()> {
+    bucket(storage, PREFIX_STREAM).save(&stream_id.to_be_bytes(), stream)
+}
+
+pub fn read_stream(storage: &dyn Storage, stream_id: u64) -> StdResult<Stream> {
+    bucket_read(storage, PREFIX_STREAM).load(&stream_id.to_be_bytes())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendRecord {
+    pub recipient: CanonicalAddr,
+    pub amount: Uint128,
+    pub asset: CanonicalAddr, // token sent
+    pub block_height: u64,
+    pub poll_hint: Option<u64>, // gov poll executing the spend
+}
+
+/// Records an executed spend under the next spend id and returns the id
+pub fn store_spend(storage: &mut dyn Storage, spend: &SpendRecord) -> StdResult<u64> {
+    let spend_id = singleton_read(storage, KEY_SPEND_COUNT)
+        .may_load()?
+        .unwrap_or(0u64)
+        + 1;
+    singleton(storage, KEY_SPEND_COUNT).save(&spend_id)?;
+    bucket(storage, PREFIX_SPEND).save(&spend_id.to_be_bytes(), spend)?;
+
+    Ok(spend_id)
+}
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_spends(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<Vec<(u64, SpendRecord)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (start, end, order_by) = match order_by {
+        Some(OrderBy::Asc) => (calc_range_start(start_after), None, OrderBy::Asc),
+        _ => (None, calc_range_end(start_after), OrderBy::Desc),
+    };
+
+    let spends: ReadonlyBucket<SpendRecord> = ReadonlyBucket::new(storage, PREFIX_SPEND);
+    spends
+        .range(start.as_deref(), end.as_deref(), order_by.into())
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            let mut id_bytes = [0u8; 8];
+            id_bytes.copy_from_slice(&k);
+            Ok((u64::from_be_bytes(id_bytes), v))
+        })
+        .collect()
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<u64>) -> Option<Vec<u8>> {
+    start_after.map(|id| {
+        let mut v = id.to_be_bytes().to_vec();
+        v.push(1);
+        v
+    })
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_end(start_after: Option<u64>) -> Option<Vec<u8>> {
+    start_after.map(|id| id.to_be_bytes().to_vec())
+}
@@ -1,8 +1,13 @@
 use crate::contract::{execute, instantiate, query};
 
-use anchor_token::community::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{from_binary, to_binary, CosmosMsg, StdError, SubMsg, Uint128, WasmMsg};
+use crate::mock_querier::mock_dependencies as mock_dependencies_with_querier;
+use anchor_token::common::OrderBy;
+use anchor_token::community::{
+    BalanceResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, SpendHistoryResponse,
+    SpendRecordResponse, StreamResponse,
+};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{attr, from_binary, to_binary, CosmosMsg, StdError, SubMsg, Uint128, WasmMsg};
 use cw20::Cw20ExecuteMsg;
 
 #[test]
@@ -77,7 +82,7 @@ fn update_config() {
 
 #[test]
 fn test_spend() {
-    let mut deps = mock_dependencies(&[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
 
     let msg = InstantiateMsg {
         gov_contract: "gov".to_string(),
@@ -94,6 +99,7 @@ fn test_spend() {
     let msg = ExecuteMsg::Spend {
         recipient: "addr0000".to_string(),
         amount: Uint128::from(1000000u128),
+        poll_hint: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -107,6 +113,7 @@ fn test_spend() {
     let msg = ExecuteMsg::Spend {
         recipient: "addr0000".to_string(),
         amount: Uint128::from(2000000u128),
+        poll_hint: None,
     };
 
     let info = mock_info("gov", &[]);
@@ -118,9 +125,31 @@ fn test_spend() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
+    // failed due to insufficient balance
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::from(500000u128))]);
     let msg = ExecuteMsg::Spend {
         recipient: "addr0000".to_string(),
         amount: Uint128::from(1000000u128),
+        poll_hint: None,
+    };
+
+    let info = mock_info("gov", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Cannot spend more than the balance not committed to streams"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::from(1000000u128))]);
+    let msg = ExecuteMsg::Spend {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        poll_hint: None,
     };
 
     let info = mock_info("gov", &[]);
@@ -138,3 +167,340 @@ fn test_spend() {
         }))]
     );
 }
+
+#[test]
+fn test_spend_history() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::from(5000000u128))]);
+
+    let msg = InstantiateMsg {
+        gov_contract: "gov".to_string(),
+        anchor_token: "anchor".to_string(),
+        spend_limit: Uint128::from(1000000u128),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res: BalanceResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Balance {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        BalanceResponse {
+            anchor_token: "anchor".to_string(),
+            balance: Uint128::from(5000000u128),
+        }
+    );
+
+    let mut env = mock_env();
+    let msg = ExecuteMsg::Spend {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        poll_hint: Some(7),
+    };
+    let info = mock_info("gov", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    env.block.height += 10;
+    let msg = ExecuteMsg::Spend {
+        recipient: "addr0001".to_string(),
+        amount: Uint128::from(500000u128),
+        poll_hint: None,
+    };
+    let info = mock_info("gov", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(res.attributes[1], attr("spend_id", "2"));
+
+    let first_spend = SpendRecordResponse {
+        id: 1,
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        asset: "anchor".to_string(),
+        block_height: env.block.height - 10,
+        poll_hint: Some(7),
+    };
+    let second_spend = SpendRecordResponse {
+        id: 2,
+        recipient: "addr0001".to_string(),
+        amount: Uint128::from(500000u128),
+        asset: "anchor".to_string(),
+        block_height: env.block.height,
+        poll_hint: None,
+    };
+
+    // latest spends first by default
+    let res: SpendHistoryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SpendHistory {
+                start_after: None,
+                limit: Some(1),
+                order_by: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.spends, vec![second_spend.clone()]);
+
+    let res: SpendHistoryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SpendHistory {
+                start_after: Some(2),
+                limit: None,
+                order_by: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.spends, vec![first_spend.clone()]);
+
+    let res: SpendHistoryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SpendHistory {
+                start_after: Some(1),
+                limit: None,
+                order_by: Some(OrderBy::Asc),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.spends, vec![second_spend]);
+
+    let res: SpendHistoryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SpendHistory {
+                start_after: None,
+                limit: None,
+                order_by: Some(OrderBy::Asc),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.spends[0], first_spend);
+    assert_eq!(res.spends.len(), 2);
+}
+
+#[test]
+fn test_stream() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let msg = InstantiateMsg {
+        gov_contract: "gov".to_string(),
+        anchor_token: "anchor".to_string(),
+        spend_limit: Uint128::from(1000000u128),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let start = mock_env().block.time.seconds();
+    let end = start + 1000;
+
+    // permission failed
+    let msg = ExecuteMsg::CreateStream {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        start,
+        end,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // failed due to spend limit
+    let msg = ExecuteMsg::CreateStream {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(2000000u128),
+        start,
+        end,
+    };
+    let info = mock_info("gov", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot spend more than spend_limit")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // failed due to empty period
+    let msg = ExecuteMsg::CreateStream {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        start,
+        end: start,
+    };
+    let info = mock_info("gov", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Stream start must be earlier than its end")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // failed due to insufficient balance
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::from(500000u128))]);
+    let msg = ExecuteMsg::CreateStream {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        start,
+        end,
+    };
+    let info = mock_info("gov", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Cannot spend more than the balance not committed to streams"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::from(1500000u128))]);
+    let msg = ExecuteMsg::CreateStream {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        start,
+        end,
+    };
+    let info = mock_info("gov", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.messages.is_empty());
+
+    // the streamed amount is reserved from spends and new streams
+    let msg = ExecuteMsg::Spend {
+        recipient: "addr0001".to_string(),
+        amount: Uint128::from(500001u128),
+        poll_hint: None,
+    };
+    let info = mock_info("gov", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Cannot spend more than the balance not committed to streams"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::CreateStream {
+        recipient: "addr0001".to_string(),
+        amount: Uint128::from(500001u128),
+        start,
+        end,
+    };
+    let info = mock_info("gov", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Cannot spend more than the balance not committed to streams"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // nothing vested yet
+    let info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimStream { stream_id: 1 },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Nothing to claim"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // claim mid-stream
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(250);
+    let info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        ExecuteMsg::ClaimStream { stream_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "anchor".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(250000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let stream: StreamResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Stream { stream_id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(
+        stream,
+        StreamResponse {
+            id: 1,
+            recipient: "addr0000".to_string(),
+            amount: Uint128::from(1000000u128),
+            claimed: Uint128::from(250000u128),
+            start,
+            end,
+        }
+    );
+
+    // claim the rest after completion
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(2000);
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ClaimStream { stream_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "anchor".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(750000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        ExecuteMsg::ClaimStream { stream_id: 1 },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Nothing to claim"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
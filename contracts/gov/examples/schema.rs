@@ -3,7 +3,10 @@ use std::env::current_dir;
 use std::fs::create_dir_all;
 
 use anchor_token::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollResponse, QueryMsg, StakerResponse,
+    ConfigResponse, Cw20HookMsg, EffectiveStakedResponse, EndPollReadinessResponse,
+    ExecutablePollsResponse, ExecuteMsg, InstantiateMsg, ParticipationResponse,
+    PendingWithdrawalsResponse, PollCountByStatusResponse, PollResponse, QueryMsg,
+    ShareInvariantResponse, StakerResponse, StakersResponse,
 };
 
 fn main() {
@@ -17,6 +20,14 @@ fn main() {
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(StakerResponse), &out_dir);
+    export_schema(&schema_for!(StakersResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(ExecutablePollsResponse), &out_dir);
+    export_schema(&schema_for!(PollCountByStatusResponse), &out_dir);
     export_schema(&schema_for!(PollResponse), &out_dir);
+    export_schema(&schema_for!(ParticipationResponse), &out_dir);
+    export_schema(&schema_for!(PendingWithdrawalsResponse), &out_dir);
+    export_schema(&schema_for!(ShareInvariantResponse), &out_dir);
+    export_schema(&schema_for!(EffectiveStakedResponse), &out_dir);
+    export_schema(&schema_for!(EndPollReadinessResponse), &out_dir);
 }
@@ -1,35 +1,50 @@
 use crate::error::ContractError;
-use crate::staking::{query_staker, stake_voting_tokens, withdraw_voting_tokens};
+use crate::migration::{migrate_config, migrate_poll_periods, migrate_poll_status_counts};
+use crate::staking::{
+    accrue_participation, claim_unstaked, prune_locked_balance, query_max_votable_amount,
+    query_participation, query_pending_withdrawals, query_share_invariant, query_staker,
+    query_stakers_by_addresses, query_withdrawable_amount, reconcile_total_share,
+    stake_voting_tokens, withdraw_voting_tokens,
+};
 use crate::state::{
-    bank_read, bank_store, config_read, config_store, poll_indexer_store, poll_read, poll_store,
-    poll_voter_read, poll_voter_store, read_poll_voters, read_polls, read_tmp_poll_id, state_read,
-    state_store, store_tmp_poll_id, Config, ExecuteData, Poll, State,
+    bank_read, bank_store, config_read, config_store, poll_read, poll_store, poll_voter_read,
+    poll_voter_store, read_executable_poll_ids, read_pending_owner, read_poll_status_count,
+    read_poll_voters, read_polls, read_tmp_poll_id, read_unstaked_balance, remove_pending_owner,
+    state_read, state_store, store_participation, store_pending_owner, store_tmp_poll_id,
+    update_poll_indexer, Config, ExecuteData, Poll, State,
 };
 
-use astroport::querier::query_token_balance;
+use astroport::querier::{query_supply, query_token_balance};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Binary, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, Api, Binary, CanonicalAddr, ContractResult, CosmosMsg,
+    Decimal, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg,
+    Timestamp, Uint128, WasmMsg,
 };
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration};
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
 
 use anchor_token::common::OrderBy;
 use anchor_token::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollExecuteMsg, PollResponse,
-    PollStatus, PollsResponse, QueryMsg, StateResponse, VoteOption, VoterInfo, VotersResponse,
-    VotersResponseItem,
+    ConfigResponse, Cw20HookMsg, EffectiveStakedResponse, EndPollReadinessResponse,
+    ExecutablePollsResponse, ExecuteMsg, ExecuteTargetPolicy, InstantiateMsg, MigrateMsg,
+    PollCountByStatusResponse, PollExecuteMsg, PollLengthLimits, PollMsgType, PollResponse,
+    PollStatus, PollsResponse, QueryMsg, QuorumBasis, StakedSource, StateResponse, VoteOption,
+    VoterInfo, VotersResponse, VotersResponseItem,
 };
 
-const MIN_TITLE_LENGTH: usize = 4;
-const MAX_TITLE_LENGTH: usize = 64;
-const MIN_DESC_LENGTH: usize = 4;
-const MAX_DESC_LENGTH: usize = 1024;
-const MIN_LINK_LENGTH: usize = 12;
-const MAX_LINK_LENGTH: usize = 128;
+const MAX_STALE_POLLS_PER_BATCH: usize = 30;
+const MAX_POLLS_PER_QUERY: usize = 30;
 
 const POLL_EXECUTE_REPLY_ID: u64 = 1;
+const MAX_FAILURE_REASON_LENGTH: usize = 256;
+
+// hex encoded sha256 digest and base32 encoded sha256 CIDv1 lengths
+const CONTENT_HASH_HEX_LENGTH: usize = 64;
+const CONTENT_HASH_CID_LENGTH: usize = 59;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -40,6 +55,13 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     validate_quorum(msg.quorum)?;
     validate_threshold(msg.threshold)?;
+    let veto_threshold = msg.veto_threshold.unwrap_or_else(Decimal::one);
+    validate_threshold(veto_threshold)?;
+    let quorum_reduction_factor = msg.quorum_reduction_factor.unwrap_or_else(Decimal::one);
+    validate_quorum(quorum_reduction_factor)?;
+
+    let poll_length_limits = msg.poll_length_limits.unwrap_or_default();
+    validate_poll_length_limits(&poll_length_limits)?;
 
     let config = Config {
         anchor_token: CanonicalAddr::from(vec![]),
@@ -51,6 +73,43 @@ pub fn instantiate(
         expiration_period: 0u64, // Depricated
         proposal_deposit: msg.proposal_deposit,
         snapshot_period: msg.snapshot_period,
+        quorum_basis: msg.quorum_basis.unwrap_or(QuorumBasis::Staked),
+        stale_poll_grace_period: msg.stale_poll_grace_period.unwrap_or_default(),
+        excluded_addresses: msg
+            .excluded_addresses
+            .unwrap_or_default()
+            .iter()
+            .map(|addr| deps.api.addr_canonicalize(addr))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?,
+        veto_period: msg.veto_period.unwrap_or_default(),
+        guardian: msg
+            .guardian
+            .map(|addr| deps.api.addr_canonicalize(&addr))
+            .transpose()?,
+        poll_length_limits,
+        use_time: msg.use_time.unwrap_or_default(),
+        voting_period_seconds: msg.voting_period_seconds.unwrap_or_default(),
+        snapshot_period_seconds: msg.snapshot_period_seconds.unwrap_or_default(),
+        timelock_period_seconds: msg.timelock_period_seconds.unwrap_or_default(),
+        stale_poll_grace_period_seconds: msg.stale_poll_grace_period_seconds.unwrap_or_default(),
+        max_participations: msg.max_participations,
+        execute_target_policy: msg
+            .execute_target_policy
+            .map(|policy| validate_execute_target_policy(deps.api, policy))
+            .transpose()?,
+        min_vote_amount: msg.min_vote_amount.unwrap_or_default(),
+        veto_threshold,
+        community_contract: msg
+            .community_contract
+            .map(|addr| deps.api.addr_canonicalize(&addr))
+            .transpose()?,
+        quorum_reduction_after: msg.quorum_reduction_after,
+        quorum_reduction_after_seconds: msg.quorum_reduction_after_seconds,
+        quorum_reduction_factor,
+        allow_opaque_execute_msgs: msg.allow_opaque_execute_msgs.unwrap_or(false),
+        allow_vote_change: msg.allow_vote_change.unwrap_or(false),
+        min_yes_votes: msg.min_yes_votes.unwrap_or_default(),
+        unstake_cooldown: msg.unstake_cooldown,
     };
 
     let state = State {
@@ -85,6 +144,29 @@ pub fn execute(
             timelock_period,
             proposal_deposit,
             snapshot_period,
+            quorum_basis,
+            excluded_addresses,
+            stale_poll_grace_period,
+            veto_period,
+            guardian,
+            poll_length_limits,
+            use_time,
+            voting_period_seconds,
+            snapshot_period_seconds,
+            timelock_period_seconds,
+            stale_poll_grace_period_seconds,
+            max_participations,
+            execute_target_policy,
+            min_vote_amount,
+            veto_threshold,
+            community_contract,
+            quorum_reduction_after,
+            quorum_reduction_after_seconds,
+            quorum_reduction_factor,
+            allow_opaque_execute_msgs,
+            allow_vote_change,
+            min_yes_votes,
+            unstake_cooldown,
         } => update_config(
             deps,
             info,
@@ -95,16 +177,59 @@ pub fn execute(
             timelock_period,
             proposal_deposit,
             snapshot_period,
+            quorum_basis,
+            excluded_addresses,
+            stale_poll_grace_period,
+            veto_period,
+            guardian,
+            poll_length_limits,
+            use_time,
+            voting_period_seconds,
+            snapshot_period_seconds,
+            timelock_period_seconds,
+            stale_poll_grace_period_seconds,
+            max_participations,
+            execute_target_policy,
+            min_vote_amount,
+            veto_threshold,
+            community_contract,
+            quorum_reduction_after,
+            quorum_reduction_after_seconds,
+            quorum_reduction_factor,
+            allow_opaque_execute_msgs,
+            allow_vote_change,
+            min_yes_votes,
+            unstake_cooldown,
         ),
-        ExecuteMsg::WithdrawVotingTokens { amount } => withdraw_voting_tokens(deps, info, amount),
+        ExecuteMsg::ProposeNewOwner { owner } => propose_new_owner(deps, info, owner),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, info),
+        ExecuteMsg::DropOwnershipProposal {} => drop_ownership_proposal(deps, info),
+        ExecuteMsg::WithdrawVotingTokens { amount } => {
+            withdraw_voting_tokens(deps, env, info, amount)
+        }
+        ExecuteMsg::ClaimUnstaked {} => claim_unstaked(deps, env, info),
         ExecuteMsg::CastVote {
             poll_id,
             vote,
             amount,
         } => cast_vote(deps, env, info, poll_id, vote, amount),
+        ExecuteMsg::ChangeVote {
+            poll_id,
+            new_vote,
+            new_amount,
+        } => change_vote(deps, env, info, poll_id, new_vote, new_amount),
         ExecuteMsg::EndPoll { poll_id } => end_poll(deps, env, poll_id),
         ExecuteMsg::ExecutePoll { poll_id } => execute_poll(deps, env, poll_id),
         ExecuteMsg::SnapshotPoll { poll_id } => snapshot_poll(deps, env, poll_id),
+        ExecuteMsg::SnapshotPolls { start_after, limit } => {
+            snapshot_polls(deps, env, start_after, limit)
+        }
+        ExecuteMsg::ExpireStalePolls { poll_ids } => expire_stale_polls(deps, env, info, poll_ids),
+        ExecuteMsg::VetoPoll { poll_id } => veto_poll(deps, env, info, poll_id),
+        ExecuteMsg::RetryFailedPoll { poll_id } => retry_failed_poll(deps, env, info, poll_id),
+        ExecuteMsg::ReconcileTotalShare { start_after, limit } => {
+            reconcile_total_share(deps, env, start_after, limit)
+        }
     }
 }
 
@@ -113,9 +238,27 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
     match msg.id {
         POLL_EXECUTE_REPLY_ID => {
             let poll_id: u64 = read_tmp_poll_id(deps.storage)?;
-            fail_poll(deps, poll_id)
+            let failure_reason = match msg.result {
+                ContractResult::Err(err) => err,
+                ContractResult::Ok(_) => String::new(),
+            };
+
+            fail_poll(deps, poll_id, failure_reason)
+        }
+        id => {
+            // replies of the messages of non atomic polls
+            let (poll_id, index) = (id >> 32, (id & u32::MAX as u64) as usize);
+            if poll_id == 0 {
+                return Err(ContractError::InvalidReplyId {});
+            }
+
+            let failure_reason = match msg.result {
+                ContractResult::Err(err) => err,
+                ContractResult::Ok(_) => String::new(),
+            };
+
+            fail_poll_message(deps, poll_id, index, failure_reason)
         }
-        _ => Err(ContractError::InvalidReplyId {}),
     }
 }
 
@@ -131,6 +274,58 @@ pub fn register_contracts(deps: DepsMut, anchor_token: String) -> Result<Respons
     Ok(Response::default())
 }
 
+/// Owner can propose a new owner, which takes over
+/// the ownership once it accepts it
+pub fn propose_new_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_pending_owner(deps.storage, &deps.api.addr_canonicalize(&owner)?)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "propose_new_owner"),
+        ("pending_owner", owner.as_str()),
+    ]))
+}
+
+pub fn accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if read_pending_owner(deps.storage)? != Some(sender_address_raw.clone()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut config: Config = config_read(deps.storage).load()?;
+    config.owner = sender_address_raw;
+    config_store(deps.storage).save(&config)?;
+    remove_pending_owner(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "accept_ownership"),
+        ("owner", info.sender.as_str()),
+    ]))
+}
+
+/// Owner can drop the pending ownership proposal
+pub fn drop_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    remove_pending_owner(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![("action", "drop_ownership_proposal")]))
+}
+
 pub fn receive_cw20(
     deps: DepsMut,
     env: Env,
@@ -153,6 +348,8 @@ pub fn receive_cw20(
             description,
             link,
             execute_msgs,
+            atomic,
+            content_hash,
         }) => create_poll(
             deps,
             env,
@@ -162,6 +359,8 @@ pub fn receive_cw20(
             description,
             link,
             execute_msgs,
+            atomic,
+            content_hash,
         ),
         _ => Err(ContractError::DataShouldBeGiven {}),
     }
@@ -178,8 +377,32 @@ pub fn update_config(
     timelock_period: Option<u64>,
     proposal_deposit: Option<Uint128>,
     snapshot_period: Option<u64>,
+    quorum_basis: Option<QuorumBasis>,
+    excluded_addresses: Option<Vec<String>>,
+    stale_poll_grace_period: Option<u64>,
+    veto_period: Option<u64>,
+    guardian: Option<String>,
+    poll_length_limits: Option<PollLengthLimits>,
+    use_time: Option<bool>,
+    voting_period_seconds: Option<u64>,
+    snapshot_period_seconds: Option<u64>,
+    timelock_period_seconds: Option<u64>,
+    stale_poll_grace_period_seconds: Option<u64>,
+    max_participations: Option<u64>,
+    execute_target_policy: Option<ExecuteTargetPolicy>,
+    min_vote_amount: Option<Uint128>,
+    veto_threshold: Option<Decimal>,
+    community_contract: Option<String>,
+    quorum_reduction_after: Option<u64>,
+    quorum_reduction_after_seconds: Option<u64>,
+    quorum_reduction_factor: Option<Decimal>,
+    allow_opaque_execute_msgs: Option<bool>,
+    allow_vote_change: Option<bool>,
+    min_yes_votes: Option<Uint128>,
+    unstake_cooldown: Option<u64>,
 ) -> Result<Response, ContractError> {
     let api = deps.api;
+    let owner_changed = owner.is_some();
     config_store(deps.storage).update(|mut config| {
         if config.owner != api.addr_canonicalize(info.sender.as_str())? {
             return Err(ContractError::Unauthorized {});
@@ -213,17 +436,204 @@ pub fn update_config(
             config.snapshot_period = period;
         }
 
+        if let Some(quorum_basis) = quorum_basis {
+            config.quorum_basis = quorum_basis;
+        }
+
+        if let Some(excluded_addresses) = excluded_addresses {
+            config.excluded_addresses = excluded_addresses
+                .iter()
+                .map(|addr| api.addr_canonicalize(addr))
+                .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+        }
+
+        if let Some(stale_poll_grace_period) = stale_poll_grace_period {
+            config.stale_poll_grace_period = stale_poll_grace_period;
+        }
+
+        if let Some(veto_period) = veto_period {
+            config.veto_period = veto_period;
+        }
+
+        if let Some(guardian) = guardian {
+            config.guardian = Some(api.addr_canonicalize(&guardian)?);
+        }
+
+        if let Some(poll_length_limits) = poll_length_limits {
+            validate_poll_length_limits(&poll_length_limits)?;
+            config.poll_length_limits = poll_length_limits;
+        }
+
+        if let Some(use_time) = use_time {
+            config.use_time = use_time;
+        }
+
+        if let Some(voting_period_seconds) = voting_period_seconds {
+            config.voting_period_seconds = voting_period_seconds;
+        }
+
+        if let Some(snapshot_period_seconds) = snapshot_period_seconds {
+            config.snapshot_period_seconds = snapshot_period_seconds;
+        }
+
+        if let Some(timelock_period_seconds) = timelock_period_seconds {
+            config.timelock_period_seconds = timelock_period_seconds;
+        }
+
+        if let Some(stale_poll_grace_period_seconds) = stale_poll_grace_period_seconds {
+            config.stale_poll_grace_period_seconds = stale_poll_grace_period_seconds;
+        }
+
+        if let Some(max_participations) = max_participations {
+            config.max_participations = Some(max_participations);
+        }
+
+        if let Some(execute_target_policy) = execute_target_policy {
+            config.execute_target_policy =
+                Some(validate_execute_target_policy(api, execute_target_policy)?);
+        }
+
+        if let Some(min_vote_amount) = min_vote_amount {
+            config.min_vote_amount = min_vote_amount;
+        }
+
+        if let Some(veto_threshold) = veto_threshold {
+            validate_threshold(veto_threshold)?;
+            config.veto_threshold = veto_threshold;
+        }
+
+        if let Some(community_contract) = community_contract {
+            config.community_contract = Some(api.addr_canonicalize(&community_contract)?);
+        }
+
+        if let Some(quorum_reduction_after) = quorum_reduction_after {
+            config.quorum_reduction_after = Some(quorum_reduction_after);
+        }
+
+        if let Some(quorum_reduction_after_seconds) = quorum_reduction_after_seconds {
+            config.quorum_reduction_after_seconds = Some(quorum_reduction_after_seconds);
+        }
+
+        if let Some(quorum_reduction_factor) = quorum_reduction_factor {
+            validate_quorum(quorum_reduction_factor)?;
+            config.quorum_reduction_factor = quorum_reduction_factor;
+        }
+
+        if let Some(allow_opaque_execute_msgs) = allow_opaque_execute_msgs {
+            config.allow_opaque_execute_msgs = allow_opaque_execute_msgs;
+        }
+
+        if let Some(allow_vote_change) = allow_vote_change {
+            config.allow_vote_change = allow_vote_change;
+        }
+
+        if let Some(min_yes_votes) = min_yes_votes {
+            config.min_yes_votes = min_yes_votes;
+        }
+
+        if let Some(unstake_cooldown) = unstake_cooldown {
+            config.unstake_cooldown = Some(unstake_cooldown);
+        }
+
         Ok(config)
     })?;
 
+    // a proposal made to the previous owner is void
+    if owner_changed {
+        remove_pending_owner(deps.storage);
+    }
+
     Ok(Response::new().add_attributes(vec![("action", "update_config")]))
 }
 
+/// validate_execute_target_policy normalizes the listed contract addresses
+fn validate_execute_target_policy(
+    api: &dyn Api,
+    policy: ExecuteTargetPolicy,
+) -> StdResult<ExecuteTargetPolicy> {
+    let validate = |contracts: Vec<String>| {
+        contracts
+            .iter()
+            .map(|addr| Ok(api.addr_validate(addr)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()
+    };
+
+    Ok(match policy {
+        ExecuteTargetPolicy::Allowlist { contracts } => ExecuteTargetPolicy::Allowlist {
+            contracts: validate(contracts)?,
+        },
+        ExecuteTargetPolicy::Denylist { contracts } => ExecuteTargetPolicy::Denylist {
+            contracts: validate(contracts)?,
+        },
+    })
+}
+
+/// ExecuteMsgVariants counts the top level keys of a JSON object without
+/// decoding their values
+struct ExecuteMsgVariants(usize);
+
+impl<'de> Deserialize<'de> for ExecuteMsgVariants {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct VariantsVisitor;
+
+        impl<'de> Visitor<'de> for VariantsVisitor {
+            type Value = ExecuteMsgVariants;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut count = 0;
+                while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {
+                    count += 1;
+                }
+                Ok(ExecuteMsgVariants(count))
+            }
+        }
+
+        deserializer.deserialize_struct("ExecuteMsgVariants", &[], VariantsVisitor)
+    }
+}
+
+/// is_decodable_execute_msg returns whether the msg decodes as a JSON object.
+/// Execute msgs must also hold a single variant key, while migrate msgs are
+/// commonly plain structs
+fn is_decodable_execute_msg(msg: &Binary, msg_type: &Option<PollMsgType>) -> bool {
+    match from_binary::<ExecuteMsgVariants>(msg) {
+        Ok(ExecuteMsgVariants(count)) => {
+            matches!(msg_type, Some(PollMsgType::Migrate { .. })) || count == 1
+        }
+        Err(_) => false,
+    }
+}
+
+/// assert_self_call_allowed returns an error if the poll msg executes on the gov
+/// contract itself, unless the msg is an UpdateConfig or RetryFailedPoll.
+/// Migrations of the gov contract do not re-enter its execute entry point
+/// and are left to the poll
+fn assert_self_call_allowed(
+    contract: &Addr,
+    gov_address: &Addr,
+    order: u64,
+    msg_type: &Option<PollMsgType>,
+    msg: &Binary,
+) -> Result<(), ContractError> {
+    if contract != gov_address || matches!(msg_type, Some(PollMsgType::Migrate { .. })) {
+        return Ok(());
+    }
+
+    match from_binary::<ExecuteMsg>(msg) {
+        Ok(ExecuteMsg::UpdateConfig { .. }) | Ok(ExecuteMsg::RetryFailedPoll { .. }) => Ok(()),
+        _ => Err(ContractError::SelfCallNotAllowed(order)),
+    }
+}
+
 /// validate_title returns an error if the title is invalid
-fn validate_title(title: &str) -> StdResult<()> {
-    if title.len() < MIN_TITLE_LENGTH {
+fn validate_title(title: &str, limits: &PollLengthLimits) -> StdResult<()> {
+    if (title.len() as u64) < limits.min_title_length {
         Err(StdError::generic_err("Title too short"))
-    } else if title.len() > MAX_TITLE_LENGTH {
+    } else if title.len() as u64 > limits.max_title_length {
         Err(StdError::generic_err("Title too long"))
     } else {
         Ok(())
@@ -231,10 +641,10 @@ fn validate_title(title: &str) -> StdResult<()> {
 }
 
 /// validate_description returns an error if the description is invalid
-fn validate_description(description: &str) -> StdResult<()> {
-    if description.len() < MIN_DESC_LENGTH {
+fn validate_description(description: &str, limits: &PollLengthLimits) -> StdResult<()> {
+    if (description.len() as u64) < limits.min_desc_length {
         Err(StdError::generic_err("Description too short"))
-    } else if description.len() > MAX_DESC_LENGTH {
+    } else if description.len() as u64 > limits.max_desc_length {
         Err(StdError::generic_err("Description too long"))
     } else {
         Ok(())
@@ -242,11 +652,11 @@ fn validate_description(description: &str) -> StdResult<()> {
 }
 
 /// validate_link returns an error if the link is invalid
-fn validate_link(link: &Option<String>) -> StdResult<()> {
+fn validate_link(link: &Option<String>, limits: &PollLengthLimits) -> StdResult<()> {
     if let Some(link) = link {
-        if link.len() < MIN_LINK_LENGTH {
+        if (link.len() as u64) < limits.min_link_length {
             Err(StdError::generic_err("Link too short"))
-        } else if link.len() > MAX_LINK_LENGTH {
+        } else if link.len() as u64 > limits.max_link_length {
             Err(StdError::generic_err("Link too long"))
         } else {
             Ok(())
@@ -256,6 +666,40 @@ fn validate_link(link: &Option<String>) -> StdResult<()> {
     }
 }
 
+/// validate_content_hash returns an error if the content hash is neither
+/// a hex encoded sha256 digest nor a base32 encoded CIDv1
+fn validate_content_hash(content_hash: &Option<String>) -> StdResult<()> {
+    if let Some(content_hash) = content_hash {
+        let is_hex_digest = content_hash.len() == CONTENT_HASH_HEX_LENGTH
+            && content_hash.chars().all(|c| c.is_ascii_hexdigit());
+        let is_base32_cid = content_hash.len() == CONTENT_HASH_CID_LENGTH
+            && content_hash.starts_with('b')
+            && content_hash
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || ('2'..='7').contains(&c));
+        if !is_hex_digest && !is_base32_cid {
+            return Err(StdError::generic_err("Invalid content hash"));
+        }
+    }
+
+    Ok(())
+}
+
+/// validate_poll_length_limits returns an error if a minimum length
+/// is greater than its maximum length
+fn validate_poll_length_limits(limits: &PollLengthLimits) -> StdResult<()> {
+    if limits.min_title_length > limits.max_title_length
+        || limits.min_desc_length > limits.max_desc_length
+        || limits.min_link_length > limits.max_link_length
+    {
+        Err(StdError::generic_err(
+            "minimum length must not be greater than maximum length",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 /// validate_quorum returns an error if the quorum is invalid
 /// (we require 0-1)
 fn validate_quorum(quorum: Decimal) -> StdResult<()> {
@@ -287,12 +731,15 @@ pub fn create_poll(
     description: String,
     link: Option<String>,
     execute_msgs: Option<Vec<PollExecuteMsg>>,
+    atomic: Option<bool>,
+    content_hash: Option<String>,
 ) -> Result<Response, ContractError> {
-    validate_title(&title)?;
-    validate_description(&description)?;
-    validate_link(&link)?;
-
     let config: Config = config_store(deps.storage).load()?;
+    validate_title(&title, &config.poll_length_limits)?;
+    validate_description(&description, &config.poll_length_limits)?;
+    validate_link(&link, &config.poll_length_limits)?;
+    validate_content_hash(&content_hash)?;
+
     if deposit_amount < config.proposal_deposit {
         return Err(ContractError::InsufficientProposalDeposit(
             config.proposal_deposit.u128(),
@@ -307,12 +754,39 @@ pub fn create_poll(
     state.total_deposit += deposit_amount;
 
     let mut data_list: Vec<ExecuteData> = vec![];
+    let mut migrate_contracts: Vec<String> = vec![];
     let all_execute_data = if let Some(exe_msgs) = execute_msgs {
         for msgs in exe_msgs {
+            if let Some(policy) = &config.execute_target_policy {
+                let contract = deps.api.addr_validate(&msgs.contract)?;
+                if !policy.is_allowed(contract.as_str()) {
+                    return Err(ContractError::DisallowedExecuteTarget {});
+                }
+            }
+
+            assert_self_call_allowed(
+                &deps.api.addr_validate(&msgs.contract)?,
+                &env.contract.address,
+                msgs.order,
+                &msgs.msg_type,
+                &msgs.msg,
+            )?;
+
+            if !config.allow_opaque_execute_msgs
+                && !is_decodable_execute_msg(&msgs.msg, &msgs.msg_type)
+            {
+                return Err(ContractError::UndecodableExecuteMsg {});
+            }
+
+            if let Some(PollMsgType::Migrate { .. }) = msgs.msg_type {
+                migrate_contracts.push(msgs.contract.clone());
+            }
+
             let execute_data = ExecuteData {
                 order: msgs.order,
                 contract: deps.api.addr_canonicalize(&msgs.contract)?,
                 msg: msgs.msg,
+                msg_type: msgs.msg_type,
             };
             data_list.push(execute_data)
         }
@@ -328,7 +802,13 @@ pub fn create_poll(
         status: PollStatus::InProgress,
         yes_votes: Uint128::zero(),
         no_votes: Uint128::zero(),
+        veto_votes: Some(Uint128::zero()),
         end_height: env.block.height + config.voting_period,
+        end_time: if config.use_time {
+            Some(env.block.time.seconds() + config.voting_period_seconds)
+        } else {
+            None
+        },
         title,
         description,
         link,
@@ -336,26 +816,60 @@ pub fn create_poll(
         deposit_amount,
         total_balance_at_end_poll: None,
         staked_amount: None,
+        execution_height: None,
+        failure_reason: None,
+        atomic,
+        execution_results: None,
+        extended: None,
+        content_hash,
+        voting_period: Some(if config.use_time {
+            config.voting_period_seconds
+        } else {
+            config.voting_period
+        }),
+        snapshot_period: Some(if config.use_time {
+            config.snapshot_period_seconds
+        } else {
+            config.snapshot_period
+        }),
+        timelock_period: Some(if config.use_time {
+            config.timelock_period_seconds
+        } else {
+            config.timelock_period
+        }),
     };
 
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &new_poll)?;
-    poll_indexer_store(deps.storage, &PollStatus::InProgress)
-        .save(&poll_id.to_be_bytes(), &true)?;
+    update_poll_indexer(deps.storage, poll_id, None, &PollStatus::InProgress)?;
 
     state_store(deps.storage).save(&state)?;
 
-    Ok(Response::new().add_attributes(vec![
-        ("action", "create_poll"),
-        (
+    let mut attributes = vec![
+        attr("action", "create_poll"),
+        attr(
             "creator",
             deps.api
                 .addr_humanize(&new_poll.creator)?
                 .to_string()
                 .as_str(),
         ),
-        ("poll_id", &poll_id.to_string()),
-        ("end_height", new_poll.end_height.to_string().as_str()),
-    ]))
+        attr("poll_id", poll_id.to_string()),
+        attr("end_height", new_poll.end_height.to_string().as_str()),
+    ];
+    if let Some(end_time) = new_poll.end_time {
+        attributes.push(attr("end_time", end_time.to_string()));
+    }
+
+    // the admin of a contract cannot be queried,
+    // so the migrations fail at execution when gov is not the admin
+    if !migrate_contracts.is_empty() {
+        attributes.push(attr(
+            "unverified_migrate_admin",
+            migrate_contracts.join(","),
+        ));
+    }
+
+    Ok(Response::new().add_attributes(attributes))
 }
 
 /*
@@ -368,14 +882,192 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Contr
         return Err(ContractError::PollNotInProgress {});
     }
 
-    if a_poll.end_height > env.block.height {
+    if !voting_period_ended(&a_poll, &env) {
         return Err(ContractError::PollVotingPeriod {});
     }
 
+    // the timelock and veto periods of time based polls count from here
+    if a_poll.end_time.is_some() {
+        a_poll.end_height = env.block.height;
+    }
+
+    let tally = tally_poll(deps, a_poll, Uint128::zero(), true)?;
+
+    let mut response = Response::new()
+        .add_messages(tally.messages)
+        .add_attributes(vec![
+            ("action", "end_poll"),
+            ("poll_id", &poll_id.to_string()),
+            ("rejected_reason", tally.rejected_reason),
+            ("passed", &tally.passed.to_string()),
+        ]);
+    if tally.extended {
+        response = response.add_attribute("extended", "true");
+    }
+
+    Ok(response)
+}
+
+/*
+ * Ends the given polls which are still in progress long after their end height.
+ * Polls within their voting period or the stale grace period are skipped.
+ */
+pub fn expire_stale_polls(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_ids: Vec<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if poll_ids.len() > MAX_STALE_POLLS_PER_BATCH {
+        return Err(ContractError::TooManyPolls(MAX_STALE_POLLS_PER_BATCH));
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut pending_refund = Uint128::zero();
+    let mut ended_polls: Vec<String> = vec![];
+    let mut passed_polls: Vec<String> = vec![];
+    let mut skipped_polls: Vec<String> = vec![];
+    for poll_id in poll_ids {
+        let mut a_poll: Poll = poll_read(deps.storage).load(&poll_id.to_be_bytes())?;
+        if a_poll.status != PollStatus::InProgress
+            || !stale_poll_grace_period_ended(&config, &a_poll, &env)
+        {
+            skipped_polls.push(poll_id.to_string());
+            continue;
+        }
+
+        if a_poll.end_time.is_some() {
+            a_poll.end_height = env.block.height;
+        }
+
+        let tally = tally_poll(deps.branch(), a_poll, pending_refund, false)?;
+        pending_refund += tally.refund_amount;
+        messages.extend(tally.messages);
+
+        if tally.passed {
+            passed_polls.push(poll_id.to_string());
+        }
+        ended_polls.push(poll_id.to_string());
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "expire_stale_polls"),
+        ("ended_polls", &ended_polls.join(",")),
+        ("passed_polls", &passed_polls.join(",")),
+        ("skipped_polls", &skipped_polls.join(",")),
+    ]))
+}
+
+/// Whether the voting period of the poll is over, by block time
+/// for time based polls and by block height otherwise
+fn voting_period_ended(poll: &Poll, env: &Env) -> bool {
+    match poll.end_time {
+        Some(end_time) => end_time <= env.block.time.seconds(),
+        None => poll.end_height <= env.block.height,
+    }
+}
+
+/// Whether the grace period after the end of the poll is over, by block time
+/// for time based polls and by block height otherwise
+fn stale_poll_grace_period_ended(config: &Config, poll: &Poll, env: &Env) -> bool {
+    match poll.end_time {
+        Some(end_time) => {
+            end_time + config.stale_poll_grace_period_seconds < env.block.time.seconds()
+        }
+        None => poll.end_height + config.stale_poll_grace_period < env.block.height,
+    }
+}
+
+/// Snapshot period of the poll, falling back to the config for polls created before
+/// the periods were recorded on the poll
+fn poll_snapshot_period(config: &Config, poll: &Poll) -> u64 {
+    poll.snapshot_period.unwrap_or(match poll.end_time {
+        Some(_) => config.snapshot_period_seconds,
+        None => config.snapshot_period,
+    })
+}
+
+/// Timelock period of the poll, falling back to the config for polls created before
+/// the periods were recorded on the poll
+fn poll_timelock_period(config: &Config, poll: &Poll) -> u64 {
+    poll.timelock_period.unwrap_or(match poll.end_time {
+        Some(_) => config.timelock_period_seconds,
+        None => config.timelock_period,
+    })
+}
+
+/// Whether the timelock of the ended poll is over, counting seconds
+/// from the end time for time based polls and blocks otherwise
+fn timelock_expired(config: &Config, poll: &Poll, env: &Env) -> bool {
+    let timelock_period = poll_timelock_period(config, poll);
+    match poll.end_time {
+        Some(end_time) => end_time + timelock_period <= env.block.time.seconds(),
+        None => poll.end_height + timelock_period <= env.block.height,
+    }
+}
+
+/// Remaining voting period of the poll and the snapshot period to compare it with,
+/// in seconds for time based polls and in blocks otherwise
+fn time_to_end(config: &Config, poll: &Poll, env: &Env) -> (u64, u64) {
+    let snapshot_period = poll_snapshot_period(config, poll);
+    match poll.end_time {
+        Some(end_time) => (end_time - env.block.time.seconds(), snapshot_period),
+        None => (poll.end_height - env.block.height, snapshot_period),
+    }
+}
+
+struct PollTally {
+    messages: Vec<CosmosMsg>,
+    rejected_reason: &'static str,
+    passed: bool,
+    refund_amount: Uint128,
+    extended: bool,
+}
+
+/// poll_staked_weight returns the staked amount snapshotted by the poll,
+/// or else the current staked balance excluding the deposits
+fn poll_staked_weight(
+    deps: Deps,
+    config: &Config,
+    state: &State,
+    poll: &Poll,
+    pending_refund: Uint128,
+) -> StdResult<(Uint128, StakedSource)> {
+    if let Some(staked_amount) = poll.staked_amount {
+        return Ok((staked_amount, StakedSource::Snapshot));
+    }
+
+    let staked_amount = query_token_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.anchor_token)?,
+        deps.api.addr_humanize(&state.contract_addr)?,
+    )?
+    .checked_sub(read_unstaked_balance(deps.storage, state)? + pending_refund)?;
+
+    Ok((staked_amount, StakedSource::Live))
+}
+
+/// tally_poll counts the votes of an ended poll, refunds the deposit when the quorum
+/// is reached and stores the final status. `pending_refund` is the amount of deposits
+/// refunded earlier in the same response, which is still held by the contract.
+/// With `allow_extension`, a poll missing the quorum for the first time is extended
+/// with a reduced quorum instead, when the config enables it.
+fn tally_poll(
+    deps: DepsMut,
+    mut a_poll: Poll,
+    pending_refund: Uint128,
+    allow_extension: bool,
+) -> Result<PollTally, ContractError> {
     let no = a_poll.no_votes.u128();
     let yes = a_poll.yes_votes.u128();
+    let veto = a_poll.veto_votes.unwrap_or_default().u128();
 
-    let tallied_weight = yes + no;
+    let tallied_weight = yes + no + veto;
 
     let mut poll_status = PollStatus::Rejected;
     let mut rejected_reason = "";
@@ -387,41 +1079,90 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Contr
 
     let (quorum, staked_weight) = if state.total_share.u128() == 0 {
         (Decimal::zero(), Uint128::zero())
-    } else if let Some(staked_amount) = a_poll.staked_amount {
-        (
-            Decimal::from_ratio(tallied_weight, staked_amount),
-            staked_amount,
-        )
     } else {
-        let staked_weight = query_token_balance(
-            &deps.querier,
-            deps.api.addr_humanize(&config.anchor_token)?,
-            deps.api.addr_humanize(&state.contract_addr)?,
-        )?
-        .checked_sub(state.total_deposit)?;
+        let (staked_weight, _) =
+            poll_staked_weight(deps.as_ref(), &config, &state, &a_poll, pending_refund)?;
+
+        let quorum_weight = match config.quorum_basis {
+            QuorumBasis::Staked => staked_weight,
+            QuorumBasis::Circulating => query_circulating_supply(deps.as_ref(), &config)?,
+        };
 
         (
-            Decimal::from_ratio(tallied_weight, staked_weight),
+            Decimal::from_ratio(tallied_weight, quorum_weight),
             staked_weight,
         )
     };
 
-    if tallied_weight == 0 || quorum < config.quorum {
+    let extended = a_poll.extended.unwrap_or_default();
+    let required_quorum = if extended {
+        decimal_mul(config.quorum, config.quorum_reduction_factor)
+    } else {
+        config.quorum
+    };
+
+    let mut refund_amount = Uint128::zero();
+    if tallied_weight == 0 || quorum < required_quorum {
+        let quorum_reduction_after = match a_poll.end_time {
+            Some(_) => config.quorum_reduction_after_seconds,
+            None => config.quorum_reduction_after,
+        };
+        if let (Some(extension), true, false) = (quorum_reduction_after, allow_extension, extended)
+        {
+            // Extension: the poll stays in progress once more with the reduced quorum
+            match a_poll.end_time {
+                Some(end_time) => a_poll.end_time = Some(end_time + extension),
+                None => a_poll.end_height += extension,
+            }
+            a_poll.extended = Some(true);
+            poll_store(deps.storage).save(&a_poll.id.to_be_bytes(), &a_poll)?;
+
+            return Ok(PollTally {
+                messages,
+                rejected_reason: "Quorum not reached",
+                passed,
+                refund_amount,
+                extended: true,
+            });
+        }
+
         // Quorum: More than quorum of the total staked tokens at the end of the voting
         // period need to have participated in the vote.
         rejected_reason = "Quorum not reached";
+    } else if Decimal::from_ratio(veto, tallied_weight) > config.veto_threshold {
+        // Veto: the deposit of a vetoed poll goes to the community contract,
+        // it stays in the contract when none is configured
+        rejected_reason = "Vetoed";
+
+        if let Some(community_contract) = config.community_contract.as_ref() {
+            if !a_poll.deposit_amount.is_zero() {
+                refund_amount = a_poll.deposit_amount;
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: deps.api.addr_humanize(community_contract)?.to_string(),
+                        amount: a_poll.deposit_amount,
+                    })?,
+                }))
+            }
+        }
     } else {
-        if Decimal::from_ratio(yes, tallied_weight) > config.threshold {
+        if Decimal::from_ratio(yes, tallied_weight) <= config.threshold {
             //Threshold: More than 50% of the tokens that participated in the vote
             // (after excluding “Abstain” votes) need to have voted in favor of the proposal (“Yes”).
+            rejected_reason = "Threshold not reached";
+        } else if a_poll.yes_votes < config.min_yes_votes {
+            // Minimum yes votes: an absolute floor on top of the quorum and threshold
+            rejected_reason = "Minimum yes votes not reached";
+        } else {
             poll_status = PollStatus::Passed;
             passed = true;
-        } else {
-            rejected_reason = "Threshold not reached";
         }
 
         // Refunds deposit only when quorum is reached
         if !a_poll.deposit_amount.is_zero() {
+            refund_amount = a_poll.deposit_amount;
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
                 funds: vec![],
@@ -438,20 +1179,51 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Contr
     state_store(deps.storage).save(&state)?;
 
     // Update poll indexer
-    poll_indexer_store(deps.storage, &PollStatus::InProgress).remove(&a_poll.id.to_be_bytes());
-    poll_indexer_store(deps.storage, &poll_status).save(&a_poll.id.to_be_bytes(), &true)?;
+    update_poll_indexer(
+        deps.storage,
+        a_poll.id,
+        Some(&PollStatus::InProgress),
+        &poll_status,
+    )?;
 
     // Update poll status
     a_poll.status = poll_status;
     a_poll.total_balance_at_end_poll = Some(staked_weight);
-    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+    poll_store(deps.storage).save(&a_poll.id.to_be_bytes(), &a_poll)?;
+
+    Ok(PollTally {
+        messages,
+        rejected_reason,
+        passed,
+        refund_amount,
+        extended: false,
+    })
+}
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        ("action", "end_poll"),
-        ("poll_id", &poll_id.to_string()),
-        ("rejected_reason", rejected_reason),
-        ("passed", &passed.to_string()),
-    ]))
+const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000u128;
+
+fn decimal_mul(a: Decimal, b: Decimal) -> Decimal {
+    Decimal::from_ratio(
+        Uint128::from(DECIMAL_FRACTIONAL) * a * b,
+        DECIMAL_FRACTIONAL,
+    )
+}
+
+/// circulating supply is the total supply of the anchor token
+/// excluding the balances of the configured excluded addresses
+fn query_circulating_supply(deps: Deps, config: &Config) -> StdResult<Uint128> {
+    let anchor_token = deps.api.addr_humanize(&config.anchor_token)?;
+    let mut circulating_supply = query_supply(&deps.querier, anchor_token.clone())?;
+    for excluded_address in config.excluded_addresses.iter() {
+        let excluded_balance = query_token_balance(
+            &deps.querier,
+            anchor_token.clone(),
+            deps.api.addr_humanize(excluded_address)?,
+        )?;
+        circulating_supply = circulating_supply.checked_sub(excluded_balance)?;
+    }
+
+    Ok(circulating_supply)
 }
 
 /*
@@ -459,16 +1231,27 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Contr
  */
 pub fn execute_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, ContractError> {
     let config: Config = config_read(deps.storage).load()?;
-    let a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+    let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
 
     if a_poll.status != PollStatus::Passed {
         return Err(ContractError::PollNotPassed {});
     }
 
-    if a_poll.end_height + config.timelock_period > env.block.height {
+    if !timelock_expired(&config, &a_poll, &env) {
         return Err(ContractError::TimelockNotExpired {});
     }
 
+    if a_poll.end_height + config.veto_period > env.block.height {
+        return Err(ContractError::VetoPeriodNotExpired {});
+    }
+
+    a_poll.execution_height = Some(env.block.height);
+    if a_poll.atomic == Some(false) {
+        return execute_non_atomic_poll(deps, env, a_poll);
+    }
+
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
     store_tmp_poll_id(deps.storage, a_poll.id)?;
 
     Ok(Response::new().add_submessage(SubMsg::reply_on_error(
@@ -481,6 +1264,130 @@ pub fn execute_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, C
     )))
 }
 
+/*
+ * Execute each msg of a passed non atomic poll as its own submsg,
+ * the reply id encodes the poll id and the position of the msg
+ */
+fn execute_non_atomic_poll(
+    deps: DepsMut,
+    env: Env,
+    mut a_poll: Poll,
+) -> Result<Response, ContractError> {
+    update_poll_indexer(
+        deps.storage,
+        a_poll.id,
+        Some(&PollStatus::Passed),
+        &PollStatus::Executed,
+    )?;
+
+    let mut msgs = a_poll.execute_data.clone().unwrap_or_default();
+    msgs.sort();
+
+    let mut submessages: Vec<SubMsg> = vec![];
+    let mut execution_results: Vec<(u64, bool, Option<String>)> = vec![];
+    for (index, msg) in msgs.into_iter().enumerate() {
+        // failures are recorded by the reply handler
+        execution_results.push((msg.order, true, None));
+        submessages.push(SubMsg::reply_on_error(
+            poll_wasm_msg(deps.api, &env.contract.address, msg)?,
+            (a_poll.id << 32) | index as u64,
+        ));
+    }
+
+    a_poll.status = PollStatus::Executed;
+    a_poll.execution_results = Some(execution_results);
+    poll_store(deps.storage).save(&a_poll.id.to_be_bytes(), &a_poll)?;
+
+    Ok(Response::new()
+        .add_submessages(submessages)
+        .add_attributes(vec![
+            ("action", "execute_poll"),
+            ("poll_id", a_poll.id.to_string().as_str()),
+        ]))
+}
+
+/*
+ * Veto a passed poll to block its execution
+ */
+pub fn veto_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if config.owner != sender_address_raw && config.guardian != Some(sender_address_raw) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+    if a_poll.status != PollStatus::Passed {
+        return Err(ContractError::PollNotPassed {});
+    }
+
+    if a_poll.end_height + config.veto_period <= env.block.height {
+        return Err(ContractError::VetoPeriodExpired {});
+    }
+
+    update_poll_indexer(
+        deps.storage,
+        poll_id,
+        Some(&PollStatus::Passed),
+        &PollStatus::Vetoed,
+    )?;
+
+    a_poll.status = PollStatus::Vetoed;
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "veto_poll"),
+        ("poll_id", poll_id.to_string().as_str()),
+        ("vetoed_by", info.sender.as_str()),
+    ]))
+}
+
+/*
+ * Return a failed poll to passed so that it can be executed again,
+ * e.g. once the cause of the failure has been fixed.
+ * Only the poll creator or the gov contract, through a passed poll, can retry
+ */
+pub fn retry_failed_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if a_poll.creator != sender_address_raw && info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if a_poll.status != PollStatus::Failed {
+        return Err(ContractError::PollNotFailed {});
+    }
+
+    update_poll_indexer(
+        deps.storage,
+        poll_id,
+        Some(&PollStatus::Failed),
+        &PollStatus::Passed,
+    )?;
+
+    a_poll.status = PollStatus::Passed;
+    a_poll.execution_height = None;
+    a_poll.failure_reason = None;
+    a_poll.execution_results = None;
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "retry_failed_poll"),
+        ("poll_id", poll_id.to_string().as_str()),
+        ("retried_by", info.sender.as_str()),
+    ]))
+}
+
 /*
  * Execute a msgs of a poll
  */
@@ -494,10 +1401,19 @@ pub fn execute_poll_messages(
         return Err(ContractError::Unauthorized {});
     }
 
+    // The poll must be the one marked pending by execute_poll and still passed;
+    // moving it to executed below consumes it, so it cannot be executed twice
     let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+    if a_poll.status != PollStatus::Passed || read_tmp_poll_id(deps.storage).ok() != Some(poll_id) {
+        return Err(ContractError::PollNotPendingExecution {});
+    }
 
-    poll_indexer_store(deps.storage, &PollStatus::Passed).remove(&poll_id.to_be_bytes());
-    poll_indexer_store(deps.storage, &PollStatus::Executed).save(&poll_id.to_be_bytes(), &true)?;
+    update_poll_indexer(
+        deps.storage,
+        poll_id,
+        Some(&PollStatus::Passed),
+        &PollStatus::Executed,
+    )?;
 
     a_poll.status = PollStatus::Executed;
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
@@ -507,11 +1423,7 @@ pub fn execute_poll_messages(
         let mut msgs = all_msgs;
         msgs.sort();
         for msg in msgs {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.addr_humanize(&msg.contract)?.to_string(),
-                msg: msg.msg,
-                funds: vec![],
-            }));
+            messages.push(poll_wasm_msg(deps.api, &env.contract.address, msg)?);
         }
     }
 
@@ -521,21 +1433,118 @@ pub fn execute_poll_messages(
     ]))
 }
 
+/// poll_wasm_msg builds the execute or migrate msg of the poll execute data,
+/// rechecking that it does not call back into the gov contract
+fn poll_wasm_msg(
+    api: &dyn Api,
+    gov_address: &Addr,
+    data: ExecuteData,
+) -> Result<CosmosMsg, ContractError> {
+    let contract = api.addr_humanize(&data.contract)?;
+    assert_self_call_allowed(
+        &contract,
+        gov_address,
+        data.order,
+        &data.msg_type,
+        &data.msg,
+    )?;
+
+    let contract_addr = contract.to_string();
+    Ok(CosmosMsg::Wasm(match data.msg_type {
+        Some(PollMsgType::Migrate { new_code_id }) => WasmMsg::Migrate {
+            contract_addr,
+            new_code_id,
+            msg: data.msg,
+        },
+        Some(PollMsgType::Execute) | None => WasmMsg::Execute {
+            contract_addr,
+            msg: data.msg,
+            funds: vec![],
+        },
+    }))
+}
+
 /*
  * Set the status of a poll to Failed if execute_poll fails
  */
-pub fn fail_poll(deps: DepsMut, poll_id: u64) -> Result<Response, ContractError> {
+pub fn fail_poll(
+    deps: DepsMut,
+    poll_id: u64,
+    failure_reason: String,
+) -> Result<Response, ContractError> {
     let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
 
-    poll_indexer_store(deps.storage, &PollStatus::Passed).remove(&poll_id.to_be_bytes());
-    poll_indexer_store(deps.storage, &PollStatus::Failed).save(&poll_id.to_be_bytes(), &true)?;
+    update_poll_indexer(
+        deps.storage,
+        poll_id,
+        Some(&PollStatus::Passed),
+        &PollStatus::Failed,
+    )?;
+
+    // keep a bounded record of the error for debugging
+    let failure_reason: String = failure_reason
+        .chars()
+        .take(MAX_FAILURE_REASON_LENGTH)
+        .collect();
 
     a_poll.status = PollStatus::Failed;
+    a_poll.failure_reason = Some(failure_reason.clone());
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
 
     Ok(Response::new().add_attributes(vec![
         ("action", "fail_poll"),
         ("poll_id", poll_id.to_string().as_str()),
+        ("failure_reason", failure_reason.as_str()),
+    ]))
+}
+
+/*
+ * Record the failure of a msg of a non atomic poll,
+ * the poll fails once all of its msgs failed
+ */
+pub fn fail_poll_message(
+    deps: DepsMut,
+    poll_id: u64,
+    index: usize,
+    failure_reason: String,
+) -> Result<Response, ContractError> {
+    let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+
+    // keep a bounded record of the error for debugging
+    let failure_reason: String = failure_reason
+        .chars()
+        .take(MAX_FAILURE_REASON_LENGTH)
+        .collect();
+
+    let execution_results = a_poll
+        .execution_results
+        .as_mut()
+        .ok_or(ContractError::InvalidReplyId {})?;
+    let order = match execution_results.get(index) {
+        Some((order, _, _)) => *order,
+        None => return Err(ContractError::InvalidReplyId {}),
+    };
+    execution_results[index] = (order, false, Some(failure_reason.clone()));
+
+    if execution_results.iter().all(|(_, succeeded, _)| !succeeded) {
+        update_poll_indexer(
+            deps.storage,
+            poll_id,
+            Some(&PollStatus::Executed),
+            &PollStatus::Failed,
+        )?;
+
+        a_poll.status = PollStatus::Failed;
+        a_poll.failure_reason = Some(failure_reason.clone());
+    }
+
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "fail_poll_message"),
+        ("poll_id", poll_id.to_string().as_str()),
+        ("order", order.to_string().as_str()),
+        ("failure_reason", failure_reason.as_str()),
     ]))
 }
 
@@ -548,9 +1557,9 @@ pub fn snapshot_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response,
         return Err(ContractError::PollNotInProgress {});
     }
 
-    let time_to_end = a_poll.end_height - env.block.height;
+    let (time_to_end, snapshot_period) = time_to_end(&config, &a_poll, &env);
 
-    if time_to_end > config.snapshot_period {
+    if time_to_end > snapshot_period {
         return Err(ContractError::SnapshotHeight {});
     }
 
@@ -566,7 +1575,7 @@ pub fn snapshot_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response,
         deps.api.addr_humanize(&config.anchor_token)?,
         deps.api.addr_humanize(&state.contract_addr)?,
     )?
-    .checked_sub(state.total_deposit)?;
+    .checked_sub(read_unstaked_balance(deps.storage, &state)?)?;
 
     a_poll.staked_amount = Some(staked_amount);
 
@@ -579,14 +1588,67 @@ pub fn snapshot_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response,
     ]))
 }
 
+/// SnapshotPolls takes a snapshot of every in-progress poll within its snapshot period,
+/// ineligible polls are skipped
+pub fn snapshot_polls(
+    deps: DepsMut,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    let state: State = state_read(deps.storage).load()?;
+    let polls = read_polls(
+        deps.storage,
+        Some(PollStatus::InProgress),
+        start_after,
+        limit,
+        Some(OrderBy::Asc),
+    )?;
+
+    let staked_amount = query_token_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.anchor_token)?,
+        deps.api.addr_humanize(&state.contract_addr)?,
+    )?
+    .checked_sub(read_unstaked_balance(deps.storage, &state)?)?;
+
+    let mut attributes = vec![
+        attr("action", "snapshot_polls"),
+        attr("staked_amount", staked_amount.to_string()),
+    ];
+    for mut a_poll in polls {
+        // polls past their end cannot be voted on anymore
+        let snapshot_period = poll_snapshot_period(&config, &a_poll);
+        let time_to_end = match a_poll.end_time {
+            Some(end_time) => end_time.checked_sub(env.block.time.seconds()),
+            None => a_poll.end_height.checked_sub(env.block.height),
+        }
+        .filter(|time_to_end| *time_to_end <= snapshot_period);
+        if time_to_end.is_none() || a_poll.staked_amount.is_some() {
+            continue;
+        }
+
+        a_poll.staked_amount = Some(staked_amount);
+        poll_store(deps.storage).save(&a_poll.id.to_be_bytes(), &a_poll)?;
+        attributes.push(attr("snapshot_poll_id", a_poll.id.to_string()));
+    }
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
 pub fn cast_vote(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     poll_id: u64,
     vote: VoteOption,
-    amount: Uint128,
+    amount: Option<Uint128>,
 ) -> Result<Response, ContractError> {
+    if amount == Some(Uint128::zero()) {
+        return Err(ContractError::InvalidVoteAmount {});
+    }
+
     let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
     let config = config_read(deps.storage).load()?;
     let state = state_read(deps.storage).load()?;
@@ -595,7 +1657,11 @@ pub fn cast_vote(
     }
 
     let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
-    if a_poll.status != PollStatus::InProgress || env.block.height > a_poll.end_height {
+    let voting_closed = match a_poll.end_time {
+        Some(end_time) => env.block.time.seconds() > end_time,
+        None => env.block.height > a_poll.end_height,
+    };
+    if a_poll.status != PollStatus::InProgress || voting_closed {
         return Err(ContractError::PollNotInProgress {});
     }
 
@@ -610,6 +1676,17 @@ pub fn cast_vote(
     let key = &sender_address_raw.as_slice();
     let mut token_manager = bank_read(deps.storage).may_load(key)?.unwrap_or_default();
 
+    // removes not in-progress poll voter info, keeping the locked balance small
+    for poll_id in prune_locked_balance(deps.storage, &mut token_manager)? {
+        poll_voter_store(deps.storage, poll_id).remove(key);
+    }
+
+    if let Some(max_participations) = config.max_participations {
+        if token_manager.locked_balance.len() as u64 >= max_participations {
+            return Err(ContractError::TooManyParticipations(max_participations));
+        }
+    }
+
     // convert share to amount
     let total_share = state.total_share;
     let total_balance = query_token_balance(
@@ -617,22 +1694,30 @@ pub fn cast_vote(
         deps.api.addr_humanize(&config.anchor_token)?,
         deps.api.addr_humanize(&state.contract_addr)?,
     )?
-    .checked_sub(state.total_deposit)?;
+    .checked_sub(read_unstaked_balance(deps.storage, &state)?)?;
 
-    if token_manager
+    let staked_amount = token_manager
         .share
-        .multiply_ratio(total_balance, total_share)
-        < amount
-    {
-        return Err(ContractError::InsufficientStaked {});
+        .multiply_ratio(total_balance, total_share);
+
+    // vote with the whole staked amount at execution time, if not given
+    let amount = match amount {
+        Some(amount) if staked_amount < amount => {
+            return Err(ContractError::InsufficientStaked {});
+        }
+        Some(amount) => amount,
+        None if staked_amount.is_zero() => return Err(ContractError::NothingStaked {}),
+        None => staked_amount,
+    };
+
+    if amount < config.min_vote_amount {
+        return Err(ContractError::VoteAmountTooSmall(
+            config.min_vote_amount.u128(),
+        ));
     }
 
     // update tally info
-    if VoteOption::Yes == vote {
-        a_poll.yes_votes += amount;
-    } else {
-        a_poll.no_votes += amount;
-    }
+    add_votes(&mut a_poll, &vote, amount);
 
     let vote_info = VoterInfo {
         vote,
@@ -643,13 +1728,17 @@ pub fn cast_vote(
         .push((poll_id, vote_info.clone()));
     bank_store(deps.storage).save(key, &token_manager)?;
 
+    let mut participation = accrue_participation(deps.storage, &state, key, token_manager.share)?;
+    participation.polls_voted += 1;
+    store_participation(deps.storage, key, &participation)?;
+
     // store poll voter && and update poll data
     poll_voter_store(deps.storage, poll_id).save(sender_address_raw.as_slice(), &vote_info)?;
 
     // processing snapshot
-    let time_to_end = a_poll.end_height - env.block.height;
+    let (time_to_end, snapshot_period) = time_to_end(&config, &a_poll, &env);
 
-    if time_to_end < config.snapshot_period && a_poll.staked_amount.is_none() {
+    if time_to_end < snapshot_period && a_poll.staked_amount.is_none() {
         a_poll.staked_amount = Some(total_balance);
     }
 
@@ -664,12 +1753,144 @@ pub fn cast_vote(
     ]))
 }
 
+/// change_vote replaces the sender's vote on an in progress poll,
+/// when vote changes are allowed by the config
+pub fn change_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    new_vote: VoteOption,
+    new_amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    if new_amount == Some(Uint128::zero()) {
+        return Err(ContractError::InvalidVoteAmount {});
+    }
+
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let config = config_read(deps.storage).load()?;
+    if !config.allow_vote_change {
+        return Err(ContractError::VoteChangeNotAllowed {});
+    }
+
+    let state = state_read(deps.storage).load()?;
+    if poll_id == 0 || state.poll_count < poll_id {
+        return Err(ContractError::PollNotFound {});
+    }
+
+    let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+    let voting_closed = match a_poll.end_time {
+        Some(end_time) => env.block.time.seconds() > end_time,
+        None => env.block.height > a_poll.end_height,
+    };
+    if a_poll.status != PollStatus::InProgress || voting_closed {
+        return Err(ContractError::PollNotInProgress {});
+    }
+
+    let prev_vote_info = poll_voter_read(deps.storage, poll_id)
+        .may_load(sender_address_raw.as_slice())?
+        .ok_or(ContractError::NotVoted {})?;
+
+    let key = &sender_address_raw.as_slice();
+    let mut token_manager = bank_read(deps.storage).may_load(key)?.unwrap_or_default();
+
+    // convert share to amount
+    let total_balance = query_token_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.anchor_token)?,
+        deps.api.addr_humanize(&state.contract_addr)?,
+    )?
+    .checked_sub(read_unstaked_balance(deps.storage, &state)?)?;
+
+    let staked_amount = token_manager
+        .share
+        .multiply_ratio(total_balance, state.total_share);
+
+    // vote with the whole staked amount at execution time, if not given
+    let amount = match new_amount {
+        Some(amount) if staked_amount < amount => {
+            return Err(ContractError::InsufficientStaked {});
+        }
+        Some(amount) => amount,
+        None if staked_amount.is_zero() => return Err(ContractError::NothingStaked {}),
+        None => staked_amount,
+    };
+
+    if amount < config.min_vote_amount {
+        return Err(ContractError::VoteAmountTooSmall(
+            config.min_vote_amount.u128(),
+        ));
+    }
+
+    // unwind the previous vote and apply the new one
+    remove_votes(&mut a_poll, &prev_vote_info.vote, prev_vote_info.balance)?;
+    add_votes(&mut a_poll, &new_vote, amount);
+
+    let vote_info = VoterInfo {
+        vote: new_vote,
+        balance: amount,
+    };
+    token_manager
+        .locked_balance
+        .retain(|(locked_poll_id, _)| *locked_poll_id != poll_id);
+    token_manager
+        .locked_balance
+        .push((poll_id, vote_info.clone()));
+    bank_store(deps.storage).save(key, &token_manager)?;
+
+    poll_voter_store(deps.storage, poll_id).save(sender_address_raw.as_slice(), &vote_info)?;
+
+    // processing snapshot
+    let (time_to_end, snapshot_period) = time_to_end(&config, &a_poll, &env);
+
+    if time_to_end < snapshot_period && a_poll.staked_amount.is_none() {
+        a_poll.staked_amount = Some(total_balance);
+    }
+
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "change_vote"),
+        ("poll_id", poll_id.to_string().as_str()),
+        ("amount", amount.to_string().as_str()),
+        ("voter", info.sender.as_str()),
+        ("vote_option", vote_info.vote.to_string().as_str()),
+    ]))
+}
+
+/// add_votes adds the amount to the tally of the vote option
+fn add_votes(poll: &mut Poll, vote: &VoteOption, amount: Uint128) {
+    match vote {
+        VoteOption::Yes => poll.yes_votes += amount,
+        VoteOption::No => poll.no_votes += amount,
+        VoteOption::NoWithVeto => {
+            poll.veto_votes = Some(poll.veto_votes.unwrap_or_default() + amount)
+        }
+    }
+}
+
+/// remove_votes subtracts the amount from the tally of the vote option
+fn remove_votes(poll: &mut Poll, vote: &VoteOption, amount: Uint128) -> StdResult<()> {
+    match vote {
+        VoteOption::Yes => poll.yes_votes = poll.yes_votes.checked_sub(amount)?,
+        VoteOption::No => poll.no_votes = poll.no_votes.checked_sub(amount)?,
+        VoteOption::NoWithVeto => {
+            poll.veto_votes = Some(poll.veto_votes.unwrap_or_default().checked_sub(amount)?)
+        }
+    }
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
         QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
         QueryMsg::State {} => Ok(to_binary(&query_state(deps)?)?),
         QueryMsg::Staker { address } => Ok(to_binary(&query_staker(deps, address)?)?),
+        QueryMsg::Participation { address } => Ok(to_binary(&query_participation(deps, address)?)?),
+        QueryMsg::StakersByAddresses { addresses } => {
+            Ok(to_binary(&query_stakers_by_addresses(deps, addresses)?)?)
+        }
         QueryMsg::Poll { poll_id } => Ok(to_binary(&query_poll(deps, poll_id)?)?),
         QueryMsg::Polls {
             filter,
@@ -695,20 +1916,83 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
             limit,
             order_by,
         )?)?),
+        QueryMsg::WithdrawableAmount { address } => {
+            Ok(to_binary(&query_withdrawable_amount(deps, address)?)?)
+        }
+        QueryMsg::MaxVotableAmount { poll_id, address } => Ok(to_binary(
+            &query_max_votable_amount(deps, env, poll_id, address)?,
+        )?),
+        QueryMsg::PollsByIds { ids } => Ok(to_binary(&query_polls_by_ids(deps, ids)?)?),
+        QueryMsg::PollCountByStatus {} => Ok(to_binary(&query_poll_count_by_status(deps)?)?),
+        QueryMsg::ExecutablePolls { start_after, limit } => Ok(to_binary(
+            &query_executable_polls(deps, env, start_after, limit)?,
+        )?),
+        QueryMsg::EffectiveStaked { poll_id } => {
+            Ok(to_binary(&query_effective_staked(deps, poll_id)?)?)
+        }
+        QueryMsg::PendingWithdrawals { address } => {
+            Ok(to_binary(&query_pending_withdrawals(deps, address)?)?)
+        }
+        QueryMsg::EndPollReadiness { poll_id } => {
+            Ok(to_binary(&query_end_poll_readiness(deps, env, poll_id)?)?)
+        }
+        QueryMsg::ShareInvariant {} => Ok(to_binary(&query_share_invariant(deps)?)?),
     }
 }
 
 fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
     let config: Config = config_read(deps.storage).load()?;
+    let registered = config.anchor_token != CanonicalAddr::from(vec![]);
     Ok(ConfigResponse {
         owner: deps.api.addr_humanize(&config.owner)?.to_string(),
-        anchor_token: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+        anchor_token: if registered {
+            Some(deps.api.addr_humanize(&config.anchor_token)?.to_string())
+        } else {
+            None
+        },
+        registered,
         quorum: config.quorum,
         threshold: config.threshold,
         voting_period: config.voting_period,
         timelock_period: config.timelock_period,
         proposal_deposit: config.proposal_deposit,
         snapshot_period: config.snapshot_period,
+        quorum_basis: config.quorum_basis,
+        excluded_addresses: config
+            .excluded_addresses
+            .iter()
+            .map(|addr| Ok(deps.api.addr_humanize(addr)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?,
+        stale_poll_grace_period: config.stale_poll_grace_period,
+        veto_period: config.veto_period,
+        guardian: config
+            .guardian
+            .map(|addr| deps.api.addr_humanize(&addr).map(|addr| addr.to_string()))
+            .transpose()?,
+        poll_length_limits: config.poll_length_limits,
+        use_time: config.use_time,
+        voting_period_seconds: config.voting_period_seconds,
+        snapshot_period_seconds: config.snapshot_period_seconds,
+        timelock_period_seconds: config.timelock_period_seconds,
+        stale_poll_grace_period_seconds: config.stale_poll_grace_period_seconds,
+        max_participations: config.max_participations,
+        execute_target_policy: config.execute_target_policy,
+        min_vote_amount: config.min_vote_amount,
+        veto_threshold: config.veto_threshold,
+        community_contract: config
+            .community_contract
+            .map(|addr| deps.api.addr_humanize(&addr).map(|addr| addr.to_string()))
+            .transpose()?,
+        quorum_reduction_after: config.quorum_reduction_after,
+        quorum_reduction_after_seconds: config.quorum_reduction_after_seconds,
+        quorum_reduction_factor: config.quorum_reduction_factor,
+        allow_opaque_execute_msgs: config.allow_opaque_execute_msgs,
+        allow_vote_change: config.allow_vote_change,
+        min_yes_votes: config.min_yes_votes,
+        unstake_cooldown: config.unstake_cooldown,
+        pending_owner: read_pending_owner(deps.storage)?
+            .map(|addr| deps.api.addr_humanize(&addr).map(|addr| addr.to_string()))
+            .transpose()?,
     })
 }
 
@@ -721,6 +2005,71 @@ fn query_state(deps: Deps) -> Result<StateResponse, ContractError> {
     })
 }
 
+fn query_effective_staked(
+    deps: Deps,
+    poll_id: u64,
+) -> Result<EffectiveStakedResponse, ContractError> {
+    let poll = match poll_read(deps.storage).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => poll,
+        None => return Err(ContractError::PollNotFound {}),
+    };
+
+    if poll.status != PollStatus::InProgress {
+        return Err(ContractError::PollNotInProgress {});
+    }
+
+    let config: Config = config_read(deps.storage).load()?;
+    let state: State = state_read(deps.storage).load()?;
+
+    // end_poll tallies against nothing staked when there are no shares
+    let (staked_amount, source) = if state.total_share.is_zero() {
+        (Uint128::zero(), StakedSource::Live)
+    } else {
+        poll_staked_weight(deps, &config, &state, &poll, Uint128::zero())?
+    };
+
+    Ok(EffectiveStakedResponse {
+        staked_amount,
+        source,
+    })
+}
+
+/// query_end_poll_readiness mirrors the checks of end_poll. Polls ended by
+/// end_poll are reported as already ended, polls which were executed,
+/// failed, vetoed or expired afterwards as not in progress
+fn query_end_poll_readiness(
+    deps: Deps,
+    env: Env,
+    poll_id: u64,
+) -> Result<EndPollReadinessResponse, ContractError> {
+    let poll = match poll_read(deps.storage).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => poll,
+        None => return Err(ContractError::PollNotFound {}),
+    };
+
+    let (blocks_remaining, reason) = match poll.status {
+        PollStatus::InProgress => {
+            let blocks_remaining = match poll.end_time {
+                Some(end_time) => end_time.saturating_sub(env.block.time.seconds()),
+                None => poll.end_height.saturating_sub(env.block.height),
+            };
+            if blocks_remaining > 0 {
+                (blocks_remaining, "voting period not over")
+            } else {
+                (0, "")
+            }
+        }
+        PollStatus::Passed | PollStatus::Rejected => (0, "already ended"),
+        _ => (0, "not in progress"),
+    };
+
+    Ok(EndPollReadinessResponse {
+        can_end: reason.is_empty(),
+        blocks_remaining,
+        reason: reason.to_string(),
+    })
+}
+
 fn query_poll(deps: Deps, poll_id: u64) -> Result<PollResponse, ContractError> {
     let poll = match poll_read(deps.storage).may_load(&poll_id.to_be_bytes())? {
         Some(poll) => Some(poll),
@@ -735,6 +2084,11 @@ fn query_poll(deps: Deps, poll_id: u64) -> Result<PollResponse, ContractError> {
         creator: deps.api.addr_humanize(&poll.creator)?.to_string(),
         status: poll.status,
         end_height: poll.end_height,
+        end_time: poll.end_time,
+        ends_at: match poll.end_time {
+            Some(end_time) => Expiration::AtTime(Timestamp::from_seconds(end_time)),
+            None => Expiration::AtHeight(poll.end_height),
+        },
         title: poll.title,
         description: poll.description,
         link: poll.link,
@@ -745,6 +2099,7 @@ fn query_poll(deps: Deps, poll_id: u64) -> Result<PollResponse, ContractError> {
                     order: msg.order,
                     contract: deps.api.addr_humanize(&msg.contract)?.to_string(),
                     msg: msg.msg,
+                    msg_type: msg.msg_type,
                 };
                 data_list.push(execute_data)
             }
@@ -754,8 +2109,70 @@ fn query_poll(deps: Deps, poll_id: u64) -> Result<PollResponse, ContractError> {
         },
         yes_votes: poll.yes_votes,
         no_votes: poll.no_votes,
+        veto_votes: poll.veto_votes.unwrap_or_default(),
         staked_amount: poll.staked_amount,
         total_balance_at_end_poll: poll.total_balance_at_end_poll,
+        execution_height: poll.execution_height,
+        failure_reason: poll.failure_reason,
+        atomic: poll.atomic.unwrap_or(true),
+        extended: poll.extended.unwrap_or_default(),
+        execution_results: poll.execution_results,
+        content_hash: poll.content_hash,
+    })
+}
+
+fn query_polls_by_ids(deps: Deps, ids: Vec<u64>) -> Result<PollsResponse, ContractError> {
+    if ids.len() > MAX_POLLS_PER_QUERY {
+        return Err(ContractError::TooManyPolls(MAX_POLLS_PER_QUERY));
+    }
+
+    let mut polls: Vec<PollResponse> = vec![];
+    for poll_id in ids {
+        match query_poll(deps, poll_id) {
+            Ok(poll) => polls.push(poll),
+            // skip nonexistent polls
+            Err(ContractError::PollNotFound {}) => continue,
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(PollsResponse { polls })
+}
+
+fn query_poll_count_by_status(deps: Deps) -> Result<PollCountByStatusResponse, ContractError> {
+    Ok(PollCountByStatusResponse {
+        in_progress: read_poll_status_count(deps.storage, &PollStatus::InProgress)?,
+        passed: read_poll_status_count(deps.storage, &PollStatus::Passed)?,
+        rejected: read_poll_status_count(deps.storage, &PollStatus::Rejected)?,
+        executed: read_poll_status_count(deps.storage, &PollStatus::Executed)?,
+        expired: read_poll_status_count(deps.storage, &PollStatus::Expired)?,
+        failed: read_poll_status_count(deps.storage, &PollStatus::Failed)?,
+        vetoed: read_poll_status_count(deps.storage, &PollStatus::Vetoed)?,
+    })
+}
+
+fn query_executable_polls(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<ExecutablePollsResponse, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+
+    // both the timelock and the veto period must be over
+    let (poll_ids, last_scanned) = read_executable_poll_ids(
+        deps.storage,
+        &|poll| {
+            timelock_expired(&config, poll, &env)
+                && poll.end_height + config.veto_period <= env.block.height
+        },
+        start_after,
+        limit,
+    )?;
+
+    Ok(ExecutablePollsResponse {
+        poll_ids,
+        last_scanned,
     })
 }
 
@@ -776,6 +2193,11 @@ fn query_polls(
                 creator: deps.api.addr_humanize(&poll.creator)?.to_string(),
                 status: poll.status.clone(),
                 end_height: poll.end_height,
+                end_time: poll.end_time,
+                ends_at: match poll.end_time {
+                    Some(end_time) => Expiration::AtTime(Timestamp::from_seconds(end_time)),
+                    None => Expiration::AtHeight(poll.end_height),
+                },
                 title: poll.title.to_string(),
                 description: poll.description.to_string(),
                 link: poll.link.clone(),
@@ -788,6 +2210,7 @@ fn query_polls(
                             order: msg.order,
                             contract: deps.api.addr_humanize(&msg.contract)?.to_string(),
                             msg: msg.msg,
+                            msg_type: msg.msg_type,
                         };
                         data_list.push(execute_data)
                     }
@@ -797,8 +2220,15 @@ fn query_polls(
                 },
                 yes_votes: poll.yes_votes,
                 no_votes: poll.no_votes,
+                veto_votes: poll.veto_votes.unwrap_or_default(),
                 staked_amount: poll.staked_amount,
                 total_balance_at_end_poll: poll.total_balance_at_end_poll,
+                execution_height: poll.execution_height,
+                failure_reason: poll.failure_reason.clone(),
+                atomic: poll.atomic.unwrap_or(true),
+                extended: poll.extended.unwrap_or_default(),
+                execution_results: poll.execution_results.clone(),
+                content_hash: poll.content_hash.clone(),
             })
         })
         .collect();
@@ -850,3 +2280,12 @@ fn query_voters(
         voters: voters_response?,
     })
 }
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate_config(deps.storage)?;
+    migrate_poll_status_counts(deps.storage)?;
+    migrate_poll_periods(deps.storage)?;
+
+    Ok(Response::default())
+}
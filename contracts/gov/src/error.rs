@@ -33,8 +33,17 @@ pub enum ContractError {
     #[error("Nothing staked")]
     NothingStaked {},
 
-    #[error("User is trying to withdraw too many tokens")]
-    InvalidWithdrawAmount {},
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("Vote amount must be greater than zero")]
+    InvalidVoteAmount {},
+
+    #[error("Vote amount must be at least {0}")]
+    VoteAmountTooSmall(u128),
+
+    #[error("User is trying to withdraw too many tokens; max withdrawable amount is {0}")]
+    InvalidWithdrawAmount(u128),
 
     #[error("Nothing to withdraw")]
     NothingToWithdraw {},
@@ -54,18 +63,60 @@ pub enum ContractError {
     #[error("Poll is not in passed status")]
     PollNotPassed {},
 
+    #[error("Poll is not in failed status")]
+    PollNotFailed {},
+
+    #[error("Poll is not pending execution")]
+    PollNotPendingExecution {},
+
+    #[error("Veto period has not expired")]
+    VetoPeriodNotExpired {},
+
+    #[error("Veto period has expired")]
+    VetoPeriodExpired {},
+
     #[error("Cannot snapshot at this height")]
     SnapshotHeight {},
 
     #[error("User has already voted")]
     AlreadyVoted {},
 
+    #[error("User has not voted on the poll")]
+    NotVoted {},
+
+    #[error("Changing votes is not allowed")]
+    VoteChangeNotAllowed {},
+
     #[error("Expire height has not been reached")]
     PollNotExpired {},
 
     #[error("Voting period has not expired")]
     PollVotingPeriod {},
 
+    #[error("Cannot process more than {0} polls at once")]
+    TooManyPolls(usize),
+
+    #[error("Cannot query more than {0} addresses at once")]
+    TooManyAddresses(usize),
+
+    #[error("Cannot vote on more than {0} in-progress polls at once")]
+    TooManyParticipations(u64),
+
+    #[error("Total share was already reconciled in this block")]
+    ReconcileRateLimited {},
+
+    #[error("Reconciliation must continue after the last reconciled staker")]
+    ReconcileCursorMismatch {},
+
+    #[error("Poll is not allowed to execute messages on the target contract")]
+    DisallowedExecuteTarget {},
+
+    #[error("Poll execute msg cannot be decoded")]
+    UndecodableExecuteMsg {},
+
+    #[error("Poll message {0} calls the gov contract")]
+    SelfCallNotAllowed(u64),
+
     #[error("Invalid Reply Id")]
     InvalidReplyId {},
 }
@@ -0,0 +1,125 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::state::{
+    config_read, config_store, count_indexed_polls, poll_store, read_indexed_poll_ids,
+    store_poll_status_count, Config, Poll, KEY_CONFIG,
+};
+use anchor_token::gov::{PollLengthLimits, PollStatus, QuorumBasis};
+use cosmwasm_std::{CanonicalAddr, Decimal, StdError, StdResult, Storage, Uint128};
+use cosmwasm_storage::ReadonlySingleton;
+
+/// Config before the gov settings were added; unknown fields are denied
+/// so that a current config is never mistaken for a legacy one
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LegacyConfig {
+    pub owner: CanonicalAddr,
+    pub anchor_token: CanonicalAddr,
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub voting_period: u64,
+    pub timelock_period: u64,
+    pub expiration_period: u64,
+    pub proposal_deposit: Uint128,
+    pub snapshot_period: u64,
+}
+
+fn read_legacy_config(storage: &dyn Storage) -> StdResult<Option<LegacyConfig>> {
+    match ReadonlySingleton::new(storage, KEY_CONFIG).load() {
+        Ok(legacy_config) => Ok(Some(legacy_config)),
+        Err(StdError::ParseErr { .. }) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Converts a legacy config to the current one, a config
+/// which was already migrated is left as is
+pub fn migrate_config(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy_config: LegacyConfig = match read_legacy_config(storage)? {
+        Some(legacy_config) => legacy_config,
+        None => return Ok(()),
+    };
+
+    config_store(storage).save(&Config {
+        owner: legacy_config.owner,
+        anchor_token: legacy_config.anchor_token,
+        quorum: legacy_config.quorum,
+        threshold: legacy_config.threshold,
+        voting_period: legacy_config.voting_period,
+        timelock_period: legacy_config.timelock_period,
+        expiration_period: legacy_config.expiration_period,
+        proposal_deposit: legacy_config.proposal_deposit,
+        snapshot_period: legacy_config.snapshot_period,
+        quorum_basis: QuorumBasis::Staked,
+        excluded_addresses: vec![],
+        stale_poll_grace_period: 0u64,
+        veto_period: 0u64,
+        guardian: None,
+        poll_length_limits: PollLengthLimits::default(),
+        use_time: false,
+        voting_period_seconds: 0u64,
+        snapshot_period_seconds: 0u64,
+        timelock_period_seconds: 0u64,
+        stale_poll_grace_period_seconds: 0u64,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: Uint128::zero(),
+        veto_threshold: Decimal::one(),
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: Decimal::one(),
+        allow_opaque_execute_msgs: true,
+        allow_vote_change: false,
+        min_yes_votes: Uint128::zero(),
+        unstake_cooldown: None,
+    })
+}
+
+/// Counts the existing polls once to initialize the poll counts per status
+pub fn migrate_poll_status_counts(storage: &mut dyn Storage) -> StdResult<()> {
+    for status in [
+        PollStatus::InProgress,
+        PollStatus::Passed,
+        PollStatus::Rejected,
+        PollStatus::Executed,
+        PollStatus::Expired,
+        PollStatus::Failed,
+        PollStatus::Vetoed,
+    ] {
+        let count = count_indexed_polls(storage, &status);
+        store_poll_status_count(storage, &status, count)?;
+    }
+
+    Ok(())
+}
+
+/// Records the periods of the config on the in progress polls which don't
+/// have them yet, so later config changes don't affect them. Time based
+/// polls get the periods in seconds
+pub fn migrate_poll_periods(storage: &mut dyn Storage) -> StdResult<()> {
+    let config: Config = config_read(storage).load()?;
+    for poll_id in read_indexed_poll_ids(storage, &PollStatus::InProgress)? {
+        let mut poll: Poll = poll_store(storage).load(&poll_id.to_be_bytes())?;
+        let (voting_period, snapshot_period, timelock_period) = match poll.end_time {
+            Some(_) => (
+                config.voting_period_seconds,
+                config.snapshot_period_seconds,
+                config.timelock_period_seconds,
+            ),
+            None => (
+                config.voting_period,
+                config.snapshot_period,
+                config.timelock_period,
+            ),
+        };
+
+        poll.voting_period = poll.voting_period.or(Some(voting_period));
+        poll.snapshot_period = poll.snapshot_period.or(Some(snapshot_period));
+        poll.timelock_period = poll.timelock_period.or(Some(timelock_period));
+        poll_store(storage).save(&poll_id.to_be_bytes(), &poll)?;
+    }
+
+    Ok(())
+}
@@ -3,7 +3,7 @@ use cosmwasm_std::{
     from_binary, from_slice, to_binary, Coin, ContractResult, Empty, OwnedDeps, Querier,
     QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
-use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 use std::collections::HashMap;
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -24,6 +24,7 @@ pub fn mock_dependencies(
 pub struct WasmMockQuerier {
     base: MockQuerier<Empty>,
     token_querier: TokenQuerier,
+    token_supplies: HashMap<String, Uint128>,
 }
 
 #[derive(Clone, Default)]
@@ -107,6 +108,30 @@ impl WasmMockQuerier {
                             to_binary(&Cw20BalanceResponse { balance }).unwrap(),
                         ))
                     }
+                    Cw20QueryMsg::TokenInfo {} => {
+                        let total_supply = match self.token_supplies.get(contract_addr) {
+                            Some(v) => *v,
+                            None => {
+                                return SystemResult::Err(SystemError::InvalidRequest {
+                                    error: format!(
+                                        "No supply info exists for the contract {}",
+                                        contract_addr
+                                    ),
+                                    request: msg.as_slice().into(),
+                                })
+                            }
+                        };
+
+                        SystemResult::Ok(ContractResult::Ok(
+                            to_binary(&TokenInfoResponse {
+                                name: "Anchor Token".to_string(),
+                                symbol: "ANC".to_string(),
+                                decimals: 6,
+                                total_supply,
+                            })
+                            .unwrap(),
+                        ))
+                    }
                     _ => panic!("DO NOT ENTER HERE"),
                 }
             }
@@ -120,6 +145,7 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             token_querier: TokenQuerier::default(),
+            token_supplies: HashMap::new(),
         }
     }
 
@@ -127,4 +153,11 @@ impl WasmMockQuerier {
     pub fn with_token_balances(&mut self, balances: &[(&String, &[(&String, &Uint128)])]) {
         self.token_querier = TokenQuerier::new(balances);
     }
+
+    pub fn with_token_supplies(&mut self, supplies: &[(&String, &Uint128)]) {
+        for (contract_addr, supply) in supplies.iter() {
+            self.token_supplies
+                .insert(contract_addr.to_string(), **supply);
+        }
+    }
 }
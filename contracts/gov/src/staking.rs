@@ -1,14 +1,22 @@
 use crate::error::ContractError;
 use crate::state::{
-    bank_read, bank_store, config_read, config_store, poll_read, poll_voter_store, state_read,
-    state_store, Config, Poll, State, TokenManager,
+    bank_read, bank_store, config_read, config_store, poll_read, poll_voter_read, poll_voter_store,
+    read_bank_shares, read_ended_poll_count, read_participation, read_pending_withdrawals,
+    read_share_reconciliation, read_total_pending_withdrawal, read_unstaked_balance, state_read,
+    state_store, store_participation, store_pending_withdrawals, store_share_reconciliation,
+    store_total_pending_withdrawal, Config, Participation, Poll, ShareReconciliation, State,
+    TokenManager,
 };
 
-use anchor_token::gov::{PollStatus, StakerResponse};
+use anchor_token::gov::{
+    BlockingPoll, MaxVotableAmountResponse, ParticipationResponse, PendingWithdrawal,
+    PendingWithdrawalsResponse, PollStatus, ShareInvariantResponse, StakerResponse,
+    StakersResponse, StakersResponseItem, VoterInfo, WithdrawableAmountResponse,
+};
 use astroport::querier::query_token_balance;
 use cosmwasm_std::{
-    to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, MessageInfo, Response, StdResult,
-    Storage, Uint128, WasmMsg,
+    to_binary, Addr, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, Storage, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 
@@ -28,13 +36,17 @@ pub fn stake_voting_tokens(
     let config: Config = config_store(deps.storage).load()?;
     let mut state: State = state_store(deps.storage).load()?;
 
+    // polls ended so far count for the share held before this stake
+    let participation = accrue_participation(deps.storage, &state, key, token_manager.share)?;
+    store_participation(deps.storage, key, &participation)?;
+
     // balance already increased, so subtract deposit amount
     let total_balance = query_token_balance(
         &deps.querier,
         deps.api.addr_humanize(&config.anchor_token)?,
         deps.api.addr_humanize(&state.contract_addr)?,
     )?
-    .checked_sub(state.total_deposit + amount)?;
+    .checked_sub(read_unstaked_balance(deps.storage, &state)? + amount)?;
 
     let share = if total_balance.is_zero() || state.total_share.is_zero() {
         amount
@@ -47,6 +59,7 @@ pub fn stake_voting_tokens(
 
     state_store(deps.storage).save(&state)?;
     bank_store(deps.storage).save(key, &token_manager)?;
+    restart_share_reconciliation(deps.storage)?;
 
     Ok(Response::new().add_attributes(vec![
         ("action", "staking"),
@@ -57,8 +70,10 @@ pub fn stake_voting_tokens(
 }
 
 // Withdraw amount if not staked. By default all funds will be withdrawn.
+// With an unstake cooldown, the amount is only claimable once it is over.
 pub fn withdraw_voting_tokens(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     amount: Option<Uint128>,
 ) -> Result<Response, ContractError> {
@@ -69,19 +84,19 @@ pub fn withdraw_voting_tokens(
         let config: Config = config_store(deps.storage).load()?;
         let mut state: State = state_store(deps.storage).load()?;
 
-        // Load total share & total balance except proposal deposit amount
-        let total_share = state.total_share.u128();
-        let total_balance = query_token_balance(
-            &deps.querier,
-            deps.api.addr_humanize(&config.anchor_token)?,
-            deps.api.addr_humanize(&state.contract_addr)?,
-        )?
-        .checked_sub(state.total_deposit)?
-        .u128();
-
-        let locked_balance =
-            compute_locked_balance(deps.storage, &mut token_manager, &sender_address_raw);
-        let locked_share = locked_balance * total_share / total_balance;
+        // polls ended so far count for the share held before this withdrawal
+        let participation = accrue_participation(deps.storage, &state, key, token_manager.share)?;
+        store_participation(deps.storage, key, &participation)?;
+
+        // removes not in-progress poll voter info & unlock tokens
+        for poll_id in prune_locked_balance(deps.storage, &mut token_manager)? {
+            poll_voter_store(deps.storage, poll_id).remove(key);
+        }
+
+        let withdrawable = compute_withdrawable(deps.as_ref(), &config, &state, &token_manager)?;
+        let total_share = withdrawable.total_share;
+        let total_balance = withdrawable.total_balance;
+        let locked_share = withdrawable.locked_share;
         let user_share = token_manager.share.u128();
 
         let withdraw_share = amount
@@ -92,7 +107,9 @@ pub fn withdraw_voting_tokens(
             .unwrap_or_else(|| withdraw_share * total_balance / total_share);
 
         if locked_share + withdraw_share > user_share {
-            Err(ContractError::InvalidWithdrawAmount {})
+            Err(ContractError::InvalidWithdrawAmount(
+                withdrawable.withdrawable_amount,
+            ))
         } else {
             let share = user_share - withdraw_share;
             token_manager.share = Uint128::from(share);
@@ -101,6 +118,30 @@ pub fn withdraw_voting_tokens(
 
             state.total_share = Uint128::from(total_share - withdraw_share);
             state_store(deps.storage).save(&state)?;
+            restart_share_reconciliation(deps.storage)?;
+
+            if let Some(unstake_cooldown) = config.unstake_cooldown {
+                let claimable_at = env.block.time.seconds() + unstake_cooldown;
+                let mut withdrawals = read_pending_withdrawals(deps.storage, key)?;
+                withdrawals.push(PendingWithdrawal {
+                    amount: Uint128::from(withdraw_amount),
+                    claimable_at,
+                });
+                store_pending_withdrawals(deps.storage, key, &withdrawals)?;
+
+                let total_pending = read_total_pending_withdrawal(deps.storage)?;
+                store_total_pending_withdrawal(
+                    deps.storage,
+                    total_pending + Uint128::from(withdraw_amount),
+                )?;
+
+                return Ok(Response::new().add_attributes(vec![
+                    ("action", "request_withdraw"),
+                    ("sender", info.sender.as_str()),
+                    ("amount", withdraw_amount.to_string().as_str()),
+                    ("claimable_at", claimable_at.to_string().as_str()),
+                ]));
+            }
 
             send_tokens(
                 deps,
@@ -115,30 +156,239 @@ pub fn withdraw_voting_tokens(
     }
 }
 
-// removes not in-progress poll voter info & unlock tokens
-// and returns the largest locked amount in participated polls.
-fn compute_locked_balance(
-    storage: &mut dyn Storage,
+// Transfers the pending withdrawals of the sender whose cooldown is over
+pub fn claim_unstaked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let key = sender_address_raw.as_slice();
+
+    let (claimable, pending): (Vec<PendingWithdrawal>, Vec<PendingWithdrawal>) =
+        read_pending_withdrawals(deps.storage, key)?
+            .into_iter()
+            .partition(|withdrawal| withdrawal.claimable_at <= env.block.time.seconds());
+
+    let amount: Uint128 = claimable.iter().map(|withdrawal| withdrawal.amount).sum();
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    store_pending_withdrawals(deps.storage, key, &pending)?;
+    let total_pending = read_total_pending_withdrawal(deps.storage)?;
+    store_total_pending_withdrawal(deps.storage, total_pending.checked_sub(amount)?)?;
+
+    let config: Config = config_read(deps.storage).load()?;
+    send_tokens(
+        deps,
+        &config.anchor_token,
+        &sender_address_raw,
+        amount.u128(),
+        "claim_unstaked",
+    )
+}
+
+const MAX_STAKERS_PER_RECONCILE: u32 = 30;
+
+/// reconcile_total_share sums the shares of the stakers in batches,
+/// and replaces the total share with the sum after the last batch, which
+/// reads fewer stakers than the limit. Anyone can run it, at most one batch
+/// per block. Shares changing during the scan restart it from the first staker
+pub fn reconcile_total_share(
+    deps: DepsMut,
+    env: Env,
+    start_after: Option<String>,
+    limit: u32,
+) -> Result<Response, ContractError> {
+    let mut reconciliation = read_share_reconciliation(deps.storage)?;
+    if reconciliation.last_batch_height >= env.block.height {
+        return Err(ContractError::ReconcileRateLimited {});
+    }
+
+    let start_after = start_after
+        .map(|addr| deps.api.addr_canonicalize(&addr))
+        .transpose()?;
+    match start_after {
+        // a new scan, dropping the one in progress if any
+        None => reconciliation.accumulated_share = Uint128::zero(),
+        Some(_) => {
+            if reconciliation.last_staker != start_after {
+                return Err(ContractError::ReconcileCursorMismatch {});
+            }
+        }
+    }
+
+    let limit = limit.clamp(1, MAX_STAKERS_PER_RECONCILE) as usize;
+    let shares = read_bank_shares(deps.storage, start_after, limit)?;
+    for (_, share) in shares.iter() {
+        reconciliation.accumulated_share += *share;
+    }
+    reconciliation.last_batch_height = env.block.height;
+
+    if shares.len() == limit {
+        let (last_staker, _) = &shares[limit - 1];
+        reconciliation.last_staker = Some(last_staker.clone());
+        store_share_reconciliation(deps.storage, &reconciliation)?;
+
+        let last_staker = deps.api.addr_humanize(last_staker)?;
+        return Ok(Response::new().add_attributes(vec![
+            ("action", "reconcile_total_share"),
+            ("last_staker", last_staker.as_str()),
+            (
+                "accumulated_share",
+                reconciliation.accumulated_share.to_string().as_str(),
+            ),
+        ]));
+    }
+
+    let mut state: State = state_store(deps.storage).load()?;
+    let total_share_before = state.total_share;
+    state.total_share = reconciliation.accumulated_share;
+    state_store(deps.storage).save(&state)?;
+
+    store_share_reconciliation(
+        deps.storage,
+        &ShareReconciliation {
+            last_batch_height: env.block.height,
+            ..ShareReconciliation::default()
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "reconcile_total_share"),
+        (
+            "total_share_before",
+            total_share_before.to_string().as_str(),
+        ),
+        ("total_share_after", state.total_share.to_string().as_str()),
+    ]))
+}
+
+// drops the sum of a scan in progress once a share changed
+fn restart_share_reconciliation(storage: &mut dyn Storage) -> StdResult<()> {
+    let reconciliation = read_share_reconciliation(storage)?;
+    if reconciliation.last_staker.is_none() {
+        return Ok(());
+    }
+
+    store_share_reconciliation(
+        storage,
+        &ShareReconciliation {
+            last_batch_height: reconciliation.last_batch_height,
+            ..ShareReconciliation::default()
+        },
+    )
+}
+
+/// accrue_participation counts the polls ended since the last accrual
+/// as eligible when the staker had a share. The share of a staker only
+/// changes when it stakes or withdraws, which accrue first, so it held the
+/// same share when each of these polls ended. Polls are not snapshotted per
+/// staker, so this counts the staked share at the end of the poll rather
+/// than at its snapshot. Stakers without a record start from the current
+/// number of ended polls
+pub fn accrue_participation(
+    storage: &dyn Storage,
+    state: &State,
+    key: &[u8],
+    share: Uint128,
+) -> StdResult<Participation> {
+    let ended_polls = read_ended_poll_count(storage, state.poll_count)?;
+    let mut participation = match read_participation(storage, key)? {
+        Some(participation) => participation,
+        None => {
+            return Ok(Participation {
+                ended_polls,
+                ..Participation::default()
+            })
+        }
+    };
+
+    if !share.is_zero() {
+        participation.polls_eligible += ended_polls.saturating_sub(participation.ended_polls);
+    }
+    participation.ended_polls = ended_polls;
+
+    Ok(participation)
+}
+
+// removes not in-progress polls from the locked balance
+// and returns the ids of the removed polls.
+pub fn prune_locked_balance(
+    storage: &dyn Storage,
     token_manager: &mut TokenManager,
-    voter: &CanonicalAddr,
-) -> u128 {
-    token_manager.locked_balance.retain(|(poll_id, _)| {
-        let poll: Poll = poll_read(storage).load(&poll_id.to_be_bytes()).unwrap();
-
-        if poll.status != PollStatus::InProgress {
-            // remove voter info from the poll
-            poll_voter_store(storage, *poll_id).remove(voter.as_slice());
+) -> StdResult<Vec<u64>> {
+    let mut removed_polls: Vec<u64> = vec![];
+    let mut locked_balance: Vec<(u64, VoterInfo)> = vec![];
+    for (poll_id, voter_info) in token_manager.locked_balance.drain(..) {
+        let poll: Poll = poll_read(storage).load(&poll_id.to_be_bytes())?;
+        if poll.status == PollStatus::InProgress {
+            locked_balance.push((poll_id, voter_info));
+        } else {
+            removed_polls.push(poll_id);
         }
+    }
+
+    token_manager.locked_balance = locked_balance;
+    Ok(removed_polls)
+}
 
-        poll.status == PollStatus::InProgress
-    });
+struct Withdrawable {
+    total_share: u128,
+    total_balance: u128,
+    locked_balance: u128,
+    locked_share: u128,
+    withdrawable_amount: u128,
+}
+
+// computes the withdrawal limits of a staker whose locked balance
+// has already been pruned; the locked amount is the largest amount
+// voted in the in-progress polls.
+fn compute_withdrawable(
+    deps: Deps,
+    config: &Config,
+    state: &State,
+    token_manager: &TokenManager,
+) -> StdResult<Withdrawable> {
+    // Load total share & total balance except proposal deposit amount
+    let total_share = state.total_share.u128();
+    let total_balance = query_token_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.anchor_token)?,
+        deps.api.addr_humanize(&state.contract_addr)?,
+    )?
+    .checked_sub(read_unstaked_balance(deps.storage, state)?)?
+    .u128();
 
-    token_manager
+    let locked_balance = token_manager
         .locked_balance
         .iter()
         .map(|(_, v)| v.balance.u128())
         .max()
-        .unwrap_or_default()
+        .unwrap_or_default();
+
+    if total_share == 0 || total_balance == 0 {
+        return Ok(Withdrawable {
+            total_share,
+            total_balance,
+            locked_balance,
+            locked_share: 0,
+            withdrawable_amount: 0,
+        });
+    }
+
+    let locked_share = locked_balance * total_share / total_balance;
+    let withdrawable_amount =
+        token_manager.share.u128().saturating_sub(locked_share) * total_balance / total_share;
+
+    Ok(Withdrawable {
+        total_share,
+        total_balance,
+        locked_balance,
+        locked_share,
+        withdrawable_amount,
+    })
 }
 
 fn send_tokens(
@@ -167,29 +417,123 @@ fn send_tokens(
         ]))
 }
 
+const MAX_STAKERS_PER_QUERY: usize = 30;
+
+pub fn query_participation(deps: Deps, address: String) -> StdResult<ParticipationResponse> {
+    let addr_raw = deps.api.addr_canonicalize(&address)?;
+    let state: State = state_read(deps.storage).load()?;
+    let token_manager = bank_read(deps.storage)
+        .may_load(addr_raw.as_slice())?
+        .unwrap_or_default();
+    let participation = accrue_participation(
+        deps.storage,
+        &state,
+        addr_raw.as_slice(),
+        token_manager.share,
+    )?;
+
+    // votes on polls still in progress are not eligible yet
+    let ratio = if participation.polls_eligible == 0 {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(
+            participation.polls_voted.min(participation.polls_eligible),
+            participation.polls_eligible,
+        )
+    };
+
+    Ok(ParticipationResponse {
+        polls_voted: participation.polls_voted,
+        polls_eligible: participation.polls_eligible,
+        ratio,
+    })
+}
+
+pub fn query_share_invariant(deps: Deps) -> StdResult<ShareInvariantResponse> {
+    let state: State = state_read(deps.storage).load()?;
+    let reconciliation = read_share_reconciliation(deps.storage)?;
+
+    Ok(ShareInvariantResponse {
+        total_share: state.total_share,
+        accumulated_share: reconciliation.accumulated_share,
+        in_progress: reconciliation.last_staker.is_some(),
+        last_staker: reconciliation
+            .last_staker
+            .map(|addr| deps.api.addr_humanize(&addr).map(|addr| addr.to_string()))
+            .transpose()?,
+    })
+}
+
+pub fn query_pending_withdrawals(
+    deps: Deps,
+    address: String,
+) -> StdResult<PendingWithdrawalsResponse> {
+    let addr_raw = deps.api.addr_canonicalize(&address)?;
+    Ok(PendingWithdrawalsResponse {
+        withdrawals: read_pending_withdrawals(deps.storage, addr_raw.as_slice())?,
+    })
+}
+
 pub fn query_staker(deps: Deps, address: String) -> StdResult<StakerResponse> {
     let addr_raw = deps.api.addr_canonicalize(&address).unwrap();
     let config: Config = config_read(deps.storage).load()?;
     let state: State = state_read(deps.storage).load()?;
-    let mut token_manager = bank_read(deps.storage)
-        .may_load(addr_raw.as_slice())?
-        .unwrap_or_default();
+    let total_balance = query_total_balance(deps, &config, &state)?;
 
-    // filter out not in-progress polls
-    token_manager.locked_balance.retain(|(poll_id, _)| {
-        let poll: Poll = poll_read(deps.storage)
-            .load(&poll_id.to_be_bytes())
-            .unwrap();
+    staker_response(deps, &state, total_balance, &addr_raw)
+}
 
-        poll.status == PollStatus::InProgress
-    });
+/// Builds the staker responses of the given addresses sharing
+/// a single query of the total staked balance
+pub fn query_stakers_by_addresses(
+    deps: Deps,
+    addresses: Vec<String>,
+) -> Result<StakersResponse, ContractError> {
+    if addresses.len() > MAX_STAKERS_PER_QUERY {
+        return Err(ContractError::TooManyAddresses(MAX_STAKERS_PER_QUERY));
+    }
 
-    let total_balance = query_token_balance(
+    let config: Config = config_read(deps.storage).load()?;
+    let state: State = state_read(deps.storage).load()?;
+    let total_balance = query_total_balance(deps, &config, &state)?;
+
+    let mut stakers: Vec<StakersResponseItem> = vec![];
+    for address in addresses {
+        let addr_raw = deps.api.addr_canonicalize(&address)?;
+        let staker = staker_response(deps, &state, total_balance, &addr_raw)?;
+        stakers.push(StakersResponseItem {
+            address,
+            balance: staker.balance,
+            share: staker.share,
+            locked_balance: staker.locked_balance,
+        });
+    }
+
+    Ok(StakersResponse { stakers })
+}
+
+// staked balance excluding the poll deposits
+fn query_total_balance(deps: Deps, config: &Config, state: &State) -> StdResult<Uint128> {
+    Ok(query_token_balance(
         &deps.querier,
         deps.api.addr_humanize(&config.anchor_token)?,
         deps.api.addr_humanize(&state.contract_addr)?,
     )?
-    .checked_sub(state.total_deposit)?;
+    .checked_sub(read_unstaked_balance(deps.storage, state)?)?)
+}
+
+fn staker_response(
+    deps: Deps,
+    state: &State,
+    total_balance: Uint128,
+    addr_raw: &CanonicalAddr,
+) -> StdResult<StakerResponse> {
+    let mut token_manager = bank_read(deps.storage)
+        .may_load(addr_raw.as_slice())?
+        .unwrap_or_default();
+
+    // filter out not in-progress polls
+    prune_locked_balance(deps.storage, &mut token_manager)?;
 
     Ok(StakerResponse {
         balance: if !state.total_share.is_zero() {
@@ -203,3 +547,89 @@ pub fn query_staker(deps: Deps, address: String) -> StdResult<StakerResponse> {
         locked_balance: token_manager.locked_balance,
     })
 }
+
+pub fn query_withdrawable_amount(
+    deps: Deps,
+    address: String,
+) -> StdResult<WithdrawableAmountResponse> {
+    let addr_raw = deps.api.addr_canonicalize(&address)?;
+    let config: Config = config_read(deps.storage).load()?;
+    let state: State = state_read(deps.storage).load()?;
+    let mut token_manager = bank_read(deps.storage)
+        .may_load(addr_raw.as_slice())?
+        .unwrap_or_default();
+
+    // filter out not in-progress polls the same way a withdrawal would
+    prune_locked_balance(deps.storage, &mut token_manager)?;
+
+    let withdrawable = compute_withdrawable(deps, &config, &state, &token_manager)?;
+    let blocking_polls: StdResult<Vec<BlockingPoll>> = token_manager
+        .locked_balance
+        .iter()
+        .map(|(poll_id, voter_info)| {
+            let poll: Poll = poll_read(deps.storage).load(&poll_id.to_be_bytes())?;
+            Ok(BlockingPoll {
+                poll_id: *poll_id,
+                balance: voter_info.balance,
+                end_height: poll.end_height,
+            })
+        })
+        .collect();
+
+    Ok(WithdrawableAmountResponse {
+        withdrawable: Uint128::from(withdrawable.withdrawable_amount),
+        locked: Uint128::from(withdrawable.locked_balance),
+        blocking_polls: blocking_polls?,
+    })
+}
+
+/// The whole staked amount can be cast on each poll, as checked by cast_vote
+pub fn query_max_votable_amount(
+    deps: Deps,
+    env: Env,
+    poll_id: u64,
+    address: String,
+) -> StdResult<MaxVotableAmountResponse> {
+    let not_votable = MaxVotableAmountResponse {
+        amount: Uint128::zero(),
+    };
+
+    let poll: Poll = match poll_read(deps.storage).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => poll,
+        None => return Ok(not_votable),
+    };
+    let voting_closed = match poll.end_time {
+        Some(end_time) => env.block.time.seconds() > end_time,
+        None => env.block.height > poll.end_height,
+    };
+    if poll.status != PollStatus::InProgress || voting_closed {
+        return Ok(not_votable);
+    }
+
+    let addr_raw = deps.api.addr_canonicalize(&address)?;
+    if poll_voter_read(deps.storage, poll_id)
+        .may_load(addr_raw.as_slice())?
+        .is_some()
+    {
+        return Ok(not_votable);
+    }
+
+    let config: Config = config_read(deps.storage).load()?;
+    let state: State = state_read(deps.storage).load()?;
+    let total_balance = query_total_balance(deps, &config, &state)?;
+    let staker = staker_response(deps, &state, total_balance, &addr_raw)?;
+
+    if let Some(max_participations) = config.max_participations {
+        if staker.locked_balance.len() as u64 >= max_participations {
+            return Ok(not_votable);
+        }
+    }
+
+    if staker.balance < config.min_vote_amount {
+        return Ok(not_votable);
+    }
+
+    Ok(MaxVotableAmountResponse {
+        amount: staker.balance,
+    })
+}
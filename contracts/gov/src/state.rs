@@ -7,17 +7,26 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use anchor_token::common::OrderBy;
-use anchor_token::gov::{PollStatus, VoterInfo};
+use anchor_token::gov::{
+    ExecuteTargetPolicy, PendingWithdrawal, PollLengthLimits, PollMsgType, PollStatus, QuorumBasis,
+    VoterInfo,
+};
 use std::cmp::Ordering;
 
-static KEY_CONFIG: &[u8] = b"config";
+pub static KEY_CONFIG: &[u8] = b"config";
 static KEY_STATE: &[u8] = b"state";
 static KEY_TMP_POLL_ID: &[u8] = b"tmp_poll_id";
+static KEY_PENDING_OWNER: &[u8] = b"pending_owner";
+static KEY_TOTAL_PENDING_WITHDRAWAL: &[u8] = b"total_pending_withdrawal";
+static KEY_SHARE_RECONCILIATION: &[u8] = b"share_reconciliation";
 
 static PREFIX_POLL_INDEXER: &[u8] = b"poll_indexer";
 static PREFIX_POLL_VOTER: &[u8] = b"poll_voter";
 static PREFIX_POLL: &[u8] = b"poll";
 static PREFIX_BANK: &[u8] = b"bank";
+static PREFIX_POLL_STATUS_COUNT: &[u8] = b"poll_status_count";
+static PREFIX_PARTICIPATION: &[u8] = b"participation";
+static PREFIX_PENDING_WITHDRAWALS: &[u8] = b"pending_withdrawals";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -30,6 +39,29 @@ pub struct Config {
     pub expiration_period: u64,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    pub quorum_basis: QuorumBasis,
+    pub excluded_addresses: Vec<CanonicalAddr>,
+    pub stale_poll_grace_period: u64,
+    pub veto_period: u64,
+    pub guardian: Option<CanonicalAddr>,
+    pub poll_length_limits: PollLengthLimits,
+    pub use_time: bool,
+    pub voting_period_seconds: u64,
+    pub snapshot_period_seconds: u64,
+    pub timelock_period_seconds: u64,
+    pub stale_poll_grace_period_seconds: u64,
+    pub max_participations: Option<u64>,
+    pub execute_target_policy: Option<ExecuteTargetPolicy>,
+    pub min_vote_amount: Uint128,
+    pub veto_threshold: Decimal,
+    pub community_contract: Option<CanonicalAddr>,
+    pub quorum_reduction_after: Option<u64>,
+    pub quorum_reduction_after_seconds: Option<u64>,
+    pub quorum_reduction_factor: Decimal,
+    pub allow_opaque_execute_msgs: bool,
+    pub allow_vote_change: bool,
+    pub min_yes_votes: Uint128,
+    pub unstake_cooldown: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -46,6 +78,24 @@ pub struct TokenManager {
     pub locked_balance: Vec<(u64, VoterInfo)>, // maps poll_id to weight voted
 }
 
+/// Participation of a staker, eligible polls are accrued lazily
+/// whenever the staker interacts, see staking::accrue_participation
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Participation {
+    pub polls_voted: u64,
+    pub polls_eligible: u64,
+    pub ended_polls: u64, // number of ended polls at the last accrual
+}
+
+/// Progress of the reconciliation of the total share,
+/// see staking::reconcile_total_share
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ShareReconciliation {
+    pub last_staker: Option<CanonicalAddr>, // none when no scan is in progress
+    pub accumulated_share: Uint128,
+    pub last_batch_height: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Poll {
     pub id: u64,
@@ -53,7 +103,11 @@ pub struct Poll {
     pub status: PollStatus,
     pub yes_votes: Uint128,
     pub no_votes: Uint128,
+    /// No with veto votes, not recorded for polls created before
+    pub veto_votes: Option<Uint128>,
     pub end_height: u64,
+    /// End time of time based polls; their end_height is set when they end
+    pub end_time: Option<u64>,
     pub title: String,
     pub description: String,
     pub link: Option<String>,
@@ -62,6 +116,23 @@ pub struct Poll {
     /// Total balance at the end poll
     pub total_balance_at_end_poll: Option<Uint128>,
     pub staked_amount: Option<Uint128>,
+    /// Block height at which the execution of the poll was attempted
+    pub execution_height: Option<u64>,
+    /// Error returned by the poll messages when the execution failed
+    pub failure_reason: Option<String>,
+    /// Whether the messages are executed together, true when omitted
+    pub atomic: Option<bool>,
+    /// (order, succeeded, error) of each message of an executed non atomic poll
+    pub execution_results: Option<Vec<(u64, bool, Option<String>)>>,
+    /// Whether the voting period was extended for missing the quorum
+    pub extended: Option<bool>,
+    /// Hash of the full proposal pinned off-chain, not recorded for polls created before
+    pub content_hash: Option<String>,
+    /// Periods applying to the poll, fixed at its creation in seconds for time based
+    /// polls and in blocks otherwise; the config applies to polls created before
+    pub voting_period: Option<u64>,
+    pub snapshot_period: Option<u64>,
+    pub timelock_period: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -69,6 +140,7 @@ pub struct ExecuteData {
     pub order: u64,
     pub contract: CanonicalAddr,
     pub msg: Binary,
+    pub msg_type: Option<PollMsgType>,
 }
 impl Eq for ExecuteData {}
 
@@ -106,6 +178,21 @@ pub fn state_read(storage: &dyn Storage) -> ReadonlySingleton<State> {
     singleton_read(storage, KEY_STATE)
 }
 
+pub fn store_pending_owner(
+    storage: &mut dyn Storage,
+    pending_owner: &CanonicalAddr,
+) -> StdResult<()> {
+    singleton(storage, KEY_PENDING_OWNER).save(pending_owner)
+}
+
+pub fn remove_pending_owner(storage: &mut dyn Storage) {
+    singleton::<CanonicalAddr>(storage, KEY_PENDING_OWNER).remove()
+}
+
+pub fn read_pending_owner(storage: &dyn Storage) -> StdResult<Option<CanonicalAddr>> {
+    singleton_read(storage, KEY_PENDING_OWNER).may_load()
+}
+
 pub fn store_tmp_poll_id(storage: &mut dyn Storage, tmp_poll_id: u64) -> StdResult<()> {
     singleton(storage, KEY_TMP_POLL_ID).save(&tmp_poll_id)
 }
@@ -132,6 +219,65 @@ pub fn poll_indexer_store<'a>(
     )
 }
 
+/// Moves a poll to the indexer of its new status,
+/// keeping the number of polls per status up to date
+pub fn update_poll_indexer(
+    storage: &mut dyn Storage,
+    poll_id: u64,
+    from: Option<&PollStatus>,
+    to: &PollStatus,
+) -> StdResult<()> {
+    if let Some(from) = from {
+        poll_indexer_store(storage, from).remove(&poll_id.to_be_bytes());
+        let count = read_poll_status_count(storage, from)?;
+        store_poll_status_count(storage, from, count.saturating_sub(1))?;
+    }
+
+    poll_indexer_store(storage, to).save(&poll_id.to_be_bytes(), &true)?;
+    let count = read_poll_status_count(storage, to)?;
+    store_poll_status_count(storage, to, count + 1)
+}
+
+pub fn store_poll_status_count(
+    storage: &mut dyn Storage,
+    status: &PollStatus,
+    count: u64,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_POLL_STATUS_COUNT).save(status.to_string().as_bytes(), &count)
+}
+
+pub fn read_poll_status_count(storage: &dyn Storage, status: &PollStatus) -> StdResult<u64> {
+    Ok(bucket_read(storage, PREFIX_POLL_STATUS_COUNT)
+        .may_load(status.to_string().as_bytes())?
+        .unwrap_or_default())
+}
+
+/// Counts the polls of the given status by iterating its indexer
+pub fn count_indexed_polls(storage: &dyn Storage, status: &PollStatus) -> u64 {
+    let poll_indexer: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_POLL_INDEXER, status.to_string().as_bytes()],
+    );
+
+    poll_indexer.range(None, None, OrderBy::Asc.into()).count() as u64
+}
+
+/// Returns the ids of all the polls of the given status
+pub fn read_indexed_poll_ids(storage: &dyn Storage, status: &PollStatus) -> StdResult<Vec<u64>> {
+    let poll_indexer: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_POLL_INDEXER, status.to_string().as_bytes()],
+    );
+
+    poll_indexer
+        .range(None, None, OrderBy::Asc.into())
+        .map(|item| {
+            let (k, _) = item?;
+            Ok(poll_read(storage).load(&k)?.id)
+        })
+        .collect()
+}
+
 pub fn poll_voter_store(storage: &mut dyn Storage, poll_id: u64) -> Bucket<VoterInfo> {
     Bucket::multilevel(storage, &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()])
 }
@@ -207,6 +353,134 @@ pub fn read_polls<'a>(
     }
 }
 
+const MAX_SCANNED_POLLS: usize = 100;
+
+/// Returns the ids of the passed polls after `start_after` for which `is_executable`
+/// holds, and the id of the last passed poll scanned. At most MAX_SCANNED_POLLS
+/// passed polls are scanned, the next page starts after the last scanned one
+pub fn read_executable_poll_ids(
+    storage: &dyn Storage,
+    is_executable: &dyn Fn(&Poll) -> bool,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<(Vec<u64>, Option<u64>)> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    let poll_indexer: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[
+            PREFIX_POLL_INDEXER,
+            PollStatus::Passed.to_string().as_bytes(),
+        ],
+    );
+
+    let mut poll_ids: Vec<u64> = vec![];
+    let mut last_scanned: Option<u64> = None;
+    for item in poll_indexer
+        .range(start.as_deref(), None, OrderBy::Asc.into())
+        .take(MAX_SCANNED_POLLS)
+    {
+        if poll_ids.len() >= limit {
+            break;
+        }
+
+        let (k, _) = item?;
+        let poll: Poll = poll_read(storage).load(&k)?;
+        last_scanned = Some(poll.id);
+        if is_executable(&poll) {
+            poll_ids.push(poll.id);
+        }
+    }
+
+    Ok((poll_ids, last_scanned))
+}
+
+pub fn store_participation(
+    storage: &mut dyn Storage,
+    key: &[u8],
+    participation: &Participation,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_PARTICIPATION).save(key, participation)
+}
+
+pub fn read_participation(storage: &dyn Storage, key: &[u8]) -> StdResult<Option<Participation>> {
+    bucket_read(storage, PREFIX_PARTICIPATION).may_load(key)
+}
+
+/// Number of polls that are no longer in progress
+pub fn read_ended_poll_count(storage: &dyn Storage, poll_count: u64) -> StdResult<u64> {
+    Ok(poll_count.saturating_sub(read_poll_status_count(storage, &PollStatus::InProgress)?))
+}
+
+pub fn store_pending_withdrawals(
+    storage: &mut dyn Storage,
+    key: &[u8],
+    withdrawals: &[PendingWithdrawal],
+) -> StdResult<()> {
+    if withdrawals.is_empty() {
+        bucket::<Vec<PendingWithdrawal>>(storage, PREFIX_PENDING_WITHDRAWALS).remove(key);
+        Ok(())
+    } else {
+        bucket(storage, PREFIX_PENDING_WITHDRAWALS).save(key, &withdrawals.to_vec())
+    }
+}
+
+pub fn read_pending_withdrawals(
+    storage: &dyn Storage,
+    key: &[u8],
+) -> StdResult<Vec<PendingWithdrawal>> {
+    Ok(bucket_read(storage, PREFIX_PENDING_WITHDRAWALS)
+        .may_load(key)?
+        .unwrap_or_default())
+}
+
+pub fn store_total_pending_withdrawal(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    singleton(storage, KEY_TOTAL_PENDING_WITHDRAWAL).save(&amount)
+}
+
+pub fn read_total_pending_withdrawal(storage: &dyn Storage) -> StdResult<Uint128> {
+    Ok(singleton_read(storage, KEY_TOTAL_PENDING_WITHDRAWAL)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+/// Balance held by the contract which is not staked,
+/// the poll deposits and the withdrawals waiting for the unstake cooldown
+pub fn read_unstaked_balance(storage: &dyn Storage, state: &State) -> StdResult<Uint128> {
+    Ok(state.total_deposit + read_total_pending_withdrawal(storage)?)
+}
+
+pub fn store_share_reconciliation(
+    storage: &mut dyn Storage,
+    reconciliation: &ShareReconciliation,
+) -> StdResult<()> {
+    singleton(storage, KEY_SHARE_RECONCILIATION).save(reconciliation)
+}
+
+pub fn read_share_reconciliation(storage: &dyn Storage) -> StdResult<ShareReconciliation> {
+    Ok(singleton_read(storage, KEY_SHARE_RECONCILIATION)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+/// Shares of at most `limit` stakers after `start_after`, in address order
+pub fn read_bank_shares(
+    storage: &dyn Storage,
+    start_after: Option<CanonicalAddr>,
+    limit: usize,
+) -> StdResult<Vec<(CanonicalAddr, Uint128)>> {
+    let start = calc_range_start_addr(start_after);
+    bank_read(storage)
+        .range(start.as_deref(), None, OrderBy::Asc.into())
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((CanonicalAddr::from(k), v.share))
+        })
+        .collect()
+}
+
 pub fn bank_store(storage: &mut dyn Storage) -> Bucket<TokenManager> {
     bucket(storage, PREFIX_BANK)
 }
@@ -1,24 +1,32 @@
-use crate::contract::{execute, instantiate, query, reply};
+use crate::contract::{execute, instantiate, migrate, query, reply};
 use crate::error::ContractError;
+use crate::migration::{migrate_poll_periods, migrate_poll_status_counts, LegacyConfig};
 use crate::mock_querier::mock_dependencies;
 use crate::state::{
-    bank_read, bank_store, config_read, poll_store, poll_voter_read, poll_voter_store, state_read,
-    Config, Poll, State, TokenManager,
+    bank_read, bank_store, config_read, poll_indexer_store, poll_read, poll_store, poll_voter_read,
+    poll_voter_store, state_read, state_store, Config, ExecuteData, Poll, State, TokenManager,
+    KEY_CONFIG,
 };
 
 use anchor_token::common::OrderBy;
 use anchor_token::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollExecuteMsg, PollResponse,
-    PollStatus, PollsResponse, QueryMsg, StakerResponse, VoteOption, VoterInfo, VotersResponse,
-    VotersResponseItem,
+    BlockingPoll, ConfigResponse, Cw20HookMsg, EffectiveStakedResponse, EndPollReadinessResponse,
+    ExecutablePollsResponse, ExecuteMsg, ExecuteTargetPolicy, InstantiateMsg,
+    MaxVotableAmountResponse, MigrateMsg, ParticipationResponse, PendingWithdrawal,
+    PendingWithdrawalsResponse, PollCountByStatusResponse, PollExecuteMsg, PollLengthLimits,
+    PollMsgType, PollResponse, PollStatus, PollsResponse, QueryMsg, QuorumBasis,
+    ShareInvariantResponse, StakedSource, StakerResponse, StakersResponse, StakersResponseItem,
+    VoteOption, VoterInfo, VotersResponse, VotersResponseItem, WithdrawableAmountResponse,
 };
 use astroport::querier::query_token_balance;
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coins, from_binary, to_binary, Addr, Api, CanonicalAddr, ContractResult, CosmosMsg,
-    Decimal, Deps, DepsMut, Env, Reply, Response, StdError, SubMsg, Timestamp, Uint128, WasmMsg,
+    attr, coins, from_binary, from_slice, to_binary, Addr, Api, Binary, CanonicalAddr,
+    ContractResult, CosmosMsg, Decimal, Deps, DepsMut, Env, Reply, Response, StdError, SubMsg,
+    Timestamp, Uint128, WasmMsg,
 };
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cosmwasm_storage::singleton;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration};
 
 const VOTING_TOKEN: &str = "voting_token";
 const TEST_CREATOR: &str = "creator";
@@ -40,6 +48,29 @@ fn mock_instantiate(deps: DepsMut) {
         timelock_period: DEFAULT_TIMELOCK_PERIOD,
         proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
     };
 
     let info = mock_info(TEST_CREATOR, &[]);
@@ -71,6 +102,29 @@ fn instantiate_msg() -> InstantiateMsg {
         timelock_period: DEFAULT_TIMELOCK_PERIOD,
         proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
     }
 }
 
@@ -95,10 +149,39 @@ fn proper_initialization() {
             timelock_period: DEFAULT_TIMELOCK_PERIOD,
             expiration_period: 0u64, // Deprecated
             proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
-            snapshot_period: DEFAULT_FIX_PERIOD
+            snapshot_period: DEFAULT_FIX_PERIOD,
+            quorum_basis: QuorumBasis::Staked,
+            excluded_addresses: vec![],
+            stale_poll_grace_period: 0u64,
+            veto_period: 0u64,
+            guardian: None,
+            poll_length_limits: PollLengthLimits::default(),
+            use_time: false,
+            voting_period_seconds: 0u64,
+            snapshot_period_seconds: 0u64,
+            timelock_period_seconds: 0u64,
+            stale_poll_grace_period_seconds: 0u64,
+            max_participations: None,
+            execute_target_policy: None,
+            min_vote_amount: Uint128::zero(),
+            veto_threshold: Decimal::one(),
+            community_contract: None,
+            quorum_reduction_after: None,
+            quorum_reduction_after_seconds: None,
+            quorum_reduction_factor: Decimal::one(),
+            allow_opaque_execute_msgs: false,
+            allow_vote_change: false,
+            min_yes_votes: Uint128::zero(),
+            unstake_cooldown: None,
         }
     );
 
+    // the anchor token is not registered yet
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert!(!config.registered);
+    assert_eq!(config.anchor_token, None);
+
     let msg = ExecuteMsg::RegisterContracts {
         anchor_token: VOTING_TOKEN.to_string(),
     };
@@ -109,6 +192,11 @@ fn proper_initialization() {
         deps.api.addr_canonicalize(VOTING_TOKEN).unwrap()
     );
 
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert!(config.registered);
+    assert_eq!(config.anchor_token, Some(VOTING_TOKEN.to_string()));
+
     let state: State = state_read(deps.as_ref().storage).load().unwrap();
     assert_eq!(
         state,
@@ -147,6 +235,29 @@ fn fails_init_invalid_quorum() {
         timelock_period: DEFAULT_TIMELOCK_PERIOD,
         proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
     };
 
     let res = instantiate(deps.as_mut(), mock_env(), info, msg);
@@ -171,6 +282,29 @@ fn fails_init_invalid_threshold() {
         timelock_period: DEFAULT_TIMELOCK_PERIOD,
         proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
     };
 
     let res = instantiate(deps.as_mut(), mock_env(), info, msg);
@@ -195,6 +329,29 @@ fn fails_contract_already_registered() {
         timelock_period: DEFAULT_TIMELOCK_PERIOD,
         proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
     };
 
     let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -312,6 +469,110 @@ fn fails_create_poll_invalid_link() {
     }
 }
 
+#[test]
+fn create_poll_with_custom_length_limits() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let limits = PollLengthLimits {
+        min_title_length: 2,
+        max_title_length: 8,
+        min_desc_length: 2,
+        max_desc_length: 16,
+        min_link_length: 4,
+        max_link_length: 20,
+    };
+    let update_config_msg = |poll_length_limits| ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: Some(poll_length_limits),
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+    };
+
+    // min > max is rejected
+    let msg = update_config_msg(PollLengthLimits {
+        min_link_length: 21,
+        ..limits.clone()
+    });
+    let info = mock_info(TEST_CREATOR, &[]);
+    match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+        Ok(_) => panic!("Must return error"),
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(
+                msg,
+                "minimum length must not be greater than maximum length"
+            )
+        }
+        Err(_) => panic!("Unknown error"),
+    }
+
+    let msg = update_config_msg(limits.clone());
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.poll_length_limits, limits);
+
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let cases = vec![
+        ("a", "test", None, "Title too short"),
+        ("abcdefghi", "test", None, "Title too long"),
+        ("ab", "a", None, "Description too short"),
+        ("ab", "0123456789abcdefg", None, "Description too long"),
+        ("ab", "ab", Some("htt"), "Link too short"),
+        ("ab", "ab", Some("http://0123456789abcd"), "Link too long"),
+    ];
+    for (title, description, link, err) in cases {
+        let msg = create_poll_msg(
+            title.to_string(),
+            description.to_string(),
+            link.map(|link| link.to_string()),
+            None,
+        );
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(msg, err),
+            Err(_) => panic!("Unknown error"),
+        }
+    }
+
+    // shorter than the default minimum lengths
+    let msg = create_poll_msg(
+        "ab".to_string(),
+        "ab".to_string(),
+        Some("http".to_string()),
+        None,
+    );
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+}
+
 #[test]
 fn fails_create_poll_invalid_deposit() {
     let mut deps = mock_dependencies(&[]);
@@ -326,6 +587,8 @@ fn fails_create_poll_invalid_deposit() {
             description: "TESTTEST".to_string(),
             link: None,
             execute_msgs: None,
+            atomic: None,
+            content_hash: None,
         })
         .unwrap(),
     });
@@ -352,6 +615,8 @@ fn create_poll_msg(
             description,
             link,
             execute_msgs: execute_msg,
+            atomic: None,
+            content_hash: None,
         })
         .unwrap(),
     })
@@ -405,16 +670,19 @@ fn query_polls() {
             order: 1u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz,
+            msg_type: None,
         },
         PollExecuteMsg {
             order: 3u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz3,
+            msg_type: None,
         },
         PollExecuteMsg {
             order: 2u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz2,
+            msg_type: None,
         },
     ];
 
@@ -449,6 +717,8 @@ fn query_polls() {
                 creator: TEST_CREATOR.to_string(),
                 status: PollStatus::InProgress,
                 end_height: 20000u64,
+                end_time: None,
+                ends_at: Expiration::AtHeight(20000u64),
                 title: "test".to_string(),
                 description: "test".to_string(),
                 link: Some("http://google.com".to_string()),
@@ -456,14 +726,23 @@ fn query_polls() {
                 execute_data: Some(execute_msgs.clone()),
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
+                veto_votes: Uint128::zero(),
                 staked_amount: None,
                 total_balance_at_end_poll: None,
+                execution_height: None,
+                failure_reason: None,
+                atomic: true,
+                execution_results: None,
+                extended: false,
+                content_hash: None,
             },
             PollResponse {
                 id: 2u64,
                 creator: TEST_CREATOR.to_string(),
                 status: PollStatus::InProgress,
                 end_height: 20000u64,
+                end_time: None,
+                ends_at: Expiration::AtHeight(20000u64),
                 title: "test2".to_string(),
                 description: "test2".to_string(),
                 link: None,
@@ -471,8 +750,15 @@ fn query_polls() {
                 execute_data: None,
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
+                veto_votes: Uint128::zero(),
                 staked_amount: None,
                 total_balance_at_end_poll: None,
+                execution_height: None,
+                failure_reason: None,
+                atomic: true,
+                execution_results: None,
+                extended: false,
+                content_hash: None,
             },
         ]
     );
@@ -496,6 +782,8 @@ fn query_polls() {
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
             end_height: 20000u64,
+            end_time: None,
+            ends_at: Expiration::AtHeight(20000u64),
             title: "test2".to_string(),
             description: "test2".to_string(),
             link: None,
@@ -503,8 +791,15 @@ fn query_polls() {
             execute_data: None,
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            veto_votes: Uint128::zero(),
             staked_amount: None,
             total_balance_at_end_poll: None,
+            execution_height: None,
+            failure_reason: None,
+            atomic: true,
+            execution_results: None,
+            extended: false,
+            content_hash: None,
         },]
     );
 
@@ -527,6 +822,8 @@ fn query_polls() {
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
             end_height: 20000u64,
+            end_time: None,
+            ends_at: Expiration::AtHeight(20000u64),
             title: "test".to_string(),
             description: "test".to_string(),
             link: Some("http://google.com".to_string()),
@@ -534,8 +831,15 @@ fn query_polls() {
             execute_data: Some(execute_msgs),
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            veto_votes: Uint128::zero(),
             staked_amount: None,
             total_balance_at_end_poll: None,
+            execution_height: None,
+            failure_reason: None,
+            atomic: true,
+            execution_results: None,
+            extended: false,
+            content_hash: None,
         }]
     );
 
@@ -558,6 +862,8 @@ fn query_polls() {
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
             end_height: 20000u64,
+            end_time: None,
+            ends_at: Expiration::AtHeight(20000u64),
             title: "test2".to_string(),
             description: "test2".to_string(),
             link: None,
@@ -565,8 +871,15 @@ fn query_polls() {
             execute_data: None,
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            veto_votes: Uint128::zero(),
             staked_amount: None,
             total_balance_at_end_poll: None,
+            execution_height: None,
+            failure_reason: None,
+            atomic: true,
+            execution_results: None,
+            extended: false,
+            content_hash: None,
         },]
     );
 
@@ -585,6 +898,260 @@ fn query_polls() {
     assert_eq!(response.polls, vec![]);
 }
 
+#[test]
+fn query_polls_by_ids() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = create_poll_msg("test2".to_string(), "test2".to_string(), None, None);
+    let _execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PollsByIds {
+            ids: vec![2, 3, 1, 0],
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response
+            .polls
+            .iter()
+            .map(|poll| (poll.id, poll.title.as_str()))
+            .collect::<Vec<(u64, &str)>>(),
+        vec![(2u64, "test2"), (1u64, "test")]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls[1], poll_res);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PollsByIds { ids: vec![5, 6] },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls, vec![]);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PollsByIds {
+            ids: (0..31).collect(),
+        },
+    );
+    assert_eq!(res, Err(ContractError::TooManyPolls(30)));
+}
+
+#[test]
+fn query_executable_polls() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    // make fake polls at various timelock stages,
+    // followed by passed polls whose timelock is far from over
+    let mut polls = vec![
+        (1u64, PollStatus::Passed, 1000u64),
+        (2u64, PollStatus::Passed, 5000u64),
+        (3u64, PollStatus::Rejected, 0u64),
+        (4u64, PollStatus::Passed, 12000u64),
+    ];
+    polls.extend((5u64..=105).map(|poll_id| (poll_id, PollStatus::Passed, 100000u64)));
+    for (poll_id, status, end_height) in polls {
+        poll_store(&mut deps.storage)
+            .save(
+                &poll_id.to_be_bytes(),
+                &Poll {
+                    id: poll_id,
+                    creator: CanonicalAddr::from(vec![]),
+                    status: status.clone(),
+                    yes_votes: Uint128::zero(),
+                    no_votes: Uint128::zero(),
+                    veto_votes: None,
+                    end_height,
+                    end_time: None,
+                    title: "title".to_string(),
+                    description: "description".to_string(),
+                    deposit_amount: Uint128::zero(),
+                    link: None,
+                    execute_data: None,
+                    total_balance_at_end_poll: None,
+                    execution_height: None,
+                    failure_reason: None,
+                    atomic: None,
+                    execution_results: None,
+                    extended: None,
+                    content_hash: None,
+                    voting_period: None,
+                    snapshot_period: None,
+                    timelock_period: None,
+                    staked_amount: None,
+                },
+            )
+            .unwrap();
+        poll_indexer_store(&mut deps.storage, &status)
+            .save(&poll_id.to_be_bytes(), &true)
+            .unwrap();
+    }
+
+    let query_page = |deps: Deps, height: u64, start_after: Option<u64>, limit: Option<u32>| {
+        let res = query(
+            deps,
+            mock_env_height(height, 10000),
+            QueryMsg::ExecutablePolls { start_after, limit },
+        )
+        .unwrap();
+        let response: ExecutablePollsResponse = from_binary(&res).unwrap();
+        response
+    };
+    let query_executable_polls = |deps: Deps, height: u64, limit: Option<u32>| -> Vec<u64> {
+        query_page(deps, height, None, limit).poll_ids
+    };
+
+    // no timelock is over yet
+    assert_eq!(
+        query_executable_polls(deps.as_ref(), DEFAULT_TIMELOCK_PERIOD, None),
+        Vec::<u64>::new()
+    );
+    assert_eq!(
+        query_executable_polls(deps.as_ref(), 1000 + DEFAULT_TIMELOCK_PERIOD, None),
+        vec![1u64]
+    );
+    assert_eq!(
+        query_executable_polls(deps.as_ref(), 5000 + DEFAULT_TIMELOCK_PERIOD - 1, None),
+        vec![1u64]
+    );
+    assert_eq!(
+        query_executable_polls(deps.as_ref(), 5000 + DEFAULT_TIMELOCK_PERIOD, None),
+        vec![1u64, 2u64]
+    );
+    assert_eq!(
+        query_executable_polls(deps.as_ref(), 12000 + DEFAULT_TIMELOCK_PERIOD, None),
+        vec![1u64, 2u64, 4u64]
+    );
+    assert_eq!(
+        query_executable_polls(deps.as_ref(), 12000 + DEFAULT_TIMELOCK_PERIOD, Some(2)),
+        vec![1u64, 2u64]
+    );
+
+    // pages continue after the last scanned poll
+    assert_eq!(
+        query_page(
+            deps.as_ref(),
+            12000 + DEFAULT_TIMELOCK_PERIOD,
+            Some(1),
+            Some(1)
+        ),
+        ExecutablePollsResponse {
+            poll_ids: vec![2u64],
+            last_scanned: Some(2u64),
+        }
+    );
+
+    // the scan stops after 100 passed polls
+    assert_eq!(
+        query_page(deps.as_ref(), 12000 + DEFAULT_TIMELOCK_PERIOD, None, None),
+        ExecutablePollsResponse {
+            poll_ids: vec![1u64, 2u64, 4u64],
+            last_scanned: Some(101u64),
+        }
+    );
+    assert_eq!(
+        query_page(
+            deps.as_ref(),
+            12000 + DEFAULT_TIMELOCK_PERIOD,
+            Some(101),
+            None
+        ),
+        ExecutablePollsResponse {
+            poll_ids: vec![],
+            last_scanned: Some(105u64),
+        }
+    );
+    assert_eq!(
+        query_page(
+            deps.as_ref(),
+            12000 + DEFAULT_TIMELOCK_PERIOD,
+            Some(105),
+            None
+        ),
+        ExecutablePollsResponse {
+            poll_ids: vec![],
+            last_scanned: None,
+        }
+    );
+}
+
+#[test]
+fn query_poll_count_by_status() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
+    for _ in 0..2 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(2 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    // nobody voted, so the poll gets rejected
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let env = mock_env_height(DEFAULT_VOTING_PERIOD, 10000);
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let expected = PollCountByStatusResponse {
+        in_progress: 1,
+        passed: 0,
+        rejected: 1,
+        executed: 0,
+        expired: 0,
+        failed: 0,
+        vetoed: 0,
+    };
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PollCountByStatus {}).unwrap();
+    let response: PollCountByStatusResponse = from_binary(&res).unwrap();
+    assert_eq!(response, expected);
+
+    // polls indexed before the counters existed are counted by the migration
+    for (poll_id, status) in [(3u64, PollStatus::Passed), (4u64, PollStatus::Executed)] {
+        poll_indexer_store(&mut deps.storage, &status)
+            .save(&poll_id.to_be_bytes(), &true)
+            .unwrap();
+    }
+    migrate_poll_status_counts(deps.as_mut().storage).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PollCountByStatus {}).unwrap();
+    let response: PollCountByStatusResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response,
+        PollCountByStatusResponse {
+            passed: 1,
+            executed: 1,
+            ..expected
+        }
+    );
+}
+
 #[test]
 fn create_poll_no_quorum() {
     let mut deps = mock_dependencies(&[]);
@@ -674,16 +1241,19 @@ fn happy_days_end_poll() {
             order: 3u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz3.clone(),
+            msg_type: None,
         },
         PollExecuteMsg {
             order: 2u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz2.clone(),
+            msg_type: None,
         },
         PollExecuteMsg {
             order: 1u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz.clone(),
+            msg_type: None,
         },
     ];
 
@@ -738,7 +1308,7 @@ fn happy_days_end_poll() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
     };
     let env = mock_env_height(POLL_START_HEIGHT, 10000);
     let info = mock_info(TEST_VOTER, &[]);
@@ -843,7 +1413,13 @@ fn happy_days_end_poll() {
 
     let msg = ExecuteMsg::ExecutePollMsgs { poll_id: 1 };
     let contract_info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-    let execute_res = execute(deps.as_mut(), creator_env, contract_info, msg).unwrap();
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        contract_info.clone(),
+        msg.clone(),
+    )
+    .unwrap();
     assert_eq!(
         execute_res.messages,
         vec![
@@ -869,6 +1445,13 @@ fn happy_days_end_poll() {
         vec![attr("action", "execute_poll"), attr("poll_id", "1"),]
     );
 
+    // the poll messages cannot be executed twice
+    let res = execute(deps.as_mut(), creator_env, contract_info, msg);
+    match res {
+        Err(ContractError::PollNotPendingExecution {}) => (),
+        _ => panic!("Must return PollNotPendingExecution error"),
+    }
+
     // Query executed polls
     let res = query(
         deps.as_ref(),
@@ -994,6 +1577,7 @@ fn fail_poll() {
         order: 1u64,
         contract: VOTING_TOKEN.to_string(),
         msg: exec_msg_bz.clone(),
+        msg_type: None,
     }];
     let msg = create_poll_msg(
         "test".to_string(),
@@ -1046,7 +1630,7 @@ fn fail_poll() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
     };
     let env = mock_env_height(POLL_START_HEIGHT, 10000);
     let info = mock_info(TEST_VOTER, &[]);
@@ -1098,6 +1682,7 @@ fn fail_poll() {
 
     // Execute Poll should send submsg ExecutePollMsgs
     creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    let execution_height = creator_env.block.height;
     let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
     let execute_res = execute(deps.as_mut(), creator_env.clone(), creator_info, msg).unwrap();
     assert_eq!(
@@ -1141,12 +1726,18 @@ fn fail_poll() {
     let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
     assert_eq!(
         res.attributes,
-        vec![attr("action", "fail_poll"), attr("poll_id", "1")]
+        vec![
+            attr("action", "fail_poll"),
+            attr("poll_id", "1"),
+            attr("failure_reason", "Error"),
+        ]
     );
 
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
     let poll_res: PollResponse = from_binary(&res).unwrap();
     assert_eq!(poll_res.status, PollStatus::Failed);
+    assert_eq!(poll_res.failure_reason, Some("Error".to_string()));
+    assert_eq!(poll_res.execution_height, Some(execution_height));
 
     let res = query(
         deps.as_ref(),
@@ -1164,95 +1755,426 @@ fn fail_poll() {
 }
 
 #[test]
-fn end_poll_zero_quorum() {
-    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+fn veto_poll() {
+    let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_register_voting_token(deps.as_mut());
-    let mut creator_env = mock_env_height(1000, 10000);
-    let mut creator_info = mock_info(VOTING_TOKEN, &[]);
 
-    let execute_msgs: Vec<PollExecuteMsg> = vec![PollExecuteMsg {
-        order: 1u64,
-        contract: VOTING_TOKEN.to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::Burn {
-            amount: Uint128::new(123),
-        })
-        .unwrap(),
-    }];
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: Some(DEFAULT_TIMELOCK_PERIOD * 2),
+        guardian: Some("guardian".to_string()),
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
 
-    let msg = create_poll_msg(
-        "test".to_string(),
-        "test".to_string(),
-        None,
-        Some(execute_msgs),
-    );
+    let info = mock_info(VOTING_TOKEN, &[]);
+    for title in ["vetoed", "executed"] {
+        let msg = create_poll_msg(title.to_string(), title.to_string(), None, None);
+        let _ = execute(deps.as_mut(), mock_env_height(0, 10000), info.clone(), msg).unwrap();
+    }
 
-    let execute_res = execute(
-        deps.as_mut(),
-        creator_env.clone(),
-        creator_info.clone(),
-        msg,
-    )
-    .unwrap();
-    assert_create_poll_result(
-        1,
-        creator_env.block.height + DEFAULT_VOTING_PERIOD,
-        TEST_CREATOR,
-        execute_res,
-        deps.as_ref(),
-    );
-    let stake_amount = 100;
     deps.querier.with_token_balances(&[(
         &VOTING_TOKEN.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+            &Uint128::from(100u128 + 2 * DEFAULT_PROPOSAL_DEPOSIT),
         )],
     )]);
 
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(stake_amount as u128),
+        amount: Uint128::from(100u128),
         msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
     });
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let info = mock_info(VOTING_TOKEN, &[]);
-    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    for poll_id in [1u64, 2u64] {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(100u128)),
+        };
+        let env = mock_env_height(0, 10000);
+        let _ = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::EndPoll { poll_id };
+        let env = mock_env_height(DEFAULT_VOTING_PERIOD, 10000);
+        let _ = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+        // the deposit of the ended poll has been refunded
+        deps.querier.with_token_balances(&[(
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100u128 + (2 - poll_id as u128) * DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        )]);
+    }
 
-    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
-    creator_info.sender = Addr::unchecked(TEST_CREATOR);
-    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    // the timelock has expired, but the veto period has not
+    let env = mock_env_height(DEFAULT_VOTING_PERIOD + DEFAULT_TIMELOCK_PERIOD, 10000);
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::VetoPeriodNotExpired {}) => (),
+        _ => panic!("Must return VetoPeriodNotExpired error"),
+    }
 
-    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+    let msg = ExecuteMsg::VetoPoll { poll_id: 1 };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
 
+    let msg = ExecuteMsg::VetoPoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), env, mock_info("guardian", &[]), msg).unwrap();
     assert_eq!(
         execute_res.attributes,
         vec![
-            attr("action", "end_poll"),
+            attr("action", "veto_poll"),
             attr("poll_id", "1"),
-            attr("rejected_reason", "Quorum not reached"),
-            attr("passed", "false"),
+            attr("vetoed_by", "guardian"),
         ]
     );
 
-    assert_eq!(execute_res.messages.len(), 0usize);
-
-    // Query rejected polls
-    let res = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::Polls {
-            filter: Some(PollStatus::Rejected),
-            start_after: None,
-            limit: None,
-            order_by: Some(OrderBy::Desc),
-        },
-    )
-    .unwrap();
-    let response: PollsResponse = from_binary(&res).unwrap();
-    assert_eq!(response.polls.len(), 1);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(PollStatus::Vetoed, value.status);
 
-    let res = query(
+    // a vetoed poll cannot be executed
+    let env = mock_env_height(DEFAULT_VOTING_PERIOD + 2 * DEFAULT_TIMELOCK_PERIOD, 10000);
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::PollNotPassed {}) => (),
+        _ => panic!("Must return PollNotPassed error"),
+    }
+
+    // without a veto, the poll is executed after the veto period
+    let msg = ExecuteMsg::VetoPoll { poll_id: 2 };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("guardian", &[]), msg);
+    match res {
+        Err(ContractError::VetoPeriodExpired {}) => (),
+        _ => panic!("Must return VetoPeriodExpired error"),
+    }
+
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 2 };
+    let execute_res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::reply_on_error(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::ExecutePollMsgs { poll_id: 2 }).unwrap(),
+                funds: vec![],
+            }),
+            1
+        )]
+    );
+}
+
+#[test]
+fn time_based_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: Some(true),
+        voting_period_seconds: Some(1000),
+        snapshot_period_seconds: Some(100),
+        timelock_period_seconds: Some(500),
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(0, 10000);
+    let execute_res = execute(deps.as_mut(), env, mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "create_poll"),
+            attr("creator", TEST_CREATOR),
+            attr("poll_id", "1"),
+            attr("end_height", DEFAULT_VOTING_PERIOD.to_string()),
+            attr("end_time", "11000"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.end_time, Some(11000));
+    assert_eq!(
+        value.ends_at,
+        Expiration::AtTime(Timestamp::from_seconds(11000))
+    );
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    let _ = execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(100u128)),
+    };
+    let env = mock_env_height(0, 10500);
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 100, 1, VoteOption::Yes, execute_res);
+
+    // the end height has passed, but not the end time
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let env = mock_env_height(DEFAULT_VOTING_PERIOD, 10999);
+    let res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::PollVotingPeriod {}) => (),
+        _ => panic!("Must return PollVotingPeriod error"),
+    }
+
+    // snapshot only within the snapshot period before the end time
+    let msg = ExecuteMsg::SnapshotPoll { poll_id: 1 };
+    let env = mock_env_height(0, 10899);
+    let res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg.clone());
+    match res {
+        Err(ContractError::SnapshotHeight {}) => (),
+        _ => panic!("Must return SnapshotHeight error"),
+    }
+
+    let env = mock_env_height(0, 10900);
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "snapshot_poll"),
+            attr("poll_id", "1"),
+            attr("staked_amount", "100"),
+        ]
+    );
+
+    // voting is closed after the end time
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+        amount: Some(Uint128::from(100u128)),
+    };
+    let env = mock_env_height(0, 11001);
+    let res = execute(deps.as_mut(), env, mock_info(TEST_VOTER_2, &[]), msg);
+    match res {
+        Err(ContractError::PollNotInProgress {}) => (),
+        _ => panic!("Must return PollNotInProgress error"),
+    }
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let env = mock_env_height(5, 11000);
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+        ]
+    );
+
+    // the poll ends at the height of the end poll
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.status, PollStatus::Passed);
+    assert_eq!(value.end_height, 5);
+
+    // the timelock counts seconds from the end time, regardless of the height
+    let query_executable_polls = |deps: Deps, env: Env| -> Vec<u64> {
+        let res = query(
+            deps,
+            env,
+            QueryMsg::ExecutablePolls {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: ExecutablePollsResponse = from_binary(&res).unwrap();
+        value.poll_ids
+    };
+
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let env = mock_env_height(5 + DEFAULT_TIMELOCK_PERIOD, 11499);
+    assert_eq!(
+        query_executable_polls(deps.as_ref(), env.clone()),
+        Vec::<u64>::new()
+    );
+    let res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg.clone());
+    match res {
+        Err(ContractError::TimelockNotExpired {}) => (),
+        _ => panic!("Must return TimelockNotExpired error"),
+    }
+
+    let env = mock_env_height(5, 11500);
+    assert_eq!(query_executable_polls(deps.as_ref(), env.clone()), vec![1]);
+    execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.execution_height, Some(5));
+}
+
+#[test]
+fn end_poll_zero_quorum() {
+    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+    let mut creator_env = mock_env_height(1000, 10000);
+    let mut creator_info = mock_info(VOTING_TOKEN, &[]);
+
+    let execute_msgs: Vec<PollExecuteMsg> = vec![PollExecuteMsg {
+        order: 1u64,
+        contract: VOTING_TOKEN.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn {
+            amount: Uint128::new(123),
+        })
+        .unwrap(),
+        msg_type: None,
+    }];
+
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(execute_msgs),
+    );
+
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+    assert_create_poll_result(
+        1,
+        creator_env.block.height + DEFAULT_VOTING_PERIOD,
+        TEST_CREATOR,
+        execute_res,
+        deps.as_ref(),
+    );
+    let stake_amount = 100;
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake_amount as u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+
+    let info = mock_info(VOTING_TOKEN, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    creator_info.sender = Addr::unchecked(TEST_CREATOR);
+    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+
+    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Quorum not reached"),
+            attr("passed", "false"),
+        ]
+    );
+
+    assert_eq!(execute_res.messages.len(), 0usize);
+
+    // Query rejected polls
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Rejected),
+            start_after: None,
+            limit: None,
+            order_by: Some(OrderBy::Desc),
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 1);
+
+    let res = query(
         deps.as_ref(),
         mock_env(),
         QueryMsg::Polls {
@@ -1336,7 +2258,7 @@ fn end_poll_quorum_rejected() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(10u128),
+        amount: Some(Uint128::from(10u128)),
     };
     let info = mock_info(TEST_VOTER, &[]);
     let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1370,25 +2292,457 @@ fn end_poll_quorum_rejected() {
 }
 
 #[test]
-fn end_poll_quorum_rejected_nothing_staked() {
-    let mut deps = mock_dependencies(&coins(100, VOTING_TOKEN));
+fn end_poll_with_circulating_quorum_basis() {
+    let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_register_voting_token(deps.as_mut());
 
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        quorum_basis: Some(QuorumBasis::Circulating),
+        excluded_addresses: Some(vec!["treasury".to_string()]),
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
     let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
-    let mut creator_env = mock_env();
-    let mut creator_info = mock_info(VOTING_TOKEN, &[]);
-    let execute_res = execute(
-        deps.as_mut(),
-        creator_env.clone(),
-        creator_info.clone(),
-        msg,
-    )
-    .unwrap();
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = create_poll_msg("test2".to_string(), "test2".to_string(), None, None);
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100u128 + 2 * DEFAULT_PROPOSAL_DEPOSIT),
+            ),
+            (&"treasury".to_string(), &Uint128::from(880u128)),
+        ],
+    )]);
+    deps.querier
+        .with_token_supplies(&[(&VOTING_TOKEN.to_string(), &Uint128::from(1000u128))]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    for poll_id in [1u64, 2u64] {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(40u128)),
+        };
+        let _ = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    }
+
+    // circulating supply is 1000 - 880 = 120, so 40 votes reach the 30% quorum
+    let env = mock_env_height(DEFAULT_VOTING_PERIOD, 10000);
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
     assert_eq!(
         execute_res.attributes,
         vec![
-            attr("action", "create_poll"),
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+        ]
+    );
+
+    // circulating supply is 1000 - 500 = 500, so 40 votes do not reach the quorum
+    // even though they are 40% of the staked amount
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+            ),
+            (&"treasury".to_string(), &Uint128::from(500u128)),
+        ],
+    )]);
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 2 };
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "2"),
+            attr("rejected_reason", "Quorum not reached"),
+            attr("passed", "false"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 2 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(PollStatus::Rejected, value.status);
+    assert_eq!(
+        Some(Uint128::from(100u128)),
+        value.total_balance_at_end_poll
+    );
+}
+
+#[test]
+fn expire_stale_polls() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: Some(100u64),
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    let info = mock_info(VOTING_TOKEN, &[]);
+    for (title, height) in [("stale1", 0u64), ("stale2", 0u64), ("fresh", 1000u64)] {
+        let msg = create_poll_msg(title.to_string(), title.to_string(), None, None);
+        let _ = execute(
+            deps.as_mut(),
+            mock_env_height(height, 10000),
+            info.clone(),
+            msg,
+        )
+        .unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(100u128 + 3 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the first poll reaches the quorum, the second one does not
+    for (poll_id, amount) in [(1u64, 40u128), (2u64, 10u128), (3u64, 40u128)] {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(amount)),
+        };
+        let _ = execute(
+            deps.as_mut(),
+            mock_env_height(1000, 10000),
+            mock_info(TEST_VOTER, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    let env = mock_env_height(DEFAULT_VOTING_PERIOD + 101, 10000);
+    let msg = ExecuteMsg::ExpireStalePolls {
+        poll_ids: vec![1u64, 2u64, 3u64],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "expire_stale_polls"),
+            attr("ended_polls", "1,2"),
+            attr("passed_polls", "1"),
+            attr("skipped_polls", "3"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(PollStatus::Passed, value.status);
+
+    // the deposit refunded by the first poll is not counted as staked
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 2 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(PollStatus::Rejected, value.status);
+    assert_eq!(
+        Some(Uint128::from(100u128)),
+        value.total_balance_at_end_poll
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 3 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(PollStatus::InProgress, value.status);
+
+    let state: State = state_read(deps.as_ref().storage).load().unwrap();
+    assert_eq!(state.total_deposit, Uint128::from(DEFAULT_PROPOSAL_DEPOSIT));
+
+    // time based polls get their grace period in seconds after their end time
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: Some(true),
+        voting_period_seconds: Some(1000u64),
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: Some(100u64),
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    let msg = create_poll_msg("timed".to_string(), "timed".to_string(), None, None);
+    let _ = execute(
+        deps.as_mut(),
+        mock_env_height(30000, 10000),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    // past the grace period in blocks, but not in seconds
+    let msg = ExecuteMsg::ExpireStalePolls {
+        poll_ids: vec![4u64],
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env_height(30000 + DEFAULT_VOTING_PERIOD + 101, 11100),
+        mock_info(TEST_CREATOR, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "expire_stale_polls"),
+            attr("ended_polls", ""),
+            attr("passed_polls", ""),
+            attr("skipped_polls", "4"),
+        ]
+    );
+
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env_height(30100, 11101),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "expire_stale_polls"),
+            attr("ended_polls", "4"),
+            attr("passed_polls", ""),
+            attr("skipped_polls", ""),
+        ]
+    );
+}
+
+#[test]
+fn end_polls_in_same_block() {
+    let stake_amount = 1000u128;
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+    for _ in 0..2 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        let _res = execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(stake_amount + 2 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake_amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for poll_id in 1..3 {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(stake_amount)),
+        };
+        let info = mock_info(TEST_VOTER, &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let mut env = mock_env();
+    env.block.height += DEFAULT_VOTING_PERIOD;
+    let info = mock_info(TEST_CREATOR, &[]);
+    for poll_id in 1..3 {
+        let msg = ExecuteMsg::EndPoll { poll_id };
+        let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(
+            execute_res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: TEST_CREATOR.to_string(),
+                    amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+                })
+                .unwrap(),
+                funds: vec![],
+            }))]
+        );
+
+        // the refund is executed before the next transaction of the block
+        deps.querier.with_token_balances(&[(
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(stake_amount + (2 - poll_id as u128) * DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        )]);
+    }
+
+    // both polls count only the staked balance
+    for poll_id in 1..3 {
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id }).unwrap();
+        let value: PollResponse = from_binary(&res).unwrap();
+        assert_eq!(value.status, PollStatus::Passed);
+        assert_eq!(
+            value.total_balance_at_end_poll,
+            Some(Uint128::from(stake_amount))
+        );
+    }
+
+    let state: State = state_read(deps.as_ref().storage).load().unwrap();
+    assert_eq!(state.total_deposit, Uint128::zero());
+}
+
+#[test]
+fn end_poll_quorum_rejected_nothing_staked() {
+    let mut deps = mock_dependencies(&coins(100, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let mut creator_env = mock_env();
+    let mut creator_info = mock_info(VOTING_TOKEN, &[]);
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "create_poll"),
             attr("creator", TEST_CREATOR),
             attr("poll_id", "1"),
             attr("end_height", "32345"),
@@ -1495,7 +2849,7 @@ fn end_poll_nay_rejected() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::No,
-        amount: Uint128::from(voter2_stake),
+        amount: Some(Uint128::from(voter2_stake)),
     };
     let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_cast_vote_success(TEST_VOTER_2, voter2_stake, 1, VoteOption::No, execute_res);
@@ -1517,70 +2871,1383 @@ fn end_poll_nay_rejected() {
 }
 
 #[test]
-fn fails_cast_vote_not_enough_staked() {
+fn end_poll_vetoed() {
+    let voter1_stake = 100u128;
+    let voter2_stake = 1000u128;
     let mut deps = mock_dependencies(&[]);
-    mock_instantiate(deps.as_mut());
+    let msg = InstantiateMsg {
+        veto_threshold: Some(Decimal::percent(33)),
+        community_contract: Some("community".to_string()),
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+        ..instantiate_msg()
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
     mock_register_voting_token(deps.as_mut());
-    let env = mock_env_height(0, 10000);
-    let info = mock_info(VOTING_TOKEN, &[]);
-
-    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
 
-    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_create_poll_result(
-        1,
-        DEFAULT_VOTING_PERIOD,
-        TEST_CREATOR,
-        execute_res,
-        deps.as_ref(),
-    );
+    let creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+    for _ in 0..2 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        let _res = execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+    }
 
     deps.querier.with_token_balances(&[(
         &VOTING_TOKEN.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(10u128 + DEFAULT_PROPOSAL_DEPOSIT),
+            &Uint128::from(voter1_stake + voter2_stake + 2 * DEFAULT_PROPOSAL_DEPOSIT),
         )],
     )]);
 
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(10u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
-    });
-
-    let info = mock_info(VOTING_TOKEN, &[]);
-    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_stake_tokens_result(
-        10,
-        DEFAULT_PROPOSAL_DEPOSIT,
-        10,
-        1,
-        execute_res,
-        deps.as_ref(),
-    );
-
-    let env = mock_env_height(0, 10000);
-    let info = mock_info(TEST_VOTER, &coins(11, VOTING_TOKEN));
-    let msg = ExecuteMsg::CastVote {
-        poll_id: 1,
-        vote: VoteOption::Yes,
-        amount: Uint128::from(11u128),
-    };
-
-    let res = execute(deps.as_mut(), env, info, msg);
+    for (voter, stake) in [(TEST_VOTER, voter1_stake), (TEST_VOTER_2, voter2_stake)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::from(stake),
+            msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        });
+        let info = mock_info(VOTING_TOKEN, &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
 
-    match res {
-        Ok(_) => panic!("Must return error"),
-        Err(ContractError::InsufficientStaked {}) => (),
-        Err(e) => panic!("Unexpected error: {:?}", e),
+    // poll 1 is vetoed by the majority, poll 2 is rejected by the majority
+    for (poll_id, voter, stake, vote) in [
+        (1, TEST_VOTER, voter1_stake, VoteOption::Yes),
+        (1, TEST_VOTER_2, voter2_stake, VoteOption::NoWithVeto),
+        (2, TEST_VOTER, voter1_stake, VoteOption::NoWithVeto),
+        (2, TEST_VOTER_2, voter2_stake, VoteOption::No),
+    ] {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: vote.clone(),
+            amount: Some(Uint128::from(stake)),
+        };
+        let info = mock_info(voter, &[]);
+        let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_cast_vote_success(voter, stake, poll_id, vote, execute_res);
     }
-}
 
-#[test]
-fn happy_days_cast_vote() {
-    let mut deps = mock_dependencies(&[]);
-    mock_instantiate(deps.as_mut());
+    let mut env = mock_env();
+    env.block.height += DEFAULT_VOTING_PERIOD;
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Vetoed"),
+            attr("passed", "false"),
+        ]
+    );
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "community".to_string(),
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.status, PollStatus::Rejected);
+    assert_eq!(value.veto_votes, Uint128::from(voter2_stake));
+
+    // the deposit of poll 1 left the contract
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(voter1_stake + voter2_stake + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 2 };
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "2"),
+            attr("rejected_reason", "Threshold not reached"),
+            attr("passed", "false"),
+        ]
+    );
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 2 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.veto_votes, Uint128::from(voter1_stake));
+}
+
+#[test]
+fn end_poll_refunds_stored_deposit() {
+    let stake_amount = 1000u128;
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the deposit is raised while the poll is in progress
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        proposal_deposit: Some(Uint128::from(2 * DEFAULT_PROPOSAL_DEPOSIT)),
+        snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(stake_amount + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake_amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(stake_amount)),
+    };
+    let info = mock_info(TEST_VOTER, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut env = mock_env();
+    env.block.height += DEFAULT_VOTING_PERIOD;
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+        ]
+    );
+
+    // the deposit made at the creation is refunded, not the current config
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.total_deposit, Uint128::zero());
+}
+
+#[test]
+fn end_poll_quorum_extension() {
+    let voter1_stake = 100u128;
+    let voter2_stake = 900u128;
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        quorum_reduction_after: Some(1000),
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: Some(Decimal::percent(50)),
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+        ..instantiate_msg()
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_register_voting_token(deps.as_mut());
+
+    let creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+    for _ in 0..2 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        let _res = execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(voter1_stake + voter2_stake + 2 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    for (voter, stake) in [(TEST_VOTER, voter1_stake), (TEST_VOTER_2, voter2_stake)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::from(stake),
+            msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        });
+        let info = mock_info(VOTING_TOKEN, &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // 10% of the stake votes on poll 1 and 20% on poll 2, both below the 30% quorum
+    for (poll_id, voter, amount) in [(1, TEST_VOTER, 100u128), (2, TEST_VOTER_2, 200u128)] {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(amount)),
+        };
+        let info = mock_info(voter, &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let mut env = mock_env();
+    env.block.height += DEFAULT_VOTING_PERIOD;
+    let info = mock_info(TEST_CREATOR, &[]);
+    for poll_id in 1..3 {
+        let msg = ExecuteMsg::EndPoll { poll_id };
+        let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(
+            execute_res.attributes,
+            vec![
+                attr("action", "end_poll"),
+                attr("poll_id", poll_id.to_string()),
+                attr("rejected_reason", "Quorum not reached"),
+                attr("passed", "false"),
+                attr("extended", "true"),
+            ]
+        );
+        assert!(execute_res.messages.is_empty());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id }).unwrap();
+        let value: PollResponse = from_binary(&res).unwrap();
+        assert_eq!(value.status, PollStatus::InProgress);
+        assert_eq!(value.end_height, env.block.height + 1000);
+        assert!(value.extended);
+    }
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::PollVotingPeriod {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // poll 2 reaches the reduced quorum of 15%
+    env.block.height += 1000;
+    let msg = ExecuteMsg::EndPoll { poll_id: 2 };
+    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "2"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+        ]
+    );
+    assert_eq!(execute_res.messages.len(), 1);
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(voter1_stake + voter2_stake + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    // poll 1 is not extended again
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Quorum not reached"),
+            attr("passed", "false"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.status, PollStatus::Rejected);
+}
+
+#[test]
+fn end_poll_quorum_extension_seconds() {
+    let stake = 1000u128;
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        use_time: Some(true),
+        voting_period_seconds: Some(1000u64),
+        quorum_reduction_after: Some(1000u64),
+        quorum_reduction_after_seconds: Some(500u64),
+        quorum_reduction_factor: Some(Decimal::percent(50)),
+        ..instantiate_msg()
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(stake + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    let _res = execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    // 10% of the stake votes, below the 30% quorum
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(100u128)),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    // the time based poll is extended by the period in seconds
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(1000);
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let execute_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Quorum not reached"),
+            attr("passed", "false"),
+            attr("extended", "true"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.status, PollStatus::InProgress);
+    assert_eq!(value.end_time, Some(env.block.time.seconds() + 500));
+    assert!(value.extended);
+}
+
+#[test]
+fn create_poll_with_content_hash() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let create_poll_msg = |content_hash: &str| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: TEST_CREATOR.to_string(),
+            amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            msg: to_binary(&Cw20HookMsg::CreatePoll {
+                title: "test".to_string(),
+                description: "test".to_string(),
+                link: None,
+                execute_msgs: None,
+                atomic: None,
+                content_hash: Some(content_hash.to_string()),
+            })
+            .unwrap(),
+        })
+    };
+
+    for content_hash in [
+        // too short
+        "1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac",
+        // not hex
+        "1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deacz",
+        // not lowercase base32
+        "BAFYBEIGDYRZT5SFP7UDM7HU76UH7Y26NF3EFUYLQABF3OCLGTQY55FBZDI",
+        // base32 without multibase prefix
+        "aafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+    ] {
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(VOTING_TOKEN, &[]),
+            create_poll_msg(content_hash),
+        ) {
+            Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+                assert_eq!(msg, "Invalid content hash")
+            }
+            _ => panic!("Must return invalid content hash error"),
+        }
+    }
+
+    let hex_digest = "1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8";
+    let cid = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+    for (poll_id, content_hash) in [(1u64, hex_digest), (2u64, cid)] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(VOTING_TOKEN, &[]),
+            create_poll_msg(content_hash),
+        )
+        .unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id }).unwrap();
+        let poll: PollResponse = from_binary(&res).unwrap();
+        assert_eq!(poll.content_hash, Some(content_hash.to_string()));
+    }
+}
+
+#[test]
+fn poll_periods_fixed_at_creation() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let env = mock_env_height(0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    // change the periods while the first poll is in progress
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateConfig {
+            owner: None,
+            quorum: None,
+            threshold: None,
+            voting_period: Some(DEFAULT_VOTING_PERIOD * 2),
+            timelock_period: Some(DEFAULT_TIMELOCK_PERIOD * 2),
+            proposal_deposit: None,
+            snapshot_period: Some(DEFAULT_FIX_PERIOD * 10),
+            quorum_basis: None,
+            excluded_addresses: None,
+            stale_poll_grace_period: None,
+            veto_period: None,
+            guardian: None,
+            poll_length_limits: None,
+            use_time: None,
+            voting_period_seconds: Some(600u64),
+            snapshot_period_seconds: Some(60u64),
+            timelock_period_seconds: Some(300u64),
+            stale_poll_grace_period_seconds: None,
+            max_participations: None,
+            execute_target_policy: None,
+            min_vote_amount: None,
+            veto_threshold: None,
+            community_contract: None,
+            quorum_reduction_after: None,
+            quorum_reduction_after_seconds: None,
+            quorum_reduction_factor: None,
+            allow_opaque_execute_msgs: None,
+            allow_vote_change: None,
+            min_yes_votes: None,
+            unstake_cooldown: None,
+        },
+    )
+    .unwrap();
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(deps.as_mut(), env, mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let poll_periods = |deps: Deps, poll_id: u64| {
+        let poll: Poll = poll_read(deps.storage)
+            .load(&poll_id.to_be_bytes())
+            .unwrap();
+        (
+            poll.voting_period,
+            poll.snapshot_period,
+            poll.timelock_period,
+        )
+    };
+    assert_eq!(
+        poll_periods(deps.as_ref(), 1),
+        (
+            Some(DEFAULT_VOTING_PERIOD),
+            Some(DEFAULT_FIX_PERIOD),
+            Some(DEFAULT_TIMELOCK_PERIOD)
+        )
+    );
+    assert_eq!(
+        poll_periods(deps.as_ref(), 2),
+        (
+            Some(DEFAULT_VOTING_PERIOD * 2),
+            Some(DEFAULT_FIX_PERIOD * 10),
+            Some(DEFAULT_TIMELOCK_PERIOD * 2)
+        )
+    );
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(2 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    // the first poll keeps its snapshot window, the second uses the new one
+    let snapshot_poll = |deps: DepsMut, poll_id: u64, height: u64| {
+        execute(
+            deps,
+            mock_env_height(height, 10000),
+            mock_info(TEST_VOTER, &[]),
+            ExecuteMsg::SnapshotPoll { poll_id },
+        )
+    };
+    match snapshot_poll(deps.as_mut(), 1, DEFAULT_VOTING_PERIOD - 50) {
+        Err(ContractError::SnapshotHeight {}) => (),
+        _ => panic!("Must return SnapshotHeight error"),
+    }
+    snapshot_poll(deps.as_mut(), 1, DEFAULT_VOTING_PERIOD - DEFAULT_FIX_PERIOD).unwrap();
+    snapshot_poll(deps.as_mut(), 2, DEFAULT_VOTING_PERIOD * 2 - 50).unwrap();
+
+    // polls created before the periods were recorded get the config values on migration
+    let mut poll: Poll = poll_read(deps.as_ref().storage)
+        .load(&1u64.to_be_bytes())
+        .unwrap();
+    poll.voting_period = None;
+    poll.snapshot_period = None;
+    poll.timelock_period = None;
+    poll_store(deps.as_mut().storage)
+        .save(&1u64.to_be_bytes(), &poll)
+        .unwrap();
+
+    // only missing periods are filled, in seconds for time based polls
+    let mut poll: Poll = poll_read(deps.as_ref().storage)
+        .load(&2u64.to_be_bytes())
+        .unwrap();
+    poll.end_time = Some(mock_env().block.time.seconds() + 600);
+    poll.voting_period = None;
+    poll.snapshot_period = None;
+    poll_store(deps.as_mut().storage)
+        .save(&2u64.to_be_bytes(), &poll)
+        .unwrap();
+
+    migrate_poll_periods(deps.as_mut().storage).unwrap();
+    assert_eq!(
+        poll_periods(deps.as_ref(), 1),
+        (
+            Some(DEFAULT_VOTING_PERIOD * 2),
+            Some(DEFAULT_FIX_PERIOD * 10),
+            Some(DEFAULT_TIMELOCK_PERIOD * 2)
+        )
+    );
+    assert_eq!(
+        poll_periods(deps.as_ref(), 2),
+        (Some(600u64), Some(60u64), Some(DEFAULT_TIMELOCK_PERIOD * 2))
+    );
+}
+
+#[test]
+fn end_poll_min_yes_votes_not_reached() {
+    let stake_amount = 500u128;
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        min_yes_votes: Some(Uint128::from(1000u128)),
+        ..instantiate_msg()
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(stake_amount + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake_amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    let _res = execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    // the whole stake votes yes, meeting the quorum and threshold
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(stake_amount)),
+    };
+    let execute_res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, stake_amount, 1, VoteOption::Yes, execute_res);
+
+    let mut env = mock_env();
+    env.block.height += DEFAULT_VOTING_PERIOD;
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Minimum yes votes not reached"),
+            attr("passed", "false"),
+        ]
+    );
+
+    // the deposit is refunded since the quorum was reached
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.status, PollStatus::Rejected);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.min_yes_votes, Uint128::from(1000u128));
+}
+
+#[test]
+fn create_poll_calling_gov_contract() {
+    const POLL_START_HEIGHT: u64 = 1000;
+    let stake_amount = 1000;
+
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+    let mut creator_env = mock_env_height(POLL_START_HEIGHT, 10000);
+    let creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+
+    let withdraw_msg = to_binary(&ExecuteMsg::WithdrawVotingTokens { amount: None }).unwrap();
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(vec![
+            PollExecuteMsg {
+                order: 1u64,
+                contract: VOTING_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::new(10),
+                })
+                .unwrap(),
+                msg_type: None,
+            },
+            PollExecuteMsg {
+                order: 2u64,
+                contract: MOCK_CONTRACT_ADDR.to_string(),
+                msg: withdraw_msg,
+                msg_type: None,
+            },
+        ]),
+    );
+    match execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    ) {
+        Err(ContractError::SelfCallNotAllowed(2)) => (),
+        _ => panic!("Must return self call not allowed error"),
+    }
+
+    let update_config_msg = Binary::from(br#"{"update_config":{"quorum":"0.5"}}"#);
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(vec![PollExecuteMsg {
+            order: 1u64,
+            contract: MOCK_CONTRACT_ADDR.to_string(),
+            msg: update_config_msg.clone(),
+            msg_type: None,
+        }]),
+    );
+    let execute_res = execute(deps.as_mut(), creator_env.clone(), creator_info, msg).unwrap();
+    assert_create_poll_result(
+        1,
+        creator_env.block.height + DEFAULT_VOTING_PERIOD,
+        TEST_CREATOR,
+        execute_res,
+        deps.as_ref(),
+    );
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(stake_amount + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake_amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(stake_amount)),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env_height(POLL_START_HEIGHT, 10000),
+        mock_info(TEST_VOTER, &[]),
+        msg,
+    )
+    .unwrap();
+
+    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    let creator_info = mock_info(TEST_CREATOR, &[]);
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        ExecuteMsg::EndPoll { poll_id: 1 },
+    )
+    .unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(stake_amount),
+        )],
+    )]);
+
+    creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info,
+        ExecuteMsg::ExecutePoll { poll_id: 1 },
+    )
+    .unwrap();
+
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env,
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::ExecutePollMsgs { poll_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            msg: update_config_msg,
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn retry_failed_poll() {
+    const POLL_START_HEIGHT: u64 = 1000;
+    let stake_amount = 1000;
+
+    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+    let mut creator_env = mock_env_height(POLL_START_HEIGHT, 10000);
+    let creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+
+    let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
+        amount: Uint128::new(123),
+    })
+    .unwrap();
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(vec![PollExecuteMsg {
+            order: 1u64,
+            contract: VOTING_TOKEN.to_string(),
+            msg: exec_msg_bz.clone(),
+            msg_type: None,
+        }]),
+    );
+    execute(deps.as_mut(), creator_env.clone(), creator_info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(stake_amount + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake_amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(stake_amount)),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env_height(POLL_START_HEIGHT, 10000),
+        mock_info(TEST_VOTER, &[]),
+        msg,
+    )
+    .unwrap();
+
+    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    let creator_info = mock_info(TEST_CREATOR, &[]);
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        ExecuteMsg::EndPoll { poll_id: 1 },
+    )
+    .unwrap();
+
+    // only failed polls can be retried
+    let msg = ExecuteMsg::RetryFailedPoll { poll_id: 1 };
+    let res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg.clone(),
+    );
+    assert_eq!(res, Err(ContractError::PollNotFailed {}));
+
+    creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        ExecuteMsg::ExecutePoll { poll_id: 1 },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::ExecutePollMsgs { poll_id: 1 },
+    )
+    .unwrap();
+
+    // the poll msgs fail, e.g. the contract lacks the balance to burn
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Err("Error".to_string()),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Failed);
+
+    let res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    let res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "retry_failed_poll"),
+            attr("poll_id", "1"),
+            attr("retried_by", TEST_CREATOR),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Passed);
+    assert_eq!(poll_res.failure_reason, None);
+    assert_eq!(poll_res.execution_height, None);
+
+    // the poll can be executed again, this time successfully
+    creator_env.block.height += 1;
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info,
+        ExecuteMsg::ExecutePoll { poll_id: 1 },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::ExecutePollMsgs { poll_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: exec_msg_bz,
+            funds: vec![],
+        }))]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Executed);
+    assert_eq!(poll_res.execution_height, Some(creator_env.block.height));
+}
+
+#[test]
+fn participation() {
+    const POLL_START_HEIGHT: u64 = 1000;
+    let stake_amount = 1000u128;
+
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let query_participation = |deps: Deps, address: &str| -> ParticipationResponse {
+        from_binary(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::Participation {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    for (index, voter) in [TEST_VOTER, TEST_VOTER_2].iter().enumerate() {
+        deps.querier.with_token_balances(&[(
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(stake_amount * (index as u128 + 1)),
+            )],
+        )]);
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::from(stake_amount),
+            msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    }
+
+    let mut env = mock_env_height(POLL_START_HEIGHT, 10000);
+    for _ in 0..2 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(VOTING_TOKEN, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(2 * stake_amount + 2 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    // voter1 votes on both polls, voter2 only on the first one
+    for (voter, poll_id) in [(TEST_VOTER, 1), (TEST_VOTER, 2), (TEST_VOTER_2, 1)] {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(stake_amount)),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+    }
+
+    // the polls are not eligible before they end
+    assert_eq!(
+        query_participation(deps.as_ref(), TEST_VOTER),
+        ParticipationResponse {
+            polls_voted: 2,
+            polls_eligible: 0,
+            ratio: Decimal::zero(),
+        }
+    );
+
+    env.block.height += DEFAULT_VOTING_PERIOD;
+    for poll_id in 1..3 {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(TEST_CREATOR, &[]),
+            ExecuteMsg::EndPoll { poll_id },
+        )
+        .unwrap();
+    }
+
+    assert_eq!(
+        query_participation(deps.as_ref(), TEST_VOTER),
+        ParticipationResponse {
+            polls_voted: 2,
+            polls_eligible: 2,
+            ratio: Decimal::one(),
+        }
+    );
+    assert_eq!(
+        query_participation(deps.as_ref(), TEST_VOTER_2),
+        ParticipationResponse {
+            polls_voted: 1,
+            polls_eligible: 2,
+            ratio: Decimal::percent(50),
+        }
+    );
+
+    // polls ended before staking are not eligible
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(3 * stake_amount),
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER_3.to_string(),
+        amount: Uint128::from(stake_amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    execute(deps.as_mut(), env, mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    assert_eq!(
+        query_participation(deps.as_ref(), TEST_VOTER_3),
+        ParticipationResponse {
+            polls_voted: 0,
+            polls_eligible: 0,
+            ratio: Decimal::zero(),
+        }
+    );
+}
+
+#[test]
+fn query_effective_staked() {
+    let stake_amount = 1000u128;
+
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let mut creator_env = mock_env();
+    let creator_info = mock_info(VOTING_TOKEN, &[]);
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+
+    let query_effective_staked = |deps: Deps| -> EffectiveStakedResponse {
+        from_binary(&query(deps, mock_env(), QueryMsg::EffectiveStaked { poll_id: 1 }).unwrap())
+            .unwrap()
+    };
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(stake_amount + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake_amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    // before the snapshot the live balance excluding the deposit is used
+    assert_eq!(
+        query_effective_staked(deps.as_ref()),
+        EffectiveStakedResponse {
+            staked_amount: Uint128::from(stake_amount),
+            source: StakedSource::Live,
+        }
+    );
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1500u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+    assert_eq!(
+        query_effective_staked(deps.as_ref()),
+        EffectiveStakedResponse {
+            staked_amount: Uint128::from(1500u128),
+            source: StakedSource::Live,
+        }
+    );
+
+    creator_env.block.height = 32345 - 10;
+    execute(
+        deps.as_mut(),
+        creator_env,
+        creator_info,
+        ExecuteMsg::SnapshotPoll { poll_id: 1 },
+    )
+    .unwrap();
+
+    // after the snapshot the balance changes are ignored
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(3000u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+    assert_eq!(
+        query_effective_staked(deps.as_ref()),
+        EffectiveStakedResponse {
+            staked_amount: Uint128::from(1500u128),
+            source: StakedSource::Snapshot,
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::EffectiveStaked { poll_id: 2 },
+    );
+    assert_eq!(res, Err(ContractError::PollNotFound {}));
+}
+
+#[test]
+fn query_end_poll_readiness() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let query_end_poll_readiness = |deps: Deps, height: u64| -> EndPollReadinessResponse {
+        let mut env = mock_env();
+        env.block.height = height;
+        from_binary(&query(deps, env, QueryMsg::EndPollReadiness { poll_id: 1 }).unwrap()).unwrap()
+    };
+
+    let end_height = mock_env().block.height + DEFAULT_VOTING_PERIOD;
+    assert_eq!(
+        query_end_poll_readiness(deps.as_ref(), end_height - 100),
+        EndPollReadinessResponse {
+            can_end: false,
+            blocks_remaining: 100,
+            reason: "voting period not over".to_string(),
+        }
+    );
+    assert_eq!(
+        query_end_poll_readiness(deps.as_ref(), end_height),
+        EndPollReadinessResponse {
+            can_end: true,
+            blocks_remaining: 0,
+            reason: "".to_string(),
+        }
+    );
+
+    let mut env = mock_env();
+    env.block.height = end_height;
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::EndPoll { poll_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        query_end_poll_readiness(deps.as_ref(), end_height + 1),
+        EndPollReadinessResponse {
+            can_end: false,
+            blocks_remaining: 0,
+            reason: "already ended".to_string(),
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::EndPollReadiness { poll_id: 2 },
+    );
+    assert_eq!(res, Err(ContractError::PollNotFound {}));
+}
+
+#[test]
+fn fails_cast_vote_not_enough_staked() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_create_poll_result(
+        1,
+        DEFAULT_VOTING_PERIOD,
+        TEST_CREATOR,
+        execute_res,
+        deps.as_ref(),
+    );
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(10u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(10u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_stake_tokens_result(
+        10,
+        DEFAULT_PROPOSAL_DEPOSIT,
+        10,
+        1,
+        execute_res,
+        deps.as_ref(),
+    );
+
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(TEST_VOTER, &coins(11, VOTING_TOKEN));
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(11u128)),
+    };
+
+    let res = execute(deps.as_mut(), env, info, msg);
+
+    match res {
+        Ok(_) => panic!("Must return error"),
+        Err(ContractError::InsufficientStaked {}) => (),
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn happy_days_cast_vote() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
     mock_register_voting_token(deps.as_mut());
 
     let env = mock_env_height(0, 10000);
@@ -1627,7 +4294,7 @@ fn happy_days_cast_vote() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(amount),
+        amount: Some(Uint128::from(amount)),
     };
 
     let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
@@ -1875,13 +4542,24 @@ fn withdraw_voting_tokens_remove_not_in_progress_poll_voter_info() {
                 status: PollStatus::InProgress,
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
+                veto_votes: None,
                 end_height: 0u64,
+                end_time: None,
                 title: "title".to_string(),
                 description: "description".to_string(),
                 deposit_amount: Uint128::zero(),
                 link: None,
                 execute_data: None,
                 total_balance_at_end_poll: None,
+                execution_height: None,
+                failure_reason: None,
+                atomic: None,
+                execution_results: None,
+                extended: None,
+                content_hash: None,
+                voting_period: None,
+                snapshot_period: None,
+                timelock_period: None,
                 staked_amount: None,
             },
         )
@@ -1896,13 +4574,24 @@ fn withdraw_voting_tokens_remove_not_in_progress_poll_voter_info() {
                 status: PollStatus::Passed,
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
+                veto_votes: None,
                 end_height: 0u64,
+                end_time: None,
                 title: "title".to_string(),
                 description: "description".to_string(),
                 deposit_amount: Uint128::zero(),
                 link: None,
                 execute_data: None,
                 total_balance_at_end_poll: None,
+                execution_height: None,
+                failure_reason: None,
+                atomic: None,
+                execution_results: None,
+                extended: None,
+                content_hash: None,
+                voting_period: None,
+                snapshot_period: None,
+                timelock_period: None,
                 staked_amount: None,
             },
         )
@@ -2009,38 +4698,433 @@ fn fails_withdraw_voting_tokens_no_stake() {
 }
 
 #[test]
-fn fails_withdraw_too_many_tokens() {
+fn fails_withdraw_too_many_tokens() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10u128))],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(10u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_stake_tokens_result(10, 0, 10, 0, execute_res, deps.as_ref());
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::from(11u128)),
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+
+    match res {
+        Ok(_) => panic!("Must return error"),
+        Err(ContractError::InvalidWithdrawAmount(10u128)) => (),
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn withdraw_voting_tokens_with_unstake_cooldown() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        unstake_cooldown: Some(100u64),
+        ..instantiate_msg()
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_register_voting_token(deps.as_mut());
+
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(10u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(10u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the withdrawal is recorded instead of transferred
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::from(4u128)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.messages.is_empty());
+
+    let claimable_at = mock_env().block.time.seconds() + 100u64;
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "request_withdraw"),
+            attr("sender", TEST_VOTER),
+            attr("amount", "4"),
+            attr("claimable_at", claimable_at.to_string()),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PendingWithdrawals {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: PendingWithdrawalsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.withdrawals,
+        vec![PendingWithdrawal {
+            amount: Uint128::from(4u128),
+            claimable_at,
+        }]
+    );
+
+    // the pending amount no longer counts as staked
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.balance, Uint128::from(6u128));
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(7u128)),
+    };
+    let res = execute(deps.as_mut(), mock_env_height(0, 10000), info, msg);
+    match res {
+        Err(ContractError::InsufficientStaked {}) => (),
+        _ => panic!("Must return insufficient staked error"),
+    }
+
+    // the cooldown is not over yet
+    let info = mock_info(TEST_VOTER, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimUnstaked {},
+    );
+    match res {
+        Err(ContractError::NothingToClaim {}) => (),
+        _ => panic!("Must return nothing to claim error"),
+    }
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let info = mock_info(TEST_VOTER, &[]);
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::ClaimUnstaked {}).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::from(4u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PendingWithdrawals {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: PendingWithdrawalsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.withdrawals, vec![]);
+
+    // the staked balance is unchanged once the tokens left the contract
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(6u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.balance, Uint128::from(6u128));
+}
+
+#[test]
+fn reconcile_total_share() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let mut total_balance = Uint128::zero();
+    for staker in [TEST_VOTER, TEST_VOTER_2, TEST_VOTER_3].iter() {
+        total_balance += Uint128::from(100u128);
+        deps.querier.with_token_balances(&[(
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_balance)],
+        )]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    }
+
+    // inject drift
+    let mut state: State = state_read(deps.as_ref().storage).load().unwrap();
+    state.total_share = Uint128::from(305u128);
+    state_store(deps.as_mut().storage).save(&state).unwrap();
+
+    let query_share_invariant = |deps: Deps| -> ShareInvariantResponse {
+        from_binary(&query(deps, mock_env(), QueryMsg::ShareInvariant {}).unwrap()).unwrap()
+    };
+    let reconcile = |start_after: Option<String>| ExecuteMsg::ReconcileTotalShare {
+        start_after,
+        limit: 2,
+    };
+
+    let mut env = mock_env();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        reconcile(None),
+    )
+    .unwrap();
+    let response = query_share_invariant(deps.as_ref());
+    assert_eq!(response.total_share, Uint128::from(305u128));
+    assert_eq!(response.accumulated_share, Uint128::from(200u128));
+    assert!(response.in_progress);
+    let last_staker = response.last_staker.unwrap();
+
+    // one batch per block
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        reconcile(Some(last_staker.clone())),
+    );
+    assert_eq!(res, Err(ContractError::ReconcileRateLimited {}));
+
+    // staking during the scan restarts it, the drifted total mints 101 shares
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(400u128))],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    let response = query_share_invariant(deps.as_ref());
+    assert_eq!(response.accumulated_share, Uint128::zero());
+    assert!(!response.in_progress);
+
+    env.block.height += 1;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        reconcile(Some(last_staker)),
+    );
+    assert_eq!(res, Err(ContractError::ReconcileCursorMismatch {}));
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        reconcile(None),
+    )
+    .unwrap();
+    let last_staker = query_share_invariant(deps.as_ref()).last_staker;
+    let total_share = query_share_invariant(deps.as_ref()).total_share;
+
+    env.block.height += 1;
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(TEST_CREATOR, &[]),
+        reconcile(last_staker),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "reconcile_total_share"),
+            attr("total_share_before", total_share.to_string()),
+            attr("total_share_after", "401"),
+        ]
+    );
+    assert_eq!(
+        query_share_invariant(deps.as_ref()),
+        ShareInvariantResponse {
+            total_share: Uint128::from(401u128),
+            accumulated_share: Uint128::zero(),
+            in_progress: false,
+            last_staker: None,
+        }
+    );
+}
+
+#[test]
+fn query_withdrawable_amount_with_blocking_polls() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_register_voting_token(deps.as_mut());
 
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _ = execute(deps.as_mut(), mock_env_height(0, 10000), info.clone(), msg).unwrap();
+    let msg = create_poll_msg("test2".to_string(), "test2".to_string(), None, None);
+    let _ = execute(deps.as_mut(), mock_env_height(100, 10000), info, msg).unwrap();
+
     deps.querier.with_token_balances(&[(
         &VOTING_TOKEN.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10u128))],
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(100u128 + 2 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
     )]);
 
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(10u128),
+        amount: Uint128::from(100u128),
         msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
     });
-
     let info = mock_info(VOTING_TOKEN, &[]);
-    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_stake_tokens_result(10, 0, 10, 0, execute_res, deps.as_ref());
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let info = mock_info(TEST_VOTER, &[]);
-    let msg = ExecuteMsg::WithdrawVotingTokens {
-        amount: Some(Uint128::from(11u128)),
-    };
+    for (poll_id, amount) in [(1u64, 70u128), (2u64, 30u128)] {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(amount)),
+        };
+        let _ = execute(deps.as_mut(), mock_env_height(0, 10000), info.clone(), msg).unwrap();
+    }
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::WithdrawableAmount {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: WithdrawableAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response,
+        WithdrawableAmountResponse {
+            withdrawable: Uint128::from(30u128),
+            locked: Uint128::from(70u128),
+            blocking_polls: vec![
+                BlockingPoll {
+                    poll_id: 1u64,
+                    balance: Uint128::from(70u128),
+                    end_height: DEFAULT_VOTING_PERIOD,
+                },
+                BlockingPoll {
+                    poll_id: 2u64,
+                    balance: Uint128::from(30u128),
+                    end_height: 100 + DEFAULT_VOTING_PERIOD,
+                },
+            ],
+        }
+    );
 
+    // withdrawing more than the query reports must fail with the same limit
+    let msg = ExecuteMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::from(31u128)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
     match res {
         Ok(_) => panic!("Must return error"),
-        Err(ContractError::InvalidWithdrawAmount {}) => (),
+        Err(ContractError::InvalidWithdrawAmount(30u128)) => (),
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
+
+    // end the first poll; its locked balance is released
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let _ = execute(
+        deps.as_mut(),
+        mock_env_height(DEFAULT_VOTING_PERIOD, 10000),
+        info.clone(),
+        msg,
+    )
+    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::WithdrawableAmount {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: WithdrawableAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response,
+        WithdrawableAmountResponse {
+            withdrawable: Uint128::from(70u128),
+            locked: Uint128::from(30u128),
+            blocking_polls: vec![BlockingPoll {
+                poll_id: 2u64,
+                balance: Uint128::from(30u128),
+                end_height: 100 + DEFAULT_VOTING_PERIOD,
+            }],
+        }
+    );
+
+    // the reported amount can be withdrawn
+    let msg = ExecuteMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::from(70u128)),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 }
 
 #[test]
@@ -2092,7 +5176,7 @@ fn fails_cast_vote_twice() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(amount),
+        amount: Some(Uint128::from(amount)),
     };
     let env = mock_env_height(0, 10000);
     let info = mock_info(TEST_VOTER, &[]);
@@ -2102,7 +5186,7 @@ fn fails_cast_vote_twice() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(amount),
+        amount: Some(Uint128::from(amount)),
     };
     let res = execute(deps.as_mut(), env, info, msg);
 
@@ -2122,7 +5206,7 @@ fn fails_cast_vote_without_poll() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 0,
         vote: VoteOption::Yes,
-        amount: Uint128::from(1u128),
+        amount: Some(Uint128::from(1u128)),
     };
     let info = mock_info(TEST_VOTER, &coins(11, VOTING_TOKEN));
 
@@ -2212,6 +5296,103 @@ fn fails_staking_wrong_token() {
     }
 }
 
+#[test]
+fn query_stakers_by_addresses() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    // stake 100 and 200 tokens
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(300u128))],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER_2.to_string(),
+        amount: Uint128::from(200u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // rewards make share:balance = 1:2
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(600u128))],
+    )]);
+
+    let addresses = vec![
+        TEST_VOTER_2.to_string(),
+        TEST_VOTER_3.to_string(),
+        TEST_VOTER.to_string(),
+    ];
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::StakersByAddresses {
+            addresses: addresses.clone(),
+        },
+    )
+    .unwrap();
+    let response: StakersResponse = from_binary(&res).unwrap();
+
+    // batched results match the individual queries
+    let expected: Vec<StakersResponseItem> = addresses
+        .into_iter()
+        .map(|address| {
+            let res = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Staker {
+                    address: address.clone(),
+                },
+            )
+            .unwrap();
+            let staker: StakerResponse = from_binary(&res).unwrap();
+            StakersResponseItem {
+                address,
+                balance: staker.balance,
+                share: staker.share,
+                locked_balance: staker.locked_balance,
+            }
+        })
+        .collect();
+    assert_eq!(response.stakers, expected);
+    assert_eq!(
+        response
+            .stakers
+            .iter()
+            .map(|staker| (staker.balance, staker.share))
+            .collect::<Vec<(Uint128, Uint128)>>(),
+        vec![
+            (Uint128::from(400u128), Uint128::from(200u128)),
+            (Uint128::zero(), Uint128::zero()),
+            (Uint128::from(200u128), Uint128::from(100u128)),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::StakersByAddresses {
+            addresses: vec![TEST_VOTER.to_string(); 31],
+        },
+    );
+    assert_eq!(res, Err(ContractError::TooManyAddresses(30)));
+}
+
 #[test]
 fn share_calculation() {
     let mut deps = mock_dependencies(&[]);
@@ -2314,61 +5495,283 @@ fn assert_create_poll_result(
         ]
     );
 
-    //confirm poll count
-    let state: State = state_read(deps.storage).load().unwrap();
-    assert_eq!(
-        state,
-        State {
-            contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
-            poll_count: 1,
-            total_share: Uint128::zero(),
-            total_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
-        }
+    //confirm poll count
+    let state: State = state_read(deps.storage).load().unwrap();
+    assert_eq!(
+        state,
+        State {
+            contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+            poll_count: 1,
+            total_share: Uint128::zero(),
+            total_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+        }
+    );
+}
+
+fn assert_stake_tokens_result(
+    total_share: u128,
+    total_deposit: u128,
+    new_share: u128,
+    poll_count: u64,
+    execute_res: Response,
+    deps: Deps,
+) {
+    assert_eq!(
+        execute_res.attributes.get(2).expect("no log"),
+        &attr("share", new_share.to_string())
+    );
+
+    let state: State = state_read(deps.storage).load().unwrap();
+    assert_eq!(
+        state,
+        State {
+            contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+            poll_count,
+            total_share: Uint128::from(total_share),
+            total_deposit: Uint128::from(total_deposit),
+        }
+    );
+}
+
+fn assert_cast_vote_success(
+    voter: &str,
+    amount: u128,
+    poll_id: u64,
+    vote_option: VoteOption,
+    execute_res: Response,
+) {
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "cast_vote"),
+            attr("poll_id", poll_id.to_string()),
+            attr("amount", amount.to_string()),
+            attr("voter", voter),
+            attr("vote_option", vote_option.to_string()),
+        ]
+    );
+}
+
+#[test]
+fn transfer_ownership() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "addr0001".to_string(),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    // nothing to accept yet
+    let info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "propose_new_owner"),
+            attr("pending_owner", "addr0001"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, TEST_CREATOR.to_string());
+    assert_eq!(config.pending_owner, Some("addr0001".to_string()));
+
+    // only the proposed owner can accept
+    let info = mock_info("addr0002", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "accept_ownership"),
+            attr("owner", "addr0001")
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, "addr0001".to_string());
+    assert_eq!(config.pending_owner, None);
+
+    // the previous owner lost its privileges
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: TEST_CREATOR.to_string(),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+}
+
+#[test]
+fn drop_ownership_proposal() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let propose_msg = ExecuteMsg::ProposeNewOwner {
+        owner: "addr0001".to_string(),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, propose_msg.clone()).unwrap();
+
+    // the owner changes directly
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("addr0002".to_string()),
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the stale proposal cannot be accepted
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.pending_owner, None);
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    // only the owner can drop a proposal
+    let info = mock_info("addr0002", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, propose_msg).unwrap();
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DropOwnershipProposal {},
     );
-}
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
 
-fn assert_stake_tokens_result(
-    total_share: u128,
-    total_deposit: u128,
-    new_share: u128,
-    poll_count: u64,
-    execute_res: Response,
-    deps: Deps,
-) {
+    let info = mock_info("addr0002", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DropOwnershipProposal {},
+    )
+    .unwrap();
     assert_eq!(
-        execute_res.attributes.get(2).expect("no log"),
-        &attr("share", new_share.to_string())
+        res.attributes,
+        vec![attr("action", "drop_ownership_proposal")]
     );
 
-    let state: State = state_read(deps.storage).load().unwrap();
-    assert_eq!(
-        state,
-        State {
-            contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
-            poll_count,
-            total_share: Uint128::from(total_share),
-            total_deposit: Uint128::from(total_deposit),
-        }
+    let info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
     );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, "addr0002".to_string());
+    assert_eq!(config.pending_owner, None);
 }
 
-fn assert_cast_vote_success(
-    voter: &str,
-    amount: u128,
-    poll_id: u64,
-    vote_option: VoteOption,
-    execute_res: Response,
-) {
-    assert_eq!(
-        execute_res.attributes,
-        vec![
-            attr("action", "cast_vote"),
-            attr("poll_id", poll_id.to_string()),
-            attr("amount", amount.to_string()),
-            attr("voter", voter),
-            attr("vote_option", vote_option.to_string()),
-        ]
-    );
+#[test]
+fn migrate_config() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        veto_period: Some(100u64),
+        unstake_cooldown: Some(50u64),
+        ..instantiate_msg()
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_register_voting_token(deps.as_mut());
+
+    // a config which was already migrated is kept
+    let config: Config = config_read(deps.as_ref().storage).load().unwrap();
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(config_read(deps.as_ref().storage).load().unwrap(), config);
+
+    // a legacy config gets the defaults of the new settings
+    singleton(deps.as_mut().storage, KEY_CONFIG)
+        .save(&LegacyConfig {
+            owner: config.owner.clone(),
+            anchor_token: config.anchor_token.clone(),
+            quorum: config.quorum,
+            threshold: config.threshold,
+            voting_period: config.voting_period,
+            timelock_period: config.timelock_period,
+            expiration_period: config.expiration_period,
+            proposal_deposit: config.proposal_deposit,
+            snapshot_period: config.snapshot_period,
+        })
+        .unwrap();
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let migrated: Config = config_read(deps.as_ref().storage).load().unwrap();
+    assert_eq!(migrated.quorum, config.quorum);
+    assert_eq!(migrated.voting_period, config.voting_period);
+    assert_eq!(migrated.veto_period, 0u64);
+    assert_eq!(migrated.unstake_cooldown, None);
+    assert!(migrated.allow_opaque_execute_msgs);
 }
 
 #[test]
@@ -2387,6 +5790,29 @@ fn update_config() {
         timelock_period: None,
         proposal_deposit: None,
         snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -2412,6 +5838,29 @@ fn update_config() {
         timelock_period: Some(20000u64),
         proposal_deposit: Some(Uint128::from(123u128)),
         snapshot_period: Some(11),
+        quorum_basis: Some(QuorumBasis::Circulating),
+        excluded_addresses: Some(vec!["treasury".to_string()]),
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -2427,6 +5876,8 @@ fn update_config() {
     assert_eq!(20000u64, config.timelock_period);
     assert_eq!(123u128, config.proposal_deposit.u128());
     assert_eq!(11u64, config.snapshot_period);
+    assert_eq!(QuorumBasis::Circulating, config.quorum_basis);
+    assert_eq!(vec!["treasury".to_string()], config.excluded_addresses);
 
     // Unauthorzied err
     let info = mock_info(TEST_CREATOR, &[]);
@@ -2438,6 +5889,29 @@ fn update_config() {
         timelock_period: None,
         proposal_deposit: None,
         snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -2476,16 +5950,19 @@ fn add_several_execute_msgs() {
             order: 1u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz,
+            msg_type: None,
         },
         PollExecuteMsg {
             order: 3u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz3,
+            msg_type: None,
         },
         PollExecuteMsg {
             order: 2u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz2,
+            msg_type: None,
         },
     ];
 
@@ -2554,26 +6031,31 @@ fn execute_poll_with_order() {
             order: 3u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz3.clone(),
+            msg_type: None,
         },
         PollExecuteMsg {
             order: 4u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz4.clone(),
+            msg_type: None,
         },
         PollExecuteMsg {
             order: 2u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz2.clone(),
+            msg_type: None,
         },
         PollExecuteMsg {
             order: 5u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz5.clone(),
+            msg_type: None,
         },
         PollExecuteMsg {
             order: 1u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz.clone(),
+            msg_type: None,
         },
     ];
 
@@ -2600,6 +6082,201 @@ fn execute_poll_with_order() {
         deps.as_ref(),
     );
 
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(stake_amount + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake_amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_stake_tokens_result(
+        stake_amount,
+        DEFAULT_PROPOSAL_DEPOSIT,
+        stake_amount,
+        1,
+        execute_res,
+        deps.as_ref(),
+    );
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(stake_amount)),
+    };
+    let env = mock_env_height(POLL_START_HEIGHT, 10000);
+    let info = mock_info(TEST_VOTER, &[]);
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "cast_vote"),
+            attr("poll_id", POLL_ID.to_string().as_str()),
+            attr("amount", "1000"),
+            attr("voter", TEST_VOTER),
+            attr("vote_option", "yes"),
+        ]
+    );
+
+    creator_info.sender = Addr::unchecked(TEST_CREATOR);
+    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+        ]
+    );
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // End poll will withdraw deposit balance
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(stake_amount),
+        )],
+    )]);
+
+    creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), creator_env.clone(), creator_info, msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::reply_on_error(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: creator_env.contract.address.to_string(),
+                msg: to_binary(&ExecuteMsg::ExecutePollMsgs { poll_id: 1 }).unwrap(),
+                funds: vec![],
+            }),
+            1
+        )]
+    );
+
+    let msg = ExecuteMsg::ExecutePollMsgs { poll_id: 1 };
+    let contract_info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let execute_res = execute(deps.as_mut(), creator_env, contract_info, msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz,
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz2,
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz3,
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz4,
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz5,
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        execute_res.attributes,
+        vec![attr("action", "execute_poll"), attr("poll_id", "1"),]
+    );
+}
+
+#[test]
+fn execute_poll_with_migrate_msg() {
+    const POLL_START_HEIGHT: u64 = 1000;
+    let stake_amount = 1000;
+
+    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+    let mut creator_env = mock_env_height(POLL_START_HEIGHT, 10000);
+    let creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+
+    let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
+        amount: Uint128::new(10),
+    })
+    .unwrap();
+    let migrate_msg_bz = Binary::from(br#"{}"#);
+
+    let execute_msgs: Vec<PollExecuteMsg> = vec![
+        PollExecuteMsg {
+            order: 2u64,
+            contract: "target".to_string(),
+            msg: migrate_msg_bz.clone(),
+            msg_type: Some(PollMsgType::Migrate { new_code_id: 2 }),
+        },
+        PollExecuteMsg {
+            order: 1u64,
+            contract: VOTING_TOKEN.to_string(),
+            msg: exec_msg_bz.clone(),
+            msg_type: Some(PollMsgType::Execute),
+        },
+    ];
+
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(execute_msgs),
+    );
+    let execute_res = execute(deps.as_mut(), creator_env.clone(), creator_info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "create_poll"),
+            attr("creator", TEST_CREATOR),
+            attr("poll_id", "1"),
+            attr(
+                "end_height",
+                (POLL_START_HEIGHT + DEFAULT_VOTING_PERIOD).to_string()
+            ),
+            attr("unverified_migrate_admin", "target"),
+        ]
+    );
+
     deps.querier.with_token_balances(&[(
         &VOTING_TOKEN.to_string(),
         &[(
@@ -2613,43 +6290,21 @@ fn execute_poll_with_order() {
         amount: Uint128::from(stake_amount as u128),
         msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
     });
-
     let info = mock_info(VOTING_TOKEN, &[]);
-    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_stake_tokens_result(
-        stake_amount,
-        DEFAULT_PROPOSAL_DEPOSIT,
-        stake_amount,
-        1,
-        execute_res,
-        deps.as_ref(),
-    );
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
     };
-    let env = mock_env_height(POLL_START_HEIGHT, 10000);
     let info = mock_info(TEST_VOTER, &[]);
-    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
-
-    assert_eq!(
-        execute_res.attributes,
-        vec![
-            attr("action", "cast_vote"),
-            attr("poll_id", POLL_ID.to_string().as_str()),
-            attr("amount", "1000"),
-            attr("voter", TEST_VOTER),
-            attr("vote_option", "yes"),
-        ]
-    );
+    let _res = execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
 
-    creator_info.sender = Addr::unchecked(TEST_CREATOR);
+    let creator_info = mock_info(TEST_CREATOR, &[]);
     creator_env.block.height += DEFAULT_VOTING_PERIOD;
-
     let msg = ExecuteMsg::EndPoll { poll_id: 1 };
-    let execute_res = execute(
+    let _res = execute(
         deps.as_mut(),
         creator_env.clone(),
         creator_info.clone(),
@@ -2657,29 +6312,6 @@ fn execute_poll_with_order() {
     )
     .unwrap();
 
-    assert_eq!(
-        execute_res.attributes,
-        vec![
-            attr("action", "end_poll"),
-            attr("poll_id", "1"),
-            attr("rejected_reason", ""),
-            attr("passed", "true"),
-        ]
-    );
-    assert_eq!(
-        execute_res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: VOTING_TOKEN.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: TEST_CREATOR.to_string(),
-                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))]
-    );
-
-    // End poll will withdraw deposit balance
     deps.querier.with_token_balances(&[(
         &VOTING_TOKEN.to_string(),
         &[(
@@ -2690,18 +6322,7 @@ fn execute_poll_with_order() {
 
     creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
     let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
-    let execute_res = execute(deps.as_mut(), creator_env.clone(), creator_info, msg).unwrap();
-    assert_eq!(
-        execute_res.messages,
-        vec![SubMsg::reply_on_error(
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: creator_env.contract.address.to_string(),
-                msg: to_binary(&ExecuteMsg::ExecutePollMsgs { poll_id: 1 }).unwrap(),
-                funds: vec![],
-            }),
-            1
-        )]
-    );
+    let _res = execute(deps.as_mut(), creator_env.clone(), creator_info, msg).unwrap();
 
     let msg = ExecuteMsg::ExecutePollMsgs { poll_id: 1 };
     let contract_info = mock_info(MOCK_CONTRACT_ADDR, &[]);
@@ -2714,32 +6335,13 @@ fn execute_poll_with_order() {
                 msg: exec_msg_bz,
                 funds: vec![],
             })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz2,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz3,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz4,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz5,
-                funds: vec![],
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Migrate {
+                contract_addr: "target".to_string(),
+                new_code_id: 2,
+                msg: migrate_msg_bz,
             })),
         ]
     );
-    assert_eq!(
-        execute_res.attributes,
-        vec![attr("action", "execute_poll"), attr("poll_id", "1"),]
-    );
 }
 
 #[test]
@@ -2800,7 +6402,7 @@ fn poll_with_empty_execute_data_marked_as_executed() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
     };
     let env = mock_env_height(POLL_START_HEIGHT, 10000);
     let info = mock_info(TEST_VOTER, &[]);
@@ -2962,7 +6564,7 @@ fn poll_with_none_execute_data_marked_as_executed() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
     };
     let env = mock_env_height(POLL_START_HEIGHT, 10000);
     let info = mock_info(TEST_VOTER, &[]);
@@ -3070,77 +6672,427 @@ fn poll_with_none_execute_data_marked_as_executed() {
 fn snapshot_poll() {
     let stake_amount = 1000;
 
-    let mut deps = mock_dependencies(&coins(100, VOTING_TOKEN));
+    let mut deps = mock_dependencies(&coins(100, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let mut creator_env = mock_env();
+    let creator_info = mock_info(VOTING_TOKEN, &[]);
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "create_poll"),
+            attr("creator", TEST_CREATOR),
+            attr("poll_id", "1"),
+            attr("end_height", "32345"),
+        ]
+    );
+
+    //must not be executed
+    let snapshot_err = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        ExecuteMsg::SnapshotPoll { poll_id: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(ContractError::SnapshotHeight {}, snapshot_err);
+
+    // change time
+    creator_env.block.height = 32345 - 10;
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from((stake_amount + DEFAULT_PROPOSAL_DEPOSIT) as u128),
+        )],
+    )]);
+
+    let fix_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        ExecuteMsg::SnapshotPoll { poll_id: 1 },
+    )
+    .unwrap();
+
+    assert_eq!(
+        fix_res.attributes,
+        vec![
+            attr("action", "snapshot_poll"),
+            attr("poll_id", "1"),
+            attr("staked_amount", stake_amount.to_string().as_str()),
+        ]
+    );
+
+    //must not be executed
+    let snapshot_error = execute(
+        deps.as_mut(),
+        creator_env,
+        creator_info,
+        ExecuteMsg::SnapshotPoll { poll_id: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(ContractError::SnapshotAlreadyOccurred {}, snapshot_error);
+}
+
+#[test]
+fn snapshot_polls() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    // make fake polls at various stages
+    for (poll_id, status, end_height, staked_amount) in [
+        (1u64, PollStatus::InProgress, 1005u64, None),
+        (2u64, PollStatus::InProgress, 5000u64, None),
+        (
+            3u64,
+            PollStatus::InProgress,
+            1008u64,
+            Some(Uint128::new(10u128)),
+        ),
+        (4u64, PollStatus::InProgress, 995u64, None),
+        (5u64, PollStatus::Passed, 1003u64, None),
+        (6u64, PollStatus::InProgress, 1010u64, None),
+    ] {
+        poll_store(&mut deps.storage)
+            .save(
+                &poll_id.to_be_bytes(),
+                &Poll {
+                    id: poll_id,
+                    creator: CanonicalAddr::from(vec![]),
+                    status: status.clone(),
+                    yes_votes: Uint128::zero(),
+                    no_votes: Uint128::zero(),
+                    veto_votes: None,
+                    end_height,
+                    end_time: None,
+                    title: "title".to_string(),
+                    description: "description".to_string(),
+                    deposit_amount: Uint128::zero(),
+                    link: None,
+                    execute_data: None,
+                    total_balance_at_end_poll: None,
+                    execution_height: None,
+                    failure_reason: None,
+                    atomic: None,
+                    execution_results: None,
+                    extended: None,
+                    content_hash: None,
+                    voting_period: None,
+                    snapshot_period: None,
+                    timelock_period: None,
+                    staked_amount,
+                },
+            )
+            .unwrap();
+        poll_indexer_store(&mut deps.storage, &status)
+            .save(&poll_id.to_be_bytes(), &true)
+            .unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000u128))],
+    )]);
+
+    let msg = ExecuteMsg::SnapshotPolls {
+        start_after: None,
+        limit: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env_height(1000, 10000),
+        mock_info(TEST_VOTER, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "snapshot_polls"),
+            attr("staked_amount", "1000"),
+            attr("snapshot_poll_id", "1"),
+            attr("snapshot_poll_id", "6"),
+        ]
+    );
+
+    let staked_amounts: Vec<Option<Uint128>> = (1u64..=6u64)
+        .map(|poll_id| {
+            poll_store(&mut deps.storage)
+                .load(&poll_id.to_be_bytes())
+                .unwrap()
+                .staked_amount
+        })
+        .collect();
+    assert_eq!(
+        staked_amounts,
+        vec![
+            Some(Uint128::new(1000u128)),
+            None,
+            Some(Uint128::new(10u128)),
+            None,
+            None,
+            Some(Uint128::new(1000u128)),
+        ]
+    );
+}
+
+#[test]
+fn cast_vote_prunes_locked_balance() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: Some(2),
+        execute_target_policy: None,
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    // poll 1 ends before polls 2 and 3
+    for height in [0u64, 10000u64, 10000u64] {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        execute(
+            deps.as_mut(),
+            mock_env_height(height, 10000),
+            mock_info(VOTING_TOKEN, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    let stake_amount = 1000u128;
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(3 * DEFAULT_PROPOSAL_DEPOSIT + stake_amount),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake_amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let cast_vote = |deps: DepsMut, height: u64, poll_id: u64| {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(10u128)),
+        };
+        execute(
+            deps,
+            mock_env_height(height, 10000),
+            mock_info(TEST_VOTER, &[]),
+            msg,
+        )
+    };
+
+    cast_vote(deps.as_mut(), 15000, 1).unwrap();
+    cast_vote(deps.as_mut(), 15000, 2).unwrap();
+    match cast_vote(deps.as_mut(), 15000, 3) {
+        Err(ContractError::TooManyParticipations(2)) => (),
+        Err(e) => panic!("Unexpected error: {:?}", e),
+        _ => panic!("Must return error"),
+    }
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    execute(
+        deps.as_mut(),
+        mock_env_height(DEFAULT_VOTING_PERIOD + 1, 10000),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    // the ended poll 1 no longer counts against the participations
+    cast_vote(deps.as_mut(), DEFAULT_VOTING_PERIOD + 1, 3).unwrap();
+
+    let token_manager = bank_read(&deps.storage)
+        .load(deps.api.addr_canonicalize(TEST_VOTER).unwrap().as_slice())
+        .unwrap();
+    let voted_polls: Vec<u64> = token_manager
+        .locked_balance
+        .iter()
+        .map(|(poll_id, _)| *poll_id)
+        .collect();
+    assert_eq!(voted_polls, vec![2u64, 3u64]);
+    assert!(poll_voter_read(&deps.storage, 1u64)
+        .load(deps.api.addr_canonicalize(TEST_VOTER).unwrap().as_slice())
+        .is_err());
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.locked_balance, token_manager.locked_balance);
+}
+
+#[test]
+fn cast_vote_with_whole_staked_amount() {
+    let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_register_voting_token(deps.as_mut());
 
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
     let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
-    let mut creator_env = mock_env();
-    let creator_info = mock_info(VOTING_TOKEN, &[]);
-    let execute_res = execute(
+    let _execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(11u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(11u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let _execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // explicit zero amount is rejected
+    let env = mock_env_height(0, 10000);
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::zero()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::InvalidVoteAmount {}) => (),
+        _ => panic!("Must return InvalidVoteAmount error"),
+    }
+
+    // nothing to vote with
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: None,
+    };
+    let res = execute(
         deps.as_mut(),
-        creator_env.clone(),
-        creator_info.clone(),
+        env.clone(),
+        mock_info(TEST_VOTER_2, &[]),
         msg,
-    )
-    .unwrap();
-    assert_eq!(
-        execute_res.attributes,
-        vec![
-            attr("action", "create_poll"),
-            attr("creator", TEST_CREATOR),
-            attr("poll_id", "1"),
-            attr("end_height", "32345"),
-        ]
     );
+    match res {
+        Err(ContractError::NothingStaked {}) => (),
+        _ => panic!("Must return NothingStaked error"),
+    }
 
-    //must not be executed
-    let snapshot_err = execute(
-        deps.as_mut(),
-        creator_env.clone(),
-        creator_info.clone(),
-        ExecuteMsg::SnapshotPoll { poll_id: 1 },
-    )
-    .unwrap_err();
-    assert_eq!(ContractError::SnapshotHeight {}, snapshot_err);
-
-    // change time
-    creator_env.block.height = 32345 - 10;
-
+    // staked balance grows after the voter last looked at it
     deps.querier.with_token_balances(&[(
         &VOTING_TOKEN.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from((stake_amount + DEFAULT_PROPOSAL_DEPOSIT) as u128),
+            &Uint128::from(22u128 + DEFAULT_PROPOSAL_DEPOSIT),
         )],
     )]);
 
-    let fix_res = execute(
-        deps.as_mut(),
-        creator_env.clone(),
-        creator_info.clone(),
-        ExecuteMsg::SnapshotPoll { poll_id: 1 },
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: None,
+    };
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 22, 1, VoteOption::Yes, execute_res);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::from(22u128));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
     )
     .unwrap();
-
+    let response: StakerResponse = from_binary(&res).unwrap();
     assert_eq!(
-        fix_res.attributes,
-        vec![
-            attr("action", "snapshot_poll"),
-            attr("poll_id", "1"),
-            attr("staked_amount", stake_amount.to_string().as_str()),
-        ]
+        response.locked_balance,
+        vec![(
+            1u64,
+            VoterInfo {
+                vote: VoteOption::Yes,
+                balance: Uint128::from(22u128),
+            }
+        )]
     );
 
-    //must not be executed
-    let snapshot_error = execute(
-        deps.as_mut(),
-        creator_env,
-        creator_info,
-        ExecuteMsg::SnapshotPoll { poll_id: 1 },
-    )
-    .unwrap_err();
-    assert_eq!(ContractError::SnapshotAlreadyOccurred {}, snapshot_error);
+    // messages with or without an amount are both accepted
+    let msg: ExecuteMsg =
+        from_slice(br#"{"cast_vote":{"poll_id":1,"vote":"yes","amount":"10"}}"#).unwrap();
+    assert_eq!(
+        msg,
+        ExecuteMsg::CastVote {
+            poll_id: 1,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(10u128)),
+        }
+    );
+    let msg: ExecuteMsg = from_slice(br#"{"cast_vote":{"poll_id":1,"vote":"yes"}}"#).unwrap();
+    assert_eq!(
+        msg,
+        ExecuteMsg::CastVote {
+            poll_id: 1,
+            vote: VoteOption::Yes,
+            amount: None,
+        }
+    );
 }
 
 #[test]
@@ -3195,7 +7147,7 @@ fn happy_days_cast_vote_with_snapshot() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(amount),
+        amount: Some(Uint128::from(amount)),
     };
 
     let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
@@ -3229,7 +7181,7 @@ fn happy_days_cast_vote_with_snapshot() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(10u128),
+        amount: Some(Uint128::from(10u128)),
     };
     let env = mock_env_height(end_height - 9, 10000);
     let info = mock_info(TEST_VOTER_2, &[]);
@@ -3261,30 +7213,201 @@ fn happy_days_cast_vote_with_snapshot() {
 
     // another voter cast a vote but the snapshot is already occurred
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_VOTER_3.to_string(),
-        amount: Uint128::from(11u128),
+        sender: TEST_VOTER_3.to_string(),
+        amount: Uint128::from(11u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let _execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(10u128)),
+    };
+    let env = mock_env_height(end_height - 8, 10000);
+    let info = mock_info(TEST_VOTER_3, &[]);
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER_3, amount, 1, VoteOption::Yes, execute_res);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.staked_amount, Some(Uint128::new(22)));
+}
+
+#[test]
+fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
+    const POLL_START_HEIGHT: u64 = 1000;
+    const POLL_ID: u64 = 1;
+    let stake_amount = 1000;
+
+    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let mut creator_env = mock_env_height(POLL_START_HEIGHT, 10000);
+    let mut creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+
+    let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
+        amount: Uint128::new(123),
+    })
+    .unwrap();
+
+    //add two messages
+    let execute_msgs: Vec<PollExecuteMsg> = vec![
+        PollExecuteMsg {
+            order: 1u64,
+            contract: VOTING_TOKEN.to_string(),
+            msg: exec_msg_bz.clone(),
+            msg_type: None,
+        },
+        PollExecuteMsg {
+            order: 2u64,
+            contract: VOTING_TOKEN.to_string(),
+            msg: exec_msg_bz,
+            msg_type: None,
+        },
+    ];
+
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(execute_msgs),
+    );
+
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+
+    assert_create_poll_result(
+        1,
+        creator_env.block.height + DEFAULT_VOTING_PERIOD,
+        TEST_CREATOR,
+        execute_res,
+        deps.as_ref(),
+    );
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from((stake_amount + DEFAULT_PROPOSAL_DEPOSIT) as u128),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake_amount as u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_stake_tokens_result(
+        stake_amount,
+        DEFAULT_PROPOSAL_DEPOSIT,
+        stake_amount,
+        1,
+        execute_res,
+        deps.as_ref(),
+    );
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(stake_amount)),
+    };
+    let env = mock_env_height(POLL_START_HEIGHT, 10000);
+    let info = mock_info(TEST_VOTER, &[]);
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "cast_vote"),
+            attr("poll_id", POLL_ID.to_string().as_str()),
+            attr("amount", "1000"),
+            attr("voter", TEST_VOTER),
+            attr("vote_option", "yes"),
+        ]
+    );
+
+    creator_env.block.height += DEFAULT_VOTING_PERIOD - 10;
+
+    // did not SnapshotPoll
+
+    // staked amount get increased 10 times
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(((10 * stake_amount) + DEFAULT_PROPOSAL_DEPOSIT) as u128),
+        )],
+    )]);
+
+    //cast another vote
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER_2.to_string(),
+        amount: Uint128::from(8 * stake_amount as u128),
         msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
     });
 
     let info = mock_info(VOTING_TOKEN, &[]);
     let _execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // another voter cast a vote
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(10u128),
+        amount: Some(Uint128::from(stake_amount)),
     };
-    let env = mock_env_height(end_height - 8, 10000);
-    let info = mock_info(TEST_VOTER_3, &[]);
+    let env = mock_env_height(creator_env.block.height, 10000);
+    let info = mock_info(TEST_VOTER_2, &[]);
     let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_cast_vote_success(TEST_VOTER_3, amount, 1, VoteOption::Yes, execute_res);
+
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "cast_vote"),
+            attr("poll_id", POLL_ID.to_string().as_str()),
+            attr("amount", "1000"),
+            attr("voter", TEST_VOTER_2),
+            attr("vote_option", "yes"),
+        ]
+    );
+
+    creator_info.sender = Addr::unchecked(TEST_CREATOR);
+    creator_env.block.height += 10;
+
+    // quorum must reach
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Quorum not reached"),
+            attr("passed", "false"),
+        ]
+    );
 
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
     let value: PollResponse = from_binary(&res).unwrap();
-    assert_eq!(value.staked_amount, Some(Uint128::new(22)));
+    assert_eq!(
+        10 * stake_amount,
+        value.total_balance_at_end_poll.unwrap().u128()
+    );
 }
 
 #[test]
-fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
+fn happy_days_end_poll_with_controlled_quorum() {
     const POLL_START_HEIGHT: u64 = 1000;
     const POLL_ID: u64 = 1;
     let stake_amount = 1000;
@@ -3307,11 +7430,13 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
             order: 1u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz.clone(),
+            msg_type: None,
         },
         PollExecuteMsg {
             order: 2u64,
             contract: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz,
+            msg_type: None,
         },
     ];
 
@@ -3366,7 +7491,7 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
     };
     let env = mock_env_height(POLL_START_HEIGHT, 10000);
     let info = mock_info(TEST_VOTER, &[]);
@@ -3385,7 +7510,23 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
 
     creator_env.block.height += DEFAULT_VOTING_PERIOD - 10;
 
-    // did not SnapshotPoll
+    // send SnapshotPoll
+    let fix_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        ExecuteMsg::SnapshotPoll { poll_id: 1 },
+    )
+    .unwrap();
+
+    assert_eq!(
+        fix_res.attributes,
+        vec![
+            attr("action", "snapshot_poll"),
+            attr("poll_id", "1"),
+            attr("staked_amount", stake_amount.to_string().as_str()),
+        ]
+    );
 
     // staked amount get increased 10 times
     deps.querier.with_token_balances(&[(
@@ -3406,11 +7547,10 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
     let info = mock_info(VOTING_TOKEN, &[]);
     let _execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // another voter cast a vote
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(8 * stake_amount)),
     };
     let env = mock_env_height(creator_env.block.height, 10000);
     let info = mock_info(TEST_VOTER_2, &[]);
@@ -3421,7 +7561,7 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
         vec![
             attr("action", "cast_vote"),
             attr("poll_id", POLL_ID.to_string().as_str()),
-            attr("amount", "1000"),
+            attr("amount", "8000"),
             attr("voter", TEST_VOTER_2),
             attr("vote_option", "yes"),
         ]
@@ -3439,225 +7579,900 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
         vec![
             attr("action", "end_poll"),
             attr("poll_id", "1"),
-            attr("rejected_reason", "Quorum not reached"),
-            attr("passed", "false"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
         ]
     );
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
 
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
     let value: PollResponse = from_binary(&res).unwrap();
     assert_eq!(
-        10 * stake_amount,
-        value.total_balance_at_end_poll.unwrap().u128()
+        stake_amount,
+        value.total_balance_at_end_poll.unwrap().u128()
+    );
+
+    assert_eq!(value.yes_votes.u128(), 9 * stake_amount);
+
+    // actual staked amount is 10 times bigger than staked amount
+    let actual_staked_weight = query_token_balance(
+        &deps.as_ref().querier,
+        Addr::unchecked(VOTING_TOKEN),
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+    )
+    .unwrap()
+    .checked_sub(Uint128::from(DEFAULT_PROPOSAL_DEPOSIT))
+    .unwrap();
+
+    assert_eq!(actual_staked_weight.u128(), (10 * stake_amount))
+}
+
+#[test]
+fn create_poll_with_execute_target_policy() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let update_config_msg = |execute_target_policy| ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: Some(execute_target_policy),
+        min_vote_amount: None,
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+    };
+    let poll_execute_msg = |contract: &str| {
+        create_poll_msg(
+            "test".to_string(),
+            "test".to_string(),
+            None,
+            Some(vec![PollExecuteMsg {
+                order: 1u64,
+                contract: contract.to_string(),
+                msg: Binary::from(br#"{"burn":{"amount":"1"}}"#.to_vec()),
+                msg_type: None,
+            }]),
+        )
+    };
+    let env = mock_env_height(0, 10000);
+
+    // no policy configured, any target is allowed
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        poll_execute_msg("escrow0000"),
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        update_config_msg(ExecuteTargetPolicy::Denylist {
+            contracts: vec!["escrow0000".to_string()],
+        }),
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config.execute_target_policy,
+        Some(ExecuteTargetPolicy::Denylist {
+            contracts: vec!["escrow0000".to_string()],
+        })
+    );
+
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        poll_execute_msg("escrow0000"),
+    ) {
+        Err(ContractError::DisallowedExecuteTarget {}) => (),
+        _ => panic!("Must return disallowed execute target error"),
+    }
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        poll_execute_msg("community0000"),
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        update_config_msg(ExecuteTargetPolicy::Allowlist {
+            contracts: vec!["community0000".to_string()],
+        }),
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        poll_execute_msg("community0000"),
+    )
+    .unwrap();
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        poll_execute_msg("collector0000"),
+    ) {
+        Err(ContractError::DisallowedExecuteTarget {}) => (),
+        _ => panic!("Must return disallowed execute target error"),
+    }
+
+    // polls without execute messages are not restricted
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg("test".to_string(), "test".to_string(), None, None),
+    )
+    .unwrap();
+}
+
+#[test]
+fn create_poll_with_undecodable_execute_msg() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let poll_execute_msg = |msg: Binary| {
+        create_poll_msg(
+            "test".to_string(),
+            "test".to_string(),
+            None,
+            Some(vec![PollExecuteMsg {
+                order: 1u64,
+                contract: VOTING_TOKEN.to_string(),
+                msg,
+                msg_type: None,
+            }]),
+        )
+    };
+    let env = mock_env_height(0, 10000);
+
+    let valid_msg = to_binary(&Cw20ExecuteMsg::Burn {
+        amount: Uint128::new(123),
+    })
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        poll_execute_msg(valid_msg),
+    )
+    .unwrap();
+
+    for garbage in [&b"not json"[..], b"[1,2]", b"{}", br#"{"a":{},"b":{}}"#] {
+        match execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(VOTING_TOKEN, &[]),
+            poll_execute_msg(Binary::from(garbage)),
+        ) {
+            Err(ContractError::UndecodableExecuteMsg {}) => (),
+            _ => panic!("Must return undecodable execute msg error"),
+        }
+    }
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateConfig {
+            owner: None,
+            quorum: None,
+            threshold: None,
+            voting_period: None,
+            timelock_period: None,
+            proposal_deposit: None,
+            snapshot_period: None,
+            quorum_basis: None,
+            excluded_addresses: None,
+            stale_poll_grace_period: None,
+            veto_period: None,
+            guardian: None,
+            poll_length_limits: None,
+            use_time: None,
+            voting_period_seconds: None,
+            snapshot_period_seconds: None,
+            timelock_period_seconds: None,
+            stale_poll_grace_period_seconds: None,
+            max_participations: None,
+            execute_target_policy: None,
+            min_vote_amount: None,
+            veto_threshold: None,
+            community_contract: None,
+            quorum_reduction_after: None,
+            quorum_reduction_after_seconds: None,
+            quorum_reduction_factor: None,
+            allow_opaque_execute_msgs: Some(true),
+            allow_vote_change: None,
+            min_yes_votes: None,
+            unstake_cooldown: None,
+        },
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert!(config.allow_opaque_execute_msgs);
+
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(VOTING_TOKEN, &[]),
+        poll_execute_msg(Binary::from(b"not json")),
+    )
+    .unwrap();
+}
+
+#[test]
+fn execute_non_atomic_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    // the poll keeps the atomic flag it was created with
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: to_binary(&Cw20HookMsg::CreatePoll {
+            title: "test".to_string(),
+            description: "test".to_string(),
+            link: None,
+            execute_msgs: None,
+            atomic: Some(false),
+            content_hash: None,
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert!(!poll_res.atomic);
+
+    // make fake passed polls
+    let contract_raw = deps.api.addr_canonicalize(VOTING_TOKEN).unwrap();
+    let execute_data = |orders: &[u64]| {
+        orders
+            .iter()
+            .map(|order| ExecuteData {
+                order: *order,
+                contract: contract_raw.clone(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::new(*order as u128),
+                })
+                .unwrap(),
+                msg_type: None,
+            })
+            .collect::<Vec<ExecuteData>>()
+    };
+    for (poll_id, atomic, orders) in [
+        (2u64, Some(false), vec![3u64, 1u64, 2u64]),
+        (3u64, Some(false), vec![1u64, 2u64]),
+        (4u64, None, vec![1u64]),
+    ] {
+        poll_store(&mut deps.storage)
+            .save(
+                &poll_id.to_be_bytes(),
+                &Poll {
+                    id: poll_id,
+                    creator: contract_raw.clone(),
+                    status: PollStatus::Passed,
+                    yes_votes: Uint128::zero(),
+                    no_votes: Uint128::zero(),
+                    veto_votes: None,
+                    end_height: 1000u64,
+                    end_time: None,
+                    title: "title".to_string(),
+                    description: "description".to_string(),
+                    deposit_amount: Uint128::zero(),
+                    link: None,
+                    execute_data: Some(execute_data(&orders)),
+                    total_balance_at_end_poll: None,
+                    execution_height: None,
+                    failure_reason: None,
+                    atomic,
+                    execution_results: None,
+                    extended: None,
+                    content_hash: None,
+                    voting_period: None,
+                    snapshot_period: None,
+                    timelock_period: None,
+                    staked_amount: None,
+                },
+            )
+            .unwrap();
+        poll_indexer_store(&mut deps.storage, &PollStatus::Passed)
+            .save(&poll_id.to_be_bytes(), &true)
+            .unwrap();
+    }
+
+    let env = mock_env_height(1000 + DEFAULT_TIMELOCK_PERIOD, 10000);
+    let info = mock_info(TEST_CREATOR, &[]);
+
+    // each message of a non atomic poll is its own submsg
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ExecutePoll { poll_id: 2 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        execute_data(&[1u64, 2u64, 3u64])
+            .into_iter()
+            .enumerate()
+            .map(|(index, data)| SubMsg::reply_on_error(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: data.msg,
+                    funds: vec![],
+                }),
+                (2u64 << 32) | index as u64,
+            ))
+            .collect::<Vec<SubMsg>>()
+    );
+
+    // the second message fails, the others went through
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: (2u64 << 32) | 1,
+            result: ContractResult::Err("Error".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "fail_poll_message"),
+            attr("poll_id", "2"),
+            attr("order", "2"),
+            attr("failure_reason", "Error"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Poll { poll_id: 2 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Executed);
+    assert_eq!(poll_res.failure_reason, None);
+    assert_eq!(
+        poll_res.execution_results,
+        Some(vec![
+            (1u64, true, None),
+            (2u64, false, Some("Error".to_string())),
+            (3u64, true, None),
+        ])
+    );
+
+    // the poll fails once all of its messages failed
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ExecutePoll { poll_id: 3 },
+    )
+    .unwrap();
+    for index in 0..2u64 {
+        reply(
+            deps.as_mut(),
+            env.clone(),
+            Reply {
+                id: (3u64 << 32) | index,
+                result: ContractResult::Err(format!("Error {}", index)),
+            },
+        )
+        .unwrap();
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Poll { poll_id: 3 }).unwrap();
+        let poll_res: PollResponse = from_binary(&res).unwrap();
+        if index == 0 {
+            assert_eq!(poll_res.status, PollStatus::Executed);
+        } else {
+            assert_eq!(poll_res.status, PollStatus::Failed);
+            assert_eq!(poll_res.failure_reason, Some("Error 1".to_string()));
+        }
+    }
+
+    // reply ids of unknown messages are rejected
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: (3u64 << 32) | 2,
+            result: ContractResult::Err("Error".to_string()),
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidReplyId {}));
+
+    // atomic polls keep executing all messages in one submsg
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ExecutePoll { poll_id: 4 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_error(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                msg: to_binary(&ExecuteMsg::ExecutePollMsgs { poll_id: 4 }).unwrap(),
+                funds: vec![],
+            }),
+            1
+        )]
     );
+
+    let res = query(deps.as_ref(), env, QueryMsg::Poll { poll_id: 4 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert!(poll_res.atomic);
+    assert_eq!(poll_res.execution_results, None);
 }
 
 #[test]
-fn happy_days_end_poll_with_controlled_quorum() {
-    const POLL_START_HEIGHT: u64 = 1000;
-    const POLL_ID: u64 = 1;
-    let stake_amount = 1000;
-
-    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+fn cast_vote_min_vote_amount() {
+    let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_register_voting_token(deps.as_mut());
 
-    let mut creator_env = mock_env_height(POLL_START_HEIGHT, 10000);
-    let mut creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
-
-    let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
-        amount: Uint128::new(123),
-    })
+    let update_config_msg = |min_vote_amount: u128| ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        quorum_basis: None,
+        excluded_addresses: None,
+        stale_poll_grace_period: None,
+        veto_period: None,
+        guardian: None,
+        poll_length_limits: None,
+        use_time: None,
+        voting_period_seconds: None,
+        snapshot_period_seconds: None,
+        timelock_period_seconds: None,
+        stale_poll_grace_period_seconds: None,
+        max_participations: None,
+        execute_target_policy: None,
+        min_vote_amount: Some(Uint128::from(min_vote_amount)),
+        veto_threshold: None,
+        community_contract: None,
+        quorum_reduction_after: None,
+        quorum_reduction_after_seconds: None,
+        quorum_reduction_factor: None,
+        allow_opaque_execute_msgs: None,
+        allow_vote_change: None,
+        min_yes_votes: None,
+        unstake_cooldown: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        update_config_msg(100),
+    )
     .unwrap();
 
-    //add two messages
-    let execute_msgs: Vec<PollExecuteMsg> = vec![
-        PollExecuteMsg {
-            order: 1u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz.clone(),
-        },
-        PollExecuteMsg {
-            order: 2u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz,
-        },
-    ];
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let mut total_staked = 0u128;
+    for (voter, stake_amount) in [
+        (TEST_VOTER, 1000u128),
+        (TEST_VOTER_2, 50u128),
+        (TEST_VOTER_3, 1000u128),
+    ] {
+        total_staked += stake_amount;
+        deps.querier.with_token_balances(&[(
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT + total_staked),
+            )],
+        )]);
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::from(stake_amount),
+            msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    }
 
-    let msg = create_poll_msg(
-        "test".to_string(),
-        "test".to_string(),
-        None,
-        Some(execute_msgs),
-    );
+    let cast_vote = |deps: DepsMut, voter: &str, amount: Option<u128>| {
+        let msg = ExecuteMsg::CastVote {
+            poll_id: 1,
+            vote: VoteOption::Yes,
+            amount: amount.map(Uint128::from),
+        };
+        execute(deps, mock_env(), mock_info(voter, &[]), msg)
+    };
 
-    let execute_res = execute(
+    match cast_vote(deps.as_mut(), TEST_VOTER, Some(99)) {
+        Err(ContractError::VoteAmountTooSmall(100)) => (),
+        _ => panic!("Must return VoteAmountTooSmall error"),
+    }
+    cast_vote(deps.as_mut(), TEST_VOTER, Some(100)).unwrap();
+
+    // the whole staked amount is below the minimum
+    match cast_vote(deps.as_mut(), TEST_VOTER_2, None) {
+        Err(ContractError::VoteAmountTooSmall(100)) => (),
+        _ => panic!("Must return VoteAmountTooSmall error"),
+    }
+
+    // raising the minimum mid-poll applies to new votes only
+    execute(
         deps.as_mut(),
-        creator_env.clone(),
-        creator_info.clone(),
-        msg,
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        update_config_msg(500),
     )
     .unwrap();
 
-    assert_create_poll_result(
-        1,
-        creator_env.block.height + DEFAULT_VOTING_PERIOD,
-        TEST_CREATOR,
-        execute_res,
-        deps.as_ref(),
-    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.min_vote_amount, Uint128::from(500u128));
+
+    match cast_vote(deps.as_mut(), TEST_VOTER_3, Some(499)) {
+        Err(ContractError::VoteAmountTooSmall(500)) => (),
+        _ => panic!("Must return VoteAmountTooSmall error"),
+    }
+    cast_vote(deps.as_mut(), TEST_VOTER_3, Some(500)).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll.yes_votes, Uint128::from(600u128));
+}
+
+#[test]
+fn change_vote() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
 
     deps.querier.with_token_balances(&[(
         &VOTING_TOKEN.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from((stake_amount + DEFAULT_PROPOSAL_DEPOSIT) as u128),
+            &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT + 1000u128),
         )],
     )]);
 
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(stake_amount as u128),
+        amount: Uint128::from(1000u128),
         msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
     });
-
-    let info = mock_info(VOTING_TOKEN, &[]);
-    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_stake_tokens_result(
-        stake_amount,
-        DEFAULT_PROPOSAL_DEPOSIT,
-        stake_amount,
-        1,
-        execute_res,
-        deps.as_ref(),
-    );
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
 
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(600u128)),
     };
-    let env = mock_env_height(POLL_START_HEIGHT, 10000);
-    let info = mock_info(TEST_VOTER, &[]);
-    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    let change_vote = |deps: DepsMut, voter: &str, vote: VoteOption, amount: Option<u128>| {
+        let msg = ExecuteMsg::ChangeVote {
+            poll_id: 1,
+            new_vote: vote,
+            new_amount: amount.map(Uint128::from),
+        };
+        execute(deps, mock_env(), mock_info(voter, &[]), msg)
+    };
+
+    // vote changes are disabled by default
+    match change_vote(deps.as_mut(), TEST_VOTER, VoteOption::No, None) {
+        Err(ContractError::VoteChangeNotAllowed {}) => (),
+        _ => panic!("Must return VoteChangeNotAllowed error"),
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateConfig {
+            owner: None,
+            quorum: None,
+            threshold: None,
+            voting_period: None,
+            timelock_period: None,
+            proposal_deposit: None,
+            snapshot_period: None,
+            quorum_basis: None,
+            excluded_addresses: None,
+            stale_poll_grace_period: None,
+            veto_period: None,
+            guardian: None,
+            poll_length_limits: None,
+            use_time: None,
+            voting_period_seconds: None,
+            snapshot_period_seconds: None,
+            timelock_period_seconds: None,
+            stale_poll_grace_period_seconds: None,
+            max_participations: None,
+            execute_target_policy: None,
+            min_vote_amount: None,
+            veto_threshold: None,
+            community_contract: None,
+            quorum_reduction_after: None,
+            quorum_reduction_after_seconds: None,
+            quorum_reduction_factor: None,
+            allow_opaque_execute_msgs: None,
+            allow_vote_change: Some(true),
+            min_yes_votes: None,
+            unstake_cooldown: None,
+        },
+    )
+    .unwrap();
+
+    match change_vote(deps.as_mut(), TEST_VOTER_2, VoteOption::No, None) {
+        Err(ContractError::NotVoted {}) => (),
+        _ => panic!("Must return NotVoted error"),
+    }
+    match change_vote(deps.as_mut(), TEST_VOTER, VoteOption::No, Some(1001)) {
+        Err(ContractError::InsufficientStaked {}) => (),
+        _ => panic!("Must return InsufficientStaked error"),
+    }
 
+    // change the vote option, keeping the amount
+    let res = change_vote(deps.as_mut(), TEST_VOTER, VoteOption::No, Some(600)).unwrap();
     assert_eq!(
-        execute_res.attributes,
+        res.attributes,
         vec![
-            attr("action", "cast_vote"),
-            attr("poll_id", POLL_ID.to_string().as_str()),
-            attr("amount", "1000"),
+            attr("action", "change_vote"),
+            attr("poll_id", "1"),
+            attr("amount", "600"),
             attr("voter", TEST_VOTER),
-            attr("vote_option", "yes"),
+            attr("vote_option", "no"),
         ]
     );
 
-    creator_env.block.height += DEFAULT_VOTING_PERIOD - 10;
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll.yes_votes, Uint128::zero());
+    assert_eq!(poll.no_votes, Uint128::from(600u128));
 
-    // send SnapshotPoll
-    let fix_res = execute(
-        deps.as_mut(),
-        creator_env.clone(),
-        creator_info.clone(),
-        ExecuteMsg::SnapshotPoll { poll_id: 1 },
+    // change the amount to the whole staked amount
+    change_vote(deps.as_mut(), TEST_VOTER, VoteOption::No, None).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll.yes_votes, Uint128::zero());
+    assert_eq!(poll.no_votes, Uint128::from(1000u128));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
     )
     .unwrap();
-
+    let staker: StakerResponse = from_binary(&res).unwrap();
     assert_eq!(
-        fix_res.attributes,
-        vec![
-            attr("action", "snapshot_poll"),
-            attr("poll_id", "1"),
-            attr("staked_amount", stake_amount.to_string().as_str()),
-        ]
+        staker.locked_balance,
+        vec![(
+            1u64,
+            VoterInfo {
+                vote: VoteOption::No,
+                balance: Uint128::from(1000u128),
+            }
+        )]
     );
+}
+
+#[test]
+fn query_max_votable_amount() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_voting_token(deps.as_mut());
+
+    for _ in 0..2 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    }
 
-    // staked amount get increased 10 times
     deps.querier.with_token_balances(&[(
         &VOTING_TOKEN.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(((10 * stake_amount) + DEFAULT_PROPOSAL_DEPOSIT) as u128),
+            &Uint128::from(2 * DEFAULT_PROPOSAL_DEPOSIT + 1000u128),
         )],
     )]);
 
-    //cast another vote
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_VOTER_2.to_string(),
-        amount: Uint128::from(8 * stake_amount as u128),
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(1000u128),
         msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
     });
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let max_votable_amount = |deps: Deps, poll_id: u64, address: &str| -> Uint128 {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::MaxVotableAmount {
+                poll_id,
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+        let response: MaxVotableAmountResponse = from_binary(&res).unwrap();
+        response.amount
+    };
+    let cast_vote = |deps: DepsMut, poll_id: u64, amount: Uint128| {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(amount),
+        };
+        execute(deps, mock_env(), mock_info(TEST_VOTER, &[]), msg)
+    };
 
-    let info = mock_info(VOTING_TOKEN, &[]);
-    let _execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let amount = max_votable_amount(deps.as_ref(), 1, TEST_VOTER);
+    assert_eq!(amount, Uint128::from(1000u128));
+    assert_eq!(
+        max_votable_amount(deps.as_ref(), 1, TEST_VOTER_2),
+        Uint128::zero()
+    );
 
-    let msg = ExecuteMsg::CastVote {
-        poll_id: 1,
-        vote: VoteOption::Yes,
-        amount: Uint128::from(8 * stake_amount),
-    };
-    let env = mock_env_height(creator_env.block.height, 10000);
-    let info = mock_info(TEST_VOTER_2, &[]);
-    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    // cast_vote accepts exactly the returned amount
+    match cast_vote(deps.as_mut(), 1, amount + Uint128::from(1u128)) {
+        Err(ContractError::InsufficientStaked {}) => (),
+        _ => panic!("Must return InsufficientStaked error"),
+    }
+    cast_vote(deps.as_mut(), 1, amount).unwrap();
 
+    // nothing more can be cast on the voted poll, the other poll is unaffected
     assert_eq!(
-        execute_res.attributes,
-        vec![
-            attr("action", "cast_vote"),
-            attr("poll_id", POLL_ID.to_string().as_str()),
-            attr("amount", "8000"),
-            attr("voter", TEST_VOTER_2),
-            attr("vote_option", "yes"),
-        ]
+        max_votable_amount(deps.as_ref(), 1, TEST_VOTER),
+        Uint128::zero()
     );
+    let amount = max_votable_amount(deps.as_ref(), 2, TEST_VOTER);
+    assert_eq!(amount, Uint128::from(1000u128));
+    cast_vote(deps.as_mut(), 2, amount).unwrap();
+}
 
-    creator_info.sender = Addr::unchecked(TEST_CREATOR);
-    creator_env.block.height += 10;
+#[test]
+fn query_max_votable_amount_not_votable() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        min_vote_amount: Some(Uint128::from(500u128)),
+        ..instantiate_msg()
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_register_voting_token(deps.as_mut());
 
-    // quorum must reach
-    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
-    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+    for _ in 0..2 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    }
+
+    let max_votable_amount = |deps: Deps, env: Env, poll_id: u64| -> Uint128 {
+        let res = query(
+            deps,
+            env,
+            QueryMsg::MaxVotableAmount {
+                poll_id,
+                address: TEST_VOTER.to_string(),
+            },
+        )
+        .unwrap();
+        let response: MaxVotableAmountResponse = from_binary(&res).unwrap();
+        response.amount
+    };
 
+    // a staked balance below the minimum vote amount cannot vote
+    let mut staked = 0u128;
+    for amount in [400u128, 200u128] {
+        staked += amount;
+        deps.querier.with_token_balances(&[(
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(2 * DEFAULT_PROPOSAL_DEPOSIT + staked),
+            )],
+        )]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: TEST_VOTER.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+        if staked == 400u128 {
+            assert_eq!(
+                max_votable_amount(deps.as_ref(), mock_env(), 1),
+                Uint128::zero()
+            );
+        }
+    }
     assert_eq!(
-        execute_res.attributes,
-        vec![
-            attr("action", "end_poll"),
-            attr("poll_id", "1"),
-            attr("rejected_reason", ""),
-            attr("passed", "true"),
-        ]
+        max_votable_amount(deps.as_ref(), mock_env(), 1),
+        Uint128::from(600u128)
     );
+
+    // nonexistent poll
     assert_eq!(
-        execute_res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: VOTING_TOKEN.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: TEST_CREATOR.to_string(),
-                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))]
+        max_votable_amount(deps.as_ref(), mock_env(), 3),
+        Uint128::zero()
     );
 
-    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
-    let value: PollResponse = from_binary(&res).unwrap();
+    // the voting period is over
+    let end_height = mock_env().block.height + DEFAULT_VOTING_PERIOD;
+    let mut env = mock_env();
+    env.block.height = end_height + 1;
     assert_eq!(
-        stake_amount,
-        value.total_balance_at_end_poll.unwrap().u128()
+        max_votable_amount(deps.as_ref(), env.clone(), 1),
+        Uint128::zero()
     );
 
-    assert_eq!(value.yes_votes.u128(), 9 * stake_amount);
-
-    // actual staked amount is 10 times bigger than staked amount
-    let actual_staked_weight = query_token_balance(
-        &deps.as_ref().querier,
-        Addr::unchecked(VOTING_TOKEN),
-        Addr::unchecked(MOCK_CONTRACT_ADDR),
+    // the poll is no longer in progress
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::EndPoll { poll_id: 2 },
     )
-    .unwrap()
-    .checked_sub(Uint128::from(DEFAULT_PROPOSAL_DEPOSIT))
     .unwrap();
-
-    assert_eq!(actual_staked_weight.u128(), (10 * stake_amount))
+    assert_eq!(
+        max_votable_amount(deps.as_ref(), mock_env(), 2),
+        Uint128::zero()
+    );
 }
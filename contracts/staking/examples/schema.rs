@@ -2,8 +2,9 @@ use std::env::current_dir;
 use std::fs::create_dir_all;
 
 use anchor_token::staking::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, StakerInfoResponse,
-    StateResponse,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, NextScheduleChangeResponse,
+    ProjectedRewardResponse, QueryMsg, RewardIndexCheckpointResponse, RewardIndexHistoryResponse,
+    StakerInfoResponse, StakerRewardBetweenResponse, StateResponse,
 };
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
@@ -20,4 +21,9 @@ fn main() {
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(StakerInfoResponse), &out_dir);
     export_schema(&schema_for!(StateResponse), &out_dir);
+    export_schema(&schema_for!(ProjectedRewardResponse), &out_dir);
+    export_schema(&schema_for!(NextScheduleChangeResponse), &out_dir);
+    export_schema(&schema_for!(StakerRewardBetweenResponse), &out_dir);
+    export_schema(&schema_for!(RewardIndexCheckpointResponse), &out_dir);
+    export_schema(&schema_for!(RewardIndexHistoryResponse), &out_dir);
 }
@@ -2,20 +2,28 @@
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, Binary, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Response, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 
 use anchor_token::staking::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
-    StakerInfoResponse, StateResponse,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    NextScheduleChangeResponse, PendingRewardResponse, ProjectedRewardResponse, QueryMsg,
+    RewardIndexCheckpointResponse, RewardIndexHistoryResponse, RewardTokenResponse,
+    StakerInfoResponse, StakerRewardBetweenResponse, StakingTokenResponse, StateResponse,
 };
 
 use crate::{
     querier::query_anc_minter,
     state::{
-        read_config, read_staker_info, read_state, remove_staker_info, store_config,
-        store_staker_info, store_state, Config, StakerInfo, State,
+        read_bond_for_allowlist, read_config, read_deprecated_staking_tokens, read_pause_info,
+        read_reward_index_checkpoint_at, read_reward_index_checkpoint_interval,
+        read_reward_index_checkpoints, read_reward_token, read_reward_tokens, read_staker_info,
+        read_staker_reward, read_state, remove_staker_info, store_bond_for_allowlist, store_config,
+        store_deprecated_staking_tokens, store_pause_info, store_reward_index_checkpoint,
+        store_reward_index_checkpoint_interval, store_reward_token, store_staker_info,
+        store_staker_reward, store_state, Config, PauseInfo, RewardIndexCheckpoint, RewardToken,
+        StakerInfo, StakerReward, State,
     },
 };
 
@@ -35,6 +43,10 @@ pub fn instantiate(
             anchor_token: deps.api.addr_canonicalize(&msg.anchor_token)?,
             staking_token: deps.api.addr_canonicalize(&msg.staking_token)?,
             distribution_schedule: msg.distribution_schedule,
+            slash_destination: match msg.slash_destination {
+                Some(slash_destination) => Some(deps.api.addr_canonicalize(&slash_destination)?),
+                None => None,
+            },
         },
     )?;
 
@@ -47,6 +59,18 @@ pub fn instantiate(
         },
     )?;
 
+    store_pause_info(
+        deps.storage,
+        &PauseInfo {
+            paused: false,
+            pauser: match msg.pauser {
+                Some(pauser) => Some(deps.api.addr_canonicalize(&pauser)?),
+                None => None,
+            },
+            allow_unbond_when_paused: false,
+        },
+    )?;
+
     Ok(Response::default())
 }
 
@@ -55,16 +79,50 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::Unbond { amount } => unbond(deps, env, info, amount),
-        ExecuteMsg::Withdraw {} => withdraw(deps, env, info),
+        ExecuteMsg::Withdraw { reward_token } => withdraw(deps, env, info, reward_token),
         ExecuteMsg::MigrateStaking {
             new_staking_contract,
         } => migrate_staking(deps, env, info, new_staking_contract),
         ExecuteMsg::UpdateConfig {
             distribution_schedule,
-        } => update_config(deps, env, info, distribution_schedule),
+            allow_retroactive,
+            slash_destination,
+            pauser,
+            allow_unbond_when_paused,
+            bond_for_allowlist,
+            reward_index_checkpoint_interval,
+        } => update_config(
+            deps,
+            env,
+            info,
+            distribution_schedule,
+            allow_retroactive,
+            slash_destination,
+            pauser,
+            allow_unbond_when_paused,
+            bond_for_allowlist,
+            reward_index_checkpoint_interval,
+        ),
+        ExecuteMsg::AddStakingToken { address, active } => {
+            add_staking_token(deps, info, address, active)
+        }
+        ExecuteMsg::Slash { staker, amount } => slash(deps, env, info, staker, amount),
+        ExecuteMsg::AddRewardToken {
+            reward_token,
+            distribution_schedule,
+        } => add_reward_token(deps, env, info, reward_token, distribution_schedule),
+        ExecuteMsg::SetPaused { paused } => set_paused(deps, info, paused),
     }
 }
 
+fn assert_not_paused(pause_info: &PauseInfo) -> StdResult<()> {
+    if pause_info.paused {
+        return Err(StdError::generic_err("contract is paused"));
+    }
+
+    Ok(())
+}
+
 pub fn receive_cw20(
     deps: DepsMut,
     env: Env,
@@ -72,22 +130,86 @@ pub fn receive_cw20(
     cw20_msg: Cw20ReceiveMsg,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
+    let token_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let deprecated = read_deprecated_staking_tokens(deps.storage)?.contains(&token_raw);
 
     match from_binary(&cw20_msg.msg) {
         Ok(Cw20HookMsg::Bond {}) => {
             // only staking token contract can execute this message
-            if config.staking_token != deps.api.addr_canonicalize(info.sender.as_str())? {
+            if config.staking_token != token_raw {
+                if deprecated {
+                    return Err(StdError::generic_err(format!(
+                        "staking token is deprecated; bond {} instead",
+                        deps.api.addr_humanize(&config.staking_token)?
+                    )));
+                }
+
                 return Err(StdError::generic_err("unauthorized"));
             }
 
             let cw20_sender = deps.api.addr_validate(&cw20_msg.sender)?;
             bond(deps, env, cw20_sender, cw20_msg.amount)
         }
+        Ok(Cw20HookMsg::BondFor { recipient }) => {
+            // only staking token contract can execute this message
+            if config.staking_token != token_raw {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+
+            let cw20_sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            if let Some(allowlist) = read_bond_for_allowlist(deps.storage)? {
+                if !allowlist.contains(&deps.api.addr_canonicalize(cw20_sender.as_str())?) {
+                    return Err(StdError::generic_err("depositor cannot bond for others"));
+                }
+            }
+
+            let recipient = deps.api.addr_validate(&recipient)?;
+            Ok(bond(deps, env, recipient, cw20_msg.amount)?
+                .add_attribute("depositor", cw20_sender.as_str()))
+        }
+        Ok(Cw20HookMsg::MigrateBond {}) => {
+            // only deprecated staking token contracts can execute this message
+            if !deprecated {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+
+            migrate_bond(deps, &config, info.sender, cw20_msg)
+        }
         Err(_) => Err(StdError::generic_err("data should be given")),
     }
 }
 
+/// Deprecated staking tokens cannot be bonded anymore,
+/// sends them back to their sender
+pub fn migrate_bond(
+    deps: DepsMut,
+    config: &Config,
+    token: Addr,
+    cw20_msg: Cw20ReceiveMsg,
+) -> StdResult<Response> {
+    let active_token = deps.api.addr_humanize(&config.staking_token)?;
+
+    Ok(Response::new()
+        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: cw20_msg.sender.clone(),
+                amount: cw20_msg.amount,
+            })?,
+            funds: vec![],
+        })])
+        .add_attributes(vec![
+            ("action", "migrate_bond"),
+            ("owner", cw20_msg.sender.as_str()),
+            ("amount", cw20_msg.amount.to_string().as_str()),
+            ("deprecated_staking_token", token.as_str()),
+            ("active_staking_token", active_token.as_str()),
+        ]))
+}
+
 pub fn bond(deps: DepsMut, env: Env, sender_addr: Addr, amount: Uint128) -> StdResult<Response> {
+    assert_not_paused(&read_pause_info(deps.storage)?)?;
+
     let sender_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(sender_addr.as_str())?;
 
     let config: Config = read_config(deps.storage)?;
@@ -97,22 +219,44 @@ pub fn bond(deps: DepsMut, env: Env, sender_addr: Addr, amount: Uint128) -> StdR
     // Compute global reward & staker reward
     compute_reward(&config, &mut state, env.block.time.seconds());
     compute_staker_reward(&state, &mut staker_info)?;
+    settle_extra_rewards(
+        deps.storage,
+        &sender_addr_raw,
+        state.total_bond_amount,
+        staker_info.bond_amount,
+        env.block.time.seconds(),
+    )?;
 
     // Increase bond_amount
     increase_bond_amount(&mut state, &mut staker_info, amount);
 
     // Store updated state with staker's staker_info
     store_staker_info(deps.storage, &sender_addr_raw, &staker_info)?;
+    checkpoint_reward_index(deps.storage, &state)?;
     store_state(deps.storage, &state)?;
 
     Ok(Response::new().add_attributes(vec![
         ("action", "bond"),
         ("owner", sender_addr.as_str()),
         ("amount", amount.to_string().as_str()),
+        (
+            "pending_reward",
+            staker_info.pending_reward.to_string().as_str(),
+        ),
+        ("bond_amount", staker_info.bond_amount.to_string().as_str()),
+        (
+            "global_reward_index",
+            state.global_reward_index.to_string().as_str(),
+        ),
     ]))
 }
 
 pub fn unbond(deps: DepsMut, env: Env, info: MessageInfo, amount: Uint128) -> StdResult<Response> {
+    let pause_info: PauseInfo = read_pause_info(deps.storage)?;
+    if !pause_info.allow_unbond_when_paused {
+        assert_not_paused(&pause_info)?;
+    }
+
     let config: Config = read_config(deps.storage)?;
     let sender_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
 
@@ -126,6 +270,13 @@ pub fn unbond(deps: DepsMut, env: Env, info: MessageInfo, amount: Uint128) -> St
     // Compute global reward & staker reward
     compute_reward(&config, &mut state, env.block.time.seconds());
     compute_staker_reward(&state, &mut staker_info)?;
+    settle_extra_rewards(
+        deps.storage,
+        &sender_addr_raw,
+        state.total_bond_amount,
+        staker_info.bond_amount,
+        env.block.time.seconds(),
+    )?;
 
     // Decrease bond_amount
     decrease_bond_amount(&mut state, &mut staker_info, amount)?;
@@ -139,6 +290,7 @@ pub fn unbond(deps: DepsMut, env: Env, info: MessageInfo, amount: Uint128) -> St
     }
 
     // Store updated state
+    checkpoint_reward_index(deps.storage, &state)?;
     store_state(deps.storage, &state)?;
 
     Ok(Response::new()
@@ -154,11 +306,27 @@ pub fn unbond(deps: DepsMut, env: Env, info: MessageInfo, amount: Uint128) -> St
             ("action", "unbond"),
             ("owner", info.sender.as_str()),
             ("amount", amount.to_string().as_str()),
+            (
+                "pending_reward",
+                staker_info.pending_reward.to_string().as_str(),
+            ),
+            ("bond_amount", staker_info.bond_amount.to_string().as_str()),
+            (
+                "global_reward_index",
+                state.global_reward_index.to_string().as_str(),
+            ),
         ]))
 }
 
 // withdraw rewards to executor
-pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+pub fn withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    reward_token: Option<String>,
+) -> StdResult<Response> {
+    assert_not_paused(&read_pause_info(deps.storage)?)?;
+
     let sender_addr_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
 
     let config: Config = read_config(deps.storage)?;
@@ -168,46 +336,112 @@ pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Respons
     // Compute global reward & staker reward
     compute_reward(&config, &mut state, env.block.time.seconds());
     compute_staker_reward(&state, &mut staker_info)?;
+    settle_extra_rewards(
+        deps.storage,
+        &sender_addr_raw,
+        state.total_bond_amount,
+        staker_info.bond_amount,
+        env.block.time.seconds(),
+    )?;
+
+    // claim every reward token when none is given
+    let reward_token_raw = match reward_token {
+        Some(reward_token) => {
+            let reward_token_raw = deps.api.addr_canonicalize(&reward_token)?;
+            if reward_token_raw != config.anchor_token
+                && read_reward_token(deps.storage, &reward_token_raw)?.is_none()
+            {
+                return Err(StdError::generic_err("reward token is not registered"));
+            }
+
+            Some(reward_token_raw)
+        }
+        None => None,
+    };
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attributes = vec![
+        attr("action", "withdraw"),
+        attr("owner", info.sender.as_str()),
+    ];
+    // claiming every reward token skips the anchor token when nothing is pending
+    if reward_token_raw == Some(config.anchor_token.clone())
+        || (reward_token_raw.is_none() && !staker_info.pending_reward.is_zero())
+    {
+        let amount = staker_info.pending_reward;
+        staker_info.pending_reward = Uint128::zero();
+
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }));
+        attributes.push(attr("amount", amount.to_string()));
+    }
+
+    for (token, _) in read_reward_tokens(deps.storage)? {
+        if reward_token_raw.is_some() && reward_token_raw != Some(token.clone()) {
+            continue;
+        }
+
+        let mut staker_reward = read_staker_reward(deps.storage, &token, &sender_addr_raw)?;
+        let amount = staker_reward.pending_reward;
+        if amount.is_zero() {
+            continue;
+        }
+
+        staker_reward.pending_reward = Uint128::zero();
+        store_staker_reward(deps.storage, &token, &sender_addr_raw, &staker_reward)?;
 
-    let amount = staker_info.pending_reward;
-    staker_info.pending_reward = Uint128::zero();
+        let token_addr = deps.api.addr_humanize(&token)?;
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }));
+        attributes.push(attr("reward_token", token_addr.as_str()));
+        attributes.push(attr("reward_amount", amount.to_string()));
+    }
 
     // Store or remove updated rewards info
     // depends on the left pending reward and bond amount
-    if staker_info.bond_amount.is_zero() {
+    if staker_info.pending_reward.is_zero() && staker_info.bond_amount.is_zero() {
         remove_staker_info(deps.storage, &sender_addr_raw);
     } else {
         store_staker_info(deps.storage, &sender_addr_raw, &staker_info)?;
     }
 
     // Store updated state
+    checkpoint_reward_index(deps.storage, &state)?;
     store_state(deps.storage, &state)?;
 
     Ok(Response::new()
-        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount,
-            })?,
-            funds: vec![],
-        })])
-        .add_attributes(vec![
-            ("action", "withdraw"),
-            ("owner", info.sender.as_str()),
-            ("amount", amount.to_string().as_str()),
-        ]))
+        .add_messages(messages)
+        .add_attributes(attributes))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     distribution_schedule: Vec<(u64, u64, Uint128)>,
+    allow_retroactive: Option<bool>,
+    slash_destination: Option<String>,
+    pauser: Option<String>,
+    allow_unbond_when_paused: Option<bool>,
+    bond_for_allowlist: Option<Vec<String>>,
+    reward_index_checkpoint_interval: Option<u64>,
 ) -> StdResult<Response> {
     // get gov address by querying anc token minter
     let config: Config = read_config(deps.storage)?;
-    let state: State = read_state(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
 
     let sender_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
     let anc_token: Addr = deps.api.addr_humanize(&config.anchor_token)?;
@@ -218,16 +452,260 @@ pub fn update_config(
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    assert_new_schedules(&config, &state, distribution_schedule.clone())?;
+    if let Some(interval) = reward_index_checkpoint_interval {
+        if interval > MAX_REWARD_INDEX_CHECKPOINT_INTERVAL {
+            return Err(StdError::generic_err(format!(
+                "reward index checkpoint interval must be at most {}",
+                MAX_REWARD_INDEX_CHECKPOINT_INTERVAL
+            )));
+        }
+    }
+
+    // settle the rewards accrued under the old schedule before swapping it,
+    // sets last_distributed_seconds to env.block.time.seconds
+    let settled_amount = compute_reward(&config, &mut state, env.block.time.seconds());
+
+    // the new schedule can only replace already started slots when explicitly allowed
+    if !allow_retroactive.unwrap_or(false) {
+        assert_new_schedules(&config, &state, distribution_schedule.clone())?;
+    }
 
     let new_config = Config {
         anchor_token: config.anchor_token,
         staking_token: config.staking_token,
         distribution_schedule,
+        slash_destination: match slash_destination {
+            Some(slash_destination) => Some(deps.api.addr_canonicalize(&slash_destination)?),
+            None => config.slash_destination,
+        },
     };
     store_config(deps.storage, &new_config)?;
+    checkpoint_reward_index(deps.storage, &state)?;
+    store_state(deps.storage, &state)?;
+
+    let mut pause_info: PauseInfo = read_pause_info(deps.storage)?;
+    if let Some(pauser) = pauser {
+        pause_info.pauser = Some(deps.api.addr_canonicalize(&pauser)?);
+    }
+    if let Some(allow_unbond_when_paused) = allow_unbond_when_paused {
+        pause_info.allow_unbond_when_paused = allow_unbond_when_paused;
+    }
+    store_pause_info(deps.storage, &pause_info)?;
+
+    if let Some(bond_for_allowlist) = bond_for_allowlist {
+        let depositors = bond_for_allowlist
+            .iter()
+            .map(|depositor| deps.api.addr_canonicalize(depositor))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+        store_bond_for_allowlist(deps.storage, &depositors)?;
+    }
+
+    if let Some(interval) = reward_index_checkpoint_interval {
+        store_reward_index_checkpoint_interval(deps.storage, interval)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_config"),
+        ("settled_amount", &settled_amount.to_string()),
+    ]))
+}
+
+pub fn add_staking_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    active: bool,
+) -> StdResult<Response> {
+    let sender_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut config: Config = read_config(deps.storage)?;
+    let anc_token: Addr = deps.api.addr_humanize(&config.anchor_token)?;
+
+    // get gov address by querying anc token minter
+    let gov_addr_raw: CanonicalAddr = deps
+        .api
+        .addr_canonicalize(&query_anc_minter(&deps.querier, anc_token)?)?;
+    if sender_addr_raw != gov_addr_raw {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let token_raw: CanonicalAddr = deps.api.addr_canonicalize(&address)?;
+    let mut deprecated_tokens: Vec<CanonicalAddr> = read_deprecated_staking_tokens(deps.storage)?;
+    if config.staking_token == token_raw || (!active && deprecated_tokens.contains(&token_raw)) {
+        return Err(StdError::generic_err("staking token is already registered"));
+    }
+
+    if active {
+        // bonds are paid out in the active token, so it only changes once all are unbonded
+        if !read_state(deps.storage)?.total_bond_amount.is_zero() {
+            return Err(StdError::generic_err(
+                "cannot switch the active staking token while tokens are bonded",
+            ));
+        }
+
+        // the current active token becomes deprecated
+        deprecated_tokens.retain(|token| *token != token_raw);
+        deprecated_tokens.push(config.staking_token);
+        config.staking_token = token_raw;
+        store_config(deps.storage, &config)?;
+    } else {
+        deprecated_tokens.push(token_raw);
+    }
+
+    store_deprecated_staking_tokens(deps.storage, &deprecated_tokens)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "add_staking_token"),
+        ("staking_token", address.as_str()),
+        ("active", &active.to_string()),
+    ]))
+}
+
+/// Slash
+/// Owner can reduce the bond amount of `staker` by `amount`,
+/// capped at the bond amount of the staker
+pub fn slash(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staker: String,
+    amount: Uint128,
+) -> StdResult<Response> {
+    let sender_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let config: Config = read_config(deps.storage)?;
+    let anc_token: Addr = deps.api.addr_humanize(&config.anchor_token)?;
+
+    // get gov address by querying anc token minter
+    let gov_addr_raw: CanonicalAddr = deps
+        .api
+        .addr_canonicalize(&query_anc_minter(&deps.querier, anc_token)?)?;
+    if sender_addr_raw != gov_addr_raw {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(&staker)?;
+    let mut state: State = read_state(deps.storage)?;
+    let mut staker_info: StakerInfo = read_staker_info(deps.storage, &staker_addr_raw)?;
 
-    Ok(Response::new().add_attributes(vec![("action", "update_config")]))
+    // settle the rewards accrued until the slash
+    compute_reward(&config, &mut state, env.block.time.seconds());
+    compute_staker_reward(&state, &mut staker_info)?;
+    settle_extra_rewards(
+        deps.storage,
+        &staker_addr_raw,
+        state.total_bond_amount,
+        staker_info.bond_amount,
+        env.block.time.seconds(),
+    )?;
+
+    let slashed_amount = std::cmp::min(amount, staker_info.bond_amount);
+    decrease_bond_amount(&mut state, &mut staker_info, slashed_amount)?;
+
+    // Store or remove updated rewards info
+    // depends on the left pending reward and bond amount
+    if staker_info.pending_reward.is_zero() && staker_info.bond_amount.is_zero() {
+        remove_staker_info(deps.storage, &staker_addr_raw);
+    } else {
+        store_staker_info(deps.storage, &staker_addr_raw, &staker_info)?;
+    }
+
+    // Store updated state
+    checkpoint_reward_index(deps.storage, &state)?;
+    store_state(deps.storage, &state)?;
+
+    // slashed tokens stay in the contract without a slash destination
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Some(slash_destination) = config.slash_destination {
+        if !slashed_amount.is_zero() {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.staking_token)?.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: deps.api.addr_humanize(&slash_destination)?.to_string(),
+                    amount: slashed_amount,
+                })?,
+                funds: vec![],
+            }));
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "slash"),
+        ("staker", staker.as_str()),
+        ("amount", slashed_amount.to_string().as_str()),
+        ("bond_amount", staker_info.bond_amount.to_string().as_str()),
+    ]))
+}
+
+/// AddRewardToken
+/// Owner can distribute an additional reward token following its own schedule,
+/// the distributed tokens must be sent to this contract
+pub fn add_reward_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    reward_token: String,
+    distribution_schedule: Vec<(u64, u64, Uint128)>,
+) -> StdResult<Response> {
+    let sender_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let config: Config = read_config(deps.storage)?;
+    let anc_token: Addr = deps.api.addr_humanize(&config.anchor_token)?;
+
+    // get gov address by querying anc token minter
+    let gov_addr_raw: CanonicalAddr = deps
+        .api
+        .addr_canonicalize(&query_anc_minter(&deps.querier, anc_token)?)?;
+    if sender_addr_raw != gov_addr_raw {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let reward_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&reward_token)?;
+    if reward_token_raw == config.anchor_token
+        || read_reward_token(deps.storage, &reward_token_raw)?.is_some()
+    {
+        return Err(StdError::generic_err("reward token is already registered"));
+    }
+
+    store_reward_token(
+        deps.storage,
+        &reward_token_raw,
+        &RewardToken {
+            distribution_schedule,
+            last_distributed: env.block.time.seconds(),
+            global_reward_index: Decimal::zero(),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "add_reward_token"),
+        ("reward_token", reward_token.as_str()),
+    ]))
+}
+
+/// SetPaused
+/// Owner or pauser can pause bond, unbond and withdraw;
+/// unbond stays available when `allow_unbond_when_paused` is set
+pub fn set_paused(deps: DepsMut, info: MessageInfo, paused: bool) -> StdResult<Response> {
+    let sender_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let config: Config = read_config(deps.storage)?;
+    let mut pause_info: PauseInfo = read_pause_info(deps.storage)?;
+
+    if pause_info.pauser != Some(sender_addr_raw.clone()) {
+        // get gov address by querying anc token minter
+        let anc_token: Addr = deps.api.addr_humanize(&config.anchor_token)?;
+        let gov_addr_raw: CanonicalAddr = deps
+            .api
+            .addr_canonicalize(&query_anc_minter(&deps.querier, anc_token)?)?;
+        if sender_addr_raw != gov_addr_raw {
+            return Err(StdError::generic_err("unauthorized"));
+        }
+    }
+
+    pause_info.paused = paused;
+    store_pause_info(deps.storage, &pause_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "set_paused"),
+        ("paused", &paused.to_string()),
+    ]))
 }
 
 pub fn migrate_staking(
@@ -285,6 +763,7 @@ pub fn migrate_staking(
     // update config
     store_config(deps.storage, &config)?;
     // update state
+    checkpoint_reward_index(deps.storage, &state)?;
     store_state(deps.storage, &state)?;
 
     let remaining_anc = total_distribution_amount.checked_sub(distributed_amount)?;
@@ -320,31 +799,151 @@ fn decrease_bond_amount(
     Ok(())
 }
 
-// compute distributed rewards and update global reward index
-fn compute_reward(config: &Config, state: &mut State, block_time: u64) {
+// record the global reward index once the checkpoint interval has passed
+// since the latest checkpoint
+const MAX_REWARD_INDEX_CHECKPOINT_INTERVAL: u64 = 30 * 86400;
+
+fn checkpoint_reward_index(storage: &mut dyn Storage, state: &State) -> StdResult<()> {
+    let interval = read_reward_index_checkpoint_interval(storage)?;
+    if let Some(checkpoint) = read_reward_index_checkpoint_at(storage, None)? {
+        if state.last_distributed < checkpoint.time.saturating_add(interval) {
+            return Ok(());
+        }
+    }
+
+    store_reward_index_checkpoint(
+        storage,
+        &RewardIndexCheckpoint {
+            time: state.last_distributed,
+            global_reward_index: state.global_reward_index,
+            total_bond_amount: state.total_bond_amount,
+        },
+    )
+}
+
+// compute distributed rewards and update global reward index,
+// returns the amount distributed since last_distributed
+fn compute_reward(config: &Config, state: &mut State, block_time: u64) -> Uint128 {
     if state.total_bond_amount.is_zero() {
         state.last_distributed = block_time;
-        return;
+        return Uint128::zero();
     }
 
-    let mut distributed_amount: Uint128 = Uint128::zero();
-    for s in config.distribution_schedule.iter() {
-        if s.0 > block_time || s.1 < state.last_distributed {
+    let distributed_amount = compute_distributed_amount(
+        &config.distribution_schedule,
+        state.last_distributed,
+        block_time,
+    );
+
+    state.last_distributed = block_time;
+    state.global_reward_index = state.global_reward_index
+        + Decimal::from_ratio(distributed_amount, state.total_bond_amount);
+
+    distributed_amount
+}
+
+// compute the amount distributed by the schedule between last_distributed and block_time
+fn compute_distributed_amount(
+    distribution_schedule: &[(u64, u64, Uint128)],
+    last_distributed: u64,
+    block_time: u64,
+) -> Uint128 {
+    compute_distributed_amounts(distribution_schedule, last_distributed, block_time)
+        .iter()
+        .fold(Uint128::zero(), |distributed_amount, s| {
+            distributed_amount + s.2
+        })
+}
+
+// compute the (start, end, amount) distributed by each slot of the schedule
+// between last_distributed and block_time
+fn compute_distributed_amounts(
+    distribution_schedule: &[(u64, u64, Uint128)],
+    last_distributed: u64,
+    block_time: u64,
+) -> Vec<(u64, u64, Uint128)> {
+    let mut distributed_amounts: Vec<(u64, u64, Uint128)> = vec![];
+    for s in distribution_schedule.iter() {
+        if s.0 > block_time || s.1 < last_distributed {
             continue;
         }
 
-        // min(s.1, block_time) - max(s.0, last_distributed)
-        let passed_time =
-            std::cmp::min(s.1, block_time) - std::cmp::max(s.0, state.last_distributed);
+        // passed time is min(s.1, block_time) - max(s.0, last_distributed)
+        let start = std::cmp::max(s.0, last_distributed);
+        let end = std::cmp::min(s.1, block_time);
+        if end <= start {
+            continue;
+        }
 
         let time = s.1 - s.0;
         let distribution_amount_per_second: Decimal = Decimal::from_ratio(s.2, time);
-        distributed_amount += distribution_amount_per_second * Uint128::from(passed_time as u128);
+        distributed_amounts.push((
+            start,
+            end,
+            distribution_amount_per_second * Uint128::from((end - start) as u128),
+        ));
     }
 
-    state.last_distributed = block_time;
-    state.global_reward_index = state.global_reward_index
-        + Decimal::from_ratio(distributed_amount, state.total_bond_amount);
+    distributed_amounts
+}
+
+// compute distributed rewards of an additional reward token
+// and update its global reward index
+fn compute_extra_reward(
+    reward_token: &mut RewardToken,
+    total_bond_amount: Uint128,
+    block_time: u64,
+) {
+    if total_bond_amount.is_zero() {
+        reward_token.last_distributed = block_time;
+        return;
+    }
+
+    let distributed_amount = compute_distributed_amount(
+        &reward_token.distribution_schedule,
+        reward_token.last_distributed,
+        block_time,
+    );
+
+    reward_token.last_distributed = block_time;
+    reward_token.global_reward_index = reward_token.global_reward_index
+        + Decimal::from_ratio(distributed_amount, total_bond_amount);
+}
+
+// withdraw reward of an additional reward token to pending reward
+fn compute_staker_extra_reward(
+    reward_token: &RewardToken,
+    bond_amount: Uint128,
+    staker_reward: &mut StakerReward,
+) -> StdResult<()> {
+    let pending_reward = (bond_amount * reward_token.global_reward_index)
+        .checked_sub(bond_amount * staker_reward.reward_index)?;
+
+    staker_reward.reward_index = reward_token.global_reward_index;
+    staker_reward.pending_reward += pending_reward;
+    Ok(())
+}
+
+// settle the additional reward tokens of the staker,
+// must be called before the bond amounts change
+fn settle_extra_rewards(
+    storage: &mut dyn Storage,
+    staker: &CanonicalAddr,
+    total_bond_amount: Uint128,
+    bond_amount: Uint128,
+    block_time: u64,
+) -> StdResult<()> {
+    for (token, mut reward_token) in read_reward_tokens(storage)? {
+        compute_extra_reward(&mut reward_token, total_bond_amount, block_time);
+
+        let mut staker_reward = read_staker_reward(storage, &token, staker)?;
+        compute_staker_extra_reward(&reward_token, bond_amount, &mut staker_reward)?;
+
+        store_reward_token(storage, &token, &reward_token)?;
+        store_staker_reward(storage, &token, staker, &staker_reward)?;
+    }
+
+    Ok(())
 }
 
 // withdraw reward to pending reward
@@ -358,22 +957,84 @@ fn compute_staker_reward(state: &State, staker_info: &mut StakerInfo) -> StdResu
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::State { block_time } => to_binary(&query_state(deps, block_time)?),
         QueryMsg::StakerInfo { staker, block_time } => {
             to_binary(&query_staker_info(deps, staker, block_time)?)
         }
+        QueryMsg::ProjectReward { staker, until } => {
+            to_binary(&query_project_reward(deps, env, staker, until)?)
+        }
+        QueryMsg::NextScheduleChange { block_time } => to_binary(&query_next_schedule_change(
+            deps,
+            block_time.unwrap_or_else(|| env.block.time.seconds()),
+        )?),
+        QueryMsg::StakerRewardBetween {
+            staker,
+            start_time,
+            end_time,
+        } => to_binary(&query_staker_reward_between(
+            deps, staker, start_time, end_time,
+        )?),
+        QueryMsg::RewardIndexHistory { start_after, limit } => {
+            to_binary(&query_reward_index_history(deps, start_after, limit)?)
+        }
+        QueryMsg::RewardIndexAt { time } => to_binary(&query_reward_index_at(deps, time)?),
     }
 }
 
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let state = read_config(deps.storage)?;
+    let active_token = deps.api.addr_humanize(&state.staking_token)?.to_string();
+
+    let mut staking_tokens = vec![StakingTokenResponse {
+        address: active_token.clone(),
+        active: true,
+    }];
+    for token in read_deprecated_staking_tokens(deps.storage)? {
+        staking_tokens.push(StakingTokenResponse {
+            address: deps.api.addr_humanize(&token)?.to_string(),
+            active: false,
+        });
+    }
+
+    let pause_info: PauseInfo = read_pause_info(deps.storage)?;
     let resp = ConfigResponse {
         anchor_token: deps.api.addr_humanize(&state.anchor_token)?.to_string(),
-        staking_token: deps.api.addr_humanize(&state.staking_token)?.to_string(),
+        staking_token: active_token,
         distribution_schedule: state.distribution_schedule,
+        staking_tokens,
+        slash_destination: match state.slash_destination {
+            Some(slash_destination) => {
+                Some(deps.api.addr_humanize(&slash_destination)?.to_string())
+            }
+            None => None,
+        },
+        pauser: match pause_info.pauser {
+            Some(pauser) => Some(deps.api.addr_humanize(&pauser)?.to_string()),
+            None => None,
+        },
+        allow_unbond_when_paused: pause_info.allow_unbond_when_paused,
+        reward_tokens: read_reward_tokens(deps.storage)?
+            .into_iter()
+            .map(|(token, reward_token)| {
+                Ok(RewardTokenResponse {
+                    reward_token: deps.api.addr_humanize(&token)?.to_string(),
+                    distribution_schedule: reward_token.distribution_schedule,
+                })
+            })
+            .collect::<StdResult<Vec<RewardTokenResponse>>>()?,
+        bond_for_allowlist: read_bond_for_allowlist(deps.storage)?
+            .map(|depositors| {
+                depositors
+                    .iter()
+                    .map(|depositor| Ok(deps.api.addr_humanize(depositor)?.to_string()))
+                    .collect::<StdResult<Vec<String>>>()
+            })
+            .transpose()?,
+        reward_index_checkpoint_interval: read_reward_index_checkpoint_interval(deps.storage)?,
     };
 
     Ok(resp)
@@ -390,6 +1051,7 @@ pub fn query_state(deps: Deps, block_time: Option<u64>) -> StdResult<StateRespon
         last_distributed: state.last_distributed,
         total_bond_amount: state.total_bond_amount,
         global_reward_index: state.global_reward_index,
+        paused: read_pause_info(deps.storage)?.paused,
     })
 }
 
@@ -401,22 +1063,177 @@ pub fn query_staker_info(
     let staker_raw = deps.api.addr_canonicalize(&staker)?;
 
     let mut staker_info: StakerInfo = read_staker_info(deps.storage, &staker_raw)?;
+    let mut state = read_state(deps.storage)?;
     if let Some(block_time) = block_time {
         let config = read_config(deps.storage)?;
-        let mut state = read_state(deps.storage)?;
 
         compute_reward(&config, &mut state, block_time);
         compute_staker_reward(&state, &mut staker_info)?;
     }
 
+    let mut pending_rewards: Vec<PendingRewardResponse> = vec![];
+    for (token, mut reward_token) in read_reward_tokens(deps.storage)? {
+        let mut staker_reward = read_staker_reward(deps.storage, &token, &staker_raw)?;
+        if let Some(block_time) = block_time {
+            compute_extra_reward(&mut reward_token, state.total_bond_amount, block_time);
+            compute_staker_extra_reward(
+                &reward_token,
+                staker_info.bond_amount,
+                &mut staker_reward,
+            )?;
+        }
+
+        pending_rewards.push(PendingRewardResponse {
+            reward_token: deps.api.addr_humanize(&token)?.to_string(),
+            reward_index: staker_reward.reward_index,
+            pending_reward: staker_reward.pending_reward,
+        });
+    }
+
     Ok(StakerInfoResponse {
         staker,
         reward_index: staker_info.reward_index,
         bond_amount: staker_info.bond_amount,
         pending_reward: staker_info.pending_reward,
+        pending_rewards,
     })
 }
 
+/// Projects the rewards distributed to the staker until the given time,
+/// assuming nobody bonds or unbonds meanwhile
+pub fn query_project_reward(
+    deps: Deps,
+    env: Env,
+    staker: String,
+    until: u64,
+) -> StdResult<ProjectedRewardResponse> {
+    if until < env.block.time.seconds() {
+        return Err(StdError::generic_err("cannot project rewards in the past"));
+    }
+
+    let staker_raw = deps.api.addr_canonicalize(&staker)?;
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    let mut staker_info: StakerInfo = read_staker_info(deps.storage, &staker_raw)?;
+
+    let breakdown = if state.total_bond_amount.is_zero() {
+        vec![]
+    } else {
+        compute_distributed_amounts(&config.distribution_schedule, state.last_distributed, until)
+            .into_iter()
+            .map(|(start, end, amount)| {
+                (
+                    start,
+                    end,
+                    amount.multiply_ratio(staker_info.bond_amount, state.total_bond_amount),
+                )
+            })
+            .collect()
+    };
+
+    compute_reward(&config, &mut state, until);
+    compute_staker_reward(&state, &mut staker_info)?;
+
+    Ok(ProjectedRewardResponse {
+        projected_reward: staker_info.pending_reward,
+        breakdown,
+    })
+}
+
+pub fn query_next_schedule_change(
+    deps: Deps,
+    block_time: u64,
+) -> StdResult<NextScheduleChangeResponse> {
+    let config: Config = read_config(deps.storage)?;
+
+    let change_time = config
+        .distribution_schedule
+        .iter()
+        .flat_map(|s| [s.0, s.1])
+        .filter(|time| *time > block_time)
+        .min();
+
+    let rate = match change_time {
+        Some(change_time) => config
+            .distribution_schedule
+            .iter()
+            .filter(|s| s.0 <= change_time && change_time < s.1)
+            .fold(Decimal::zero(), |rate, s| {
+                rate + Decimal::from_ratio(s.2, s.1 - s.0)
+            }),
+        None => Decimal::zero(),
+    };
+
+    Ok(NextScheduleChangeResponse { change_time, rate })
+}
+
+/// Approximates the reward accrued by the staker between the given times, assuming
+/// its current bond amount and the current total bond amount held over the whole window;
+/// the result is off when either changed within the window
+pub fn query_staker_reward_between(
+    deps: Deps,
+    staker: String,
+    start_time: u64,
+    end_time: u64,
+) -> StdResult<StakerRewardBetweenResponse> {
+    if start_time > end_time {
+        return Err(StdError::generic_err("start_time is after end_time"));
+    }
+
+    let staker_raw = deps.api.addr_canonicalize(&staker)?;
+    let config: Config = read_config(deps.storage)?;
+    let state: State = read_state(deps.storage)?;
+    let staker_info: StakerInfo = read_staker_info(deps.storage, &staker_raw)?;
+
+    // replay the distribution of the window from the staker's reward index
+    let mut window_state = State {
+        last_distributed: start_time,
+        global_reward_index: staker_info.reward_index,
+        ..state
+    };
+    let mut window_staker_info = StakerInfo {
+        pending_reward: Uint128::zero(),
+        ..staker_info
+    };
+    compute_reward(&config, &mut window_state, end_time);
+    compute_staker_reward(&window_state, &mut window_staker_info)?;
+
+    Ok(StakerRewardBetweenResponse {
+        staker,
+        reward: window_staker_info.pending_reward,
+    })
+}
+
+pub fn query_reward_index_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<RewardIndexHistoryResponse> {
+    let checkpoints = read_reward_index_checkpoints(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(reward_index_checkpoint_response)
+        .collect();
+
+    Ok(RewardIndexHistoryResponse { checkpoints })
+}
+
+pub fn query_reward_index_at(deps: Deps, time: u64) -> StdResult<RewardIndexCheckpointResponse> {
+    match read_reward_index_checkpoint_at(deps.storage, Some(time))? {
+        Some(checkpoint) => Ok(reward_index_checkpoint_response(checkpoint)),
+        None => Err(StdError::generic_err("no checkpoint at or before the time")),
+    }
+}
+
+fn reward_index_checkpoint_response(
+    checkpoint: RewardIndexCheckpoint,
+) -> RewardIndexCheckpointResponse {
+    RewardIndexCheckpointResponse {
+        time: checkpoint.time,
+        global_reward_index: checkpoint.global_reward_index,
+        total_bond_amount: checkpoint.total_bond_amount,
+    }
+}
+
 pub fn assert_new_schedules(
     config: &Config,
     state: &State,
@@ -1,19 +1,33 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, Decimal, StdResult, Storage, Uint128};
+use cosmwasm_std::{CanonicalAddr, Decimal, Order, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_STATE: &[u8] = b"state";
+static KEY_DEPRECATED_STAKING_TOKENS: &[u8] = b"deprecated_staking_tokens";
+static KEY_PAUSE_INFO: &[u8] = b"pause_info";
+static KEY_BOND_FOR_ALLOWLIST: &[u8] = b"bond_for_allowlist";
+static KEY_REWARD_INDEX_CHECKPOINT_INTERVAL: &[u8] = b"reward_index_checkpoint_interval";
 
 static PREFIX_REWARD: &[u8] = b"reward";
+static PREFIX_REWARD_TOKEN: &[u8] = b"reward_token";
+static PREFIX_STAKER_REWARD: &[u8] = b"staker_reward";
+static PREFIX_REWARD_INDEX_CHECKPOINT: &[u8] = b"reward_index_checkpoint";
+
+// one hour of chain time
+const DEFAULT_REWARD_INDEX_CHECKPOINT_INTERVAL: u64 = 3600;
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub anchor_token: CanonicalAddr,
     pub staking_token: CanonicalAddr,
     pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    pub slash_destination: Option<CanonicalAddr>,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -24,6 +38,49 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
 
+/// Staking tokens replaced by the active `config.staking_token`
+pub fn store_deprecated_staking_tokens(
+    storage: &mut dyn Storage,
+    staking_tokens: &[CanonicalAddr],
+) -> StdResult<()> {
+    singleton(storage, KEY_DEPRECATED_STAKING_TOKENS).save(&staking_tokens.to_vec())
+}
+
+pub fn read_deprecated_staking_tokens(storage: &dyn Storage) -> StdResult<Vec<CanonicalAddr>> {
+    Ok(singleton_read(storage, KEY_DEPRECATED_STAKING_TOKENS)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+/// Depositors allowed to bond for another address, unrestricted when not set
+pub fn store_bond_for_allowlist(
+    storage: &mut dyn Storage,
+    depositors: &[CanonicalAddr],
+) -> StdResult<()> {
+    singleton(storage, KEY_BOND_FOR_ALLOWLIST).save(&depositors.to_vec())
+}
+
+pub fn read_bond_for_allowlist(storage: &dyn Storage) -> StdResult<Option<Vec<CanonicalAddr>>> {
+    singleton_read(storage, KEY_BOND_FOR_ALLOWLIST).may_load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PauseInfo {
+    pub paused: bool,
+    pub pauser: Option<CanonicalAddr>,
+    pub allow_unbond_when_paused: bool,
+}
+
+pub fn store_pause_info(storage: &mut dyn Storage, pause_info: &PauseInfo) -> StdResult<()> {
+    singleton(storage, KEY_PAUSE_INFO).save(pause_info)
+}
+
+pub fn read_pause_info(storage: &dyn Storage) -> StdResult<PauseInfo> {
+    Ok(singleton_read(storage, KEY_PAUSE_INFO)
+        .may_load()?
+        .unwrap_or_default())
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub last_distributed: u64,
@@ -72,3 +129,128 @@ pub fn read_staker_info(storage: &dyn Storage, owner: &CanonicalAddr) -> StdResu
         }),
     }
 }
+
+/// Distribution of an additional reward token, accounted
+/// the same way as the ANC rewards of the `State`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardToken {
+    pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    pub last_distributed: u64,
+    pub global_reward_index: Decimal,
+}
+
+pub fn store_reward_token(
+    storage: &mut dyn Storage,
+    reward_token: &CanonicalAddr,
+    info: &RewardToken,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_REWARD_TOKEN).save(reward_token.as_slice(), info)
+}
+
+pub fn read_reward_token(
+    storage: &dyn Storage,
+    reward_token: &CanonicalAddr,
+) -> StdResult<Option<RewardToken>> {
+    ReadonlyBucket::new(storage, PREFIX_REWARD_TOKEN).may_load(reward_token.as_slice())
+}
+
+pub fn read_reward_tokens(storage: &dyn Storage) -> StdResult<Vec<(CanonicalAddr, RewardToken)>> {
+    ReadonlyBucket::new(storage, PREFIX_REWARD_TOKEN)
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct StakerReward {
+    pub reward_index: Decimal,
+    pub pending_reward: Uint128,
+}
+
+pub fn store_staker_reward(
+    storage: &mut dyn Storage,
+    reward_token: &CanonicalAddr,
+    owner: &CanonicalAddr,
+    staker_reward: &StakerReward,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_STAKER_REWARD, reward_token.as_slice()])
+        .save(owner.as_slice(), staker_reward)
+}
+
+pub fn read_staker_reward(
+    storage: &dyn Storage,
+    reward_token: &CanonicalAddr,
+    owner: &CanonicalAddr,
+) -> StdResult<StakerReward> {
+    Ok(
+        ReadonlyBucket::multilevel(storage, &[PREFIX_STAKER_REWARD, reward_token.as_slice()])
+            .may_load(owner.as_slice())?
+            .unwrap_or_default(),
+    )
+}
+
+pub fn store_reward_index_checkpoint_interval(
+    storage: &mut dyn Storage,
+    interval: u64,
+) -> StdResult<()> {
+    singleton(storage, KEY_REWARD_INDEX_CHECKPOINT_INTERVAL).save(&interval)
+}
+
+pub fn read_reward_index_checkpoint_interval(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(
+        singleton_read(storage, KEY_REWARD_INDEX_CHECKPOINT_INTERVAL)
+            .may_load()?
+            .unwrap_or(DEFAULT_REWARD_INDEX_CHECKPOINT_INTERVAL),
+    )
+}
+
+/// Global reward index of the `State` at a past time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardIndexCheckpoint {
+    pub time: u64,
+    pub global_reward_index: Decimal,
+    pub total_bond_amount: Uint128,
+}
+
+pub fn store_reward_index_checkpoint(
+    storage: &mut dyn Storage,
+    checkpoint: &RewardIndexCheckpoint,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_REWARD_INDEX_CHECKPOINT)
+        .save(&checkpoint.time.to_be_bytes(), checkpoint)
+}
+
+/// Returns the latest checkpoint at or before the given time,
+/// the latest checkpoint overall when omitted
+pub fn read_reward_index_checkpoint_at(
+    storage: &dyn Storage,
+    time: Option<u64>,
+) -> StdResult<Option<RewardIndexCheckpoint>> {
+    let end = time.map(|time| time.saturating_add(1).to_be_bytes());
+    ReadonlyBucket::new(storage, PREFIX_REWARD_INDEX_CHECKPOINT)
+        .range(None, end.as_ref().map(|end| &end[..]), Order::Descending)
+        .next()
+        .map(|item| Ok(item?.1))
+        .transpose()
+}
+
+pub fn read_reward_index_checkpoints(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<RewardIndexCheckpoint>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|time| time.saturating_add(1).to_be_bytes());
+    ReadonlyBucket::new(storage, PREFIX_REWARD_INDEX_CHECKPOINT)
+        .range(
+            start.as_ref().map(|start| &start[..]),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
@@ -1,13 +1,17 @@
 use crate::contract::{execute, instantiate, query};
 use crate::mock_querier::mock_dependencies;
+use crate::state::store_reward_index_checkpoint_interval;
 use anchor_token::staking::ExecuteMsg::UpdateConfig;
 use anchor_token::staking::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, StakerInfoResponse,
-    StateResponse,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, NextScheduleChangeResponse,
+    PendingRewardResponse, ProjectedRewardResponse, QueryMsg, RewardIndexCheckpointResponse,
+    RewardIndexHistoryResponse, RewardTokenResponse, StakerInfoResponse,
+    StakerRewardBetweenResponse, StakingTokenResponse, StateResponse,
 };
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, CosmosMsg, Decimal, StdError, SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, CosmosMsg, Decimal, Response, StdError, StdResult, SubMsg,
+    Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
@@ -18,6 +22,8 @@ fn proper_initialization() {
     let msg = InstantiateMsg {
         anchor_token: "reward0000".to_string(),
         staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
         distribution_schedule: vec![(100, 200, Uint128::from(1000000u128))],
     };
 
@@ -35,6 +41,16 @@ fn proper_initialization() {
             anchor_token: "reward0000".to_string(),
             staking_token: "staking0000".to_string(),
             distribution_schedule: vec![(100, 200, Uint128::from(1000000u128))],
+            staking_tokens: vec![StakingTokenResponse {
+                address: "staking0000".to_string(),
+                active: true,
+            }],
+            slash_destination: None,
+            pauser: None,
+            allow_unbond_when_paused: false,
+            reward_tokens: vec![],
+            bond_for_allowlist: None,
+            reward_index_checkpoint_interval: 3600,
         }
     );
 
@@ -51,6 +67,7 @@ fn proper_initialization() {
             last_distributed: mock_env().block.time.seconds(),
             total_bond_amount: Uint128::zero(),
             global_reward_index: Decimal::zero(),
+            paused: false,
         }
     );
 }
@@ -62,6 +79,8 @@ fn test_bond_tokens() {
     let msg = InstantiateMsg {
         anchor_token: "reward0000".to_string(),
         staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
         distribution_schedule: vec![
             (
                 mock_env().block.time.seconds(),
@@ -87,7 +106,18 @@ fn test_bond_tokens() {
 
     let info = mock_info("staking0000", &[]);
     let mut env = mock_env();
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "bond"),
+            attr("owner", "addr0000"),
+            attr("amount", "100"),
+            attr("pending_reward", "0"),
+            attr("bond_amount", "100"),
+            attr("global_reward_index", "0"),
+        ]
+    );
 
     assert_eq!(
         from_binary::<StakerInfoResponse>(
@@ -106,6 +136,7 @@ fn test_bond_tokens() {
             staker: "addr0000".to_string(),
             reward_index: Decimal::zero(),
             pending_reward: Uint128::zero(),
+            pending_rewards: vec![],
             bond_amount: Uint128::from(100u128),
         }
     );
@@ -124,6 +155,7 @@ fn test_bond_tokens() {
             total_bond_amount: Uint128::from(100u128),
             global_reward_index: Decimal::zero(),
             last_distributed: mock_env().block.time.seconds(),
+            paused: false,
         }
     );
 
@@ -135,7 +167,18 @@ fn test_bond_tokens() {
     });
     env.block.time = env.block.time.plus_seconds(10);
 
-    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "bond"),
+            attr("owner", "addr0000"),
+            attr("amount", "100"),
+            attr("pending_reward", "100000"),
+            attr("bond_amount", "200"),
+            attr("global_reward_index", "1000"),
+        ]
+    );
 
     assert_eq!(
         from_binary::<StakerInfoResponse>(
@@ -154,6 +197,7 @@ fn test_bond_tokens() {
             staker: "addr0000".to_string(),
             reward_index: Decimal::from_ratio(1000u128, 1u128),
             pending_reward: Uint128::from(100000u128),
+            pending_rewards: vec![],
             bond_amount: Uint128::from(200u128),
         }
     );
@@ -172,6 +216,7 @@ fn test_bond_tokens() {
             total_bond_amount: Uint128::from(200u128),
             global_reward_index: Decimal::from_ratio(1000u128, 1u128),
             last_distributed: mock_env().block.time.seconds() + 10,
+            paused: false,
         }
     );
 
@@ -197,6 +242,8 @@ fn test_unbond() {
     let msg = InstantiateMsg {
         anchor_token: "reward0000".to_string(),
         staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
         distribution_schedule: vec![
             (12345, 12345 + 100, Uint128::from(1000000u128)),
             (12345 + 100, 12345 + 200, Uint128::from(10000000u128)),
@@ -248,6 +295,138 @@ fn test_unbond() {
             funds: vec![],
         }))]
     );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unbond"),
+            attr("owner", "addr0000"),
+            attr("amount", "100"),
+            attr("pending_reward", "0"),
+            attr("bond_amount", "0"),
+            attr("global_reward_index", "0"),
+        ]
+    );
+}
+
+#[test]
+fn test_bond_for() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        anchor_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
+        distribution_schedule: vec![(12345, 12345 + 100, Uint128::from(1000000u128))],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // zapper0000 bonds 100 tokens for addr0000
+    let bond_for_msg = |depositor: &str| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: depositor.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::BondFor {
+                recipient: "addr0000".to_string(),
+            })
+            .unwrap(),
+        })
+    };
+    let info = mock_info("staking0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, bond_for_msg("zapper0000")).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "bond"),
+            attr("owner", "addr0000"),
+            attr("amount", "100"),
+            attr("pending_reward", "0"),
+            attr("bond_amount", "100"),
+            attr("global_reward_index", "0"),
+            attr("depositor", "zapper0000"),
+        ]
+    );
+
+    // the depositor has nothing to unbond
+    let msg = ExecuteMsg::Unbond {
+        amount: Uint128::from(100u128),
+    };
+    let info = mock_info("zapper0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    match res {
+        StdError::GenericErr { msg, .. } => {
+            assert_eq!(msg, "Cannot unbond more than bond amount");
+        }
+        _ => panic!("Must return generic error"),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "staking0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // gov restricts bond for to zapper0001
+    deps.querier.with_anc_minter("gov0000".to_string());
+    let msg = ExecuteMsg::UpdateConfig {
+        distribution_schedule: vec![(12345, 12345 + 100, Uint128::from(1000000u128))],
+        allow_retroactive: Some(true),
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: None,
+        bond_for_allowlist: Some(vec!["zapper0001".to_string()]),
+        reward_index_checkpoint_interval: None,
+    };
+    let info = mock_info("gov0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config.bond_for_allowlist,
+        Some(vec!["zapper0001".to_string()])
+    );
+
+    let info = mock_info("staking0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        bond_for_msg("zapper0000"),
+    )
+    .unwrap_err();
+    match res {
+        StdError::GenericErr { msg, .. } => {
+            assert_eq!(msg, "depositor cannot bond for others");
+        }
+        _ => panic!("Must return generic error"),
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info, bond_for_msg("zapper0001")).unwrap();
+    let staker_info: StakerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::StakerInfo {
+                staker: "addr0000".to_string(),
+                block_time: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(staker_info.bond_amount, Uint128::from(100u128));
 }
 
 #[test]
@@ -257,6 +436,8 @@ fn test_compute_reward() {
     let msg = InstantiateMsg {
         anchor_token: "reward0000".to_string(),
         staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
         distribution_schedule: vec![
             (
                 mock_env().block.time.seconds(),
@@ -313,6 +494,7 @@ fn test_compute_reward() {
             staker: "addr0000".to_string(),
             reward_index: Decimal::from_ratio(10000u128, 1u128),
             pending_reward: Uint128::from(1000000u128),
+            pending_rewards: vec![],
             bond_amount: Uint128::from(200u128),
         }
     );
@@ -344,6 +526,7 @@ fn test_compute_reward() {
             staker: "addr0000".to_string(),
             reward_index: Decimal::from_ratio(15000u64, 1u64),
             pending_reward: Uint128::from(2000000u128),
+            pending_rewards: vec![],
             bond_amount: Uint128::from(100u128),
         }
     );
@@ -366,6 +549,7 @@ fn test_compute_reward() {
             staker: "addr0000".to_string(),
             reward_index: Decimal::from_ratio(25000u64, 1u64),
             pending_reward: Uint128::from(3000000u128),
+            pending_rewards: vec![],
             bond_amount: Uint128::from(100u128),
         }
     );
@@ -378,6 +562,8 @@ fn test_withdraw() {
     let msg = InstantiateMsg {
         anchor_token: "reward0000".to_string(),
         staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
         distribution_schedule: vec![
             (
                 mock_env().block.time.seconds(),
@@ -411,7 +597,7 @@ fn test_withdraw() {
 
     let info = mock_info("addr0000", &[]);
 
-    let msg = ExecuteMsg::Withdraw {};
+    let msg = ExecuteMsg::Withdraw { reward_token: None };
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
 
     assert_eq!(
@@ -435,6 +621,8 @@ fn test_migrate_staking() {
     let msg = InstantiateMsg {
         anchor_token: "reward0000".to_string(),
         staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
         distribution_schedule: vec![
             (
                 mock_env().block.time.seconds(),
@@ -467,7 +655,7 @@ fn test_migrate_staking() {
     env.block.time = env.block.time.plus_seconds(100);
     let info = mock_info("addr0000", &[]);
 
-    let msg = ExecuteMsg::Withdraw {};
+    let msg = ExecuteMsg::Withdraw { reward_token: None };
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     assert_eq!(
@@ -545,7 +733,17 @@ fn test_migrate_staking() {
                     mock_env().block.time.seconds() + 150,
                     Uint128::from(5000000u128)
                 ), // slot was modified
-            ]
+            ],
+            staking_tokens: vec![StakingTokenResponse {
+                address: "staking0000".to_string(),
+                active: true,
+            }],
+            slash_destination: None,
+            pauser: None,
+            allow_unbond_when_paused: false,
+            reward_tokens: vec![],
+            bond_for_allowlist: None,
+            reward_index_checkpoint_interval: 3600,
         }
     );
 }
@@ -557,6 +755,8 @@ fn test_update_config() {
     let msg = InstantiateMsg {
         anchor_token: "reward0000".to_string(),
         staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
         distribution_schedule: vec![
             (
                 mock_env().block.time.seconds(),
@@ -595,6 +795,12 @@ fn test_update_config() {
             mock_env().block.time.seconds() + 400,
             Uint128::from(10000000u128),
         )],
+        allow_retroactive: None,
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: None,
+        bond_for_allowlist: None,
+        reward_index_checkpoint_interval: None,
     };
 
     deps.querier.with_anc_minter("gov0000".to_string());
@@ -622,7 +828,7 @@ fn test_update_config() {
     env.block.time = env.block.time.plus_seconds(100);
     let info = mock_info("addr0000", &[]);
 
-    let msg = ExecuteMsg::Withdraw {};
+    let msg = ExecuteMsg::Withdraw { reward_token: None };
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(
         res.messages,
@@ -665,12 +871,18 @@ fn test_update_config() {
                 Uint128::from(10000000u128),
             ),
         ],
+        allow_retroactive: None,
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: None,
+        bond_for_allowlist: None,
+        reward_index_checkpoint_interval: None,
     };
 
     deps.querier.with_anc_minter("gov0000".to_string());
 
     let info = mock_info("gov0000", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, update_config);
+    let res = execute(deps.as_mut(), env.clone(), info, update_config);
     match res {
         Err(StdError::GenericErr { msg, .. }) => {
             assert_eq!(msg, "new schedule removes already started distribution")
@@ -694,8 +906,8 @@ fn test_update_config() {
 
     let info = mock_info("addr0000", &[]);
 
-    let msg = ExecuteMsg::Withdraw {};
-    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let msg = ExecuteMsg::Withdraw { reward_token: None };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     //cannot update previous scehdule
     let update_config = UpdateConfig {
@@ -726,12 +938,18 @@ fn test_update_config() {
                 Uint128::from(10000000u128),
             ),
         ],
+        allow_retroactive: None,
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: None,
+        bond_for_allowlist: None,
+        reward_index_checkpoint_interval: None,
     };
 
     deps.querier.with_anc_minter("gov0000".to_string());
 
     let info = mock_info("gov0000", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, update_config);
+    let res = execute(deps.as_mut(), env.clone(), info, update_config);
     match res {
         Err(StdError::GenericErr { msg, .. }) => {
             assert_eq!(msg, "new schedule removes already started distribution")
@@ -768,14 +986,23 @@ fn test_update_config() {
                 Uint128::from(10000000u128),
             ),
         ],
+        allow_retroactive: None,
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: None,
+        bond_for_allowlist: None,
+        reward_index_checkpoint_interval: None,
     };
 
     deps.querier.with_anc_minter("gov0000".to_string());
 
     let info = mock_info("gov0000", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, update_config).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info, update_config).unwrap();
 
-    assert_eq!(res.attributes, vec![("action", "update_config")]);
+    assert_eq!(
+        res.attributes,
+        vec![("action", "update_config"), ("settled_amount", "0")]
+    );
 
     // query config
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
@@ -840,14 +1067,23 @@ fn test_update_config() {
                 Uint128::from(50000000u128),
             ),
         ],
+        allow_retroactive: None,
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: None,
+        bond_for_allowlist: None,
+        reward_index_checkpoint_interval: None,
     };
 
     deps.querier.with_anc_minter("gov0000".to_string());
 
     let info = mock_info("gov0000", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, update_config).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info, update_config).unwrap();
 
-    assert_eq!(res.attributes, vec![("action", "update_config")]);
+    assert_eq!(
+        res.attributes,
+        vec![("action", "update_config"), ("settled_amount", "0")]
+    );
 
     // query config
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
@@ -911,14 +1147,23 @@ fn test_update_config() {
                 Uint128::from(80000000u128),
             ),
         ],
+        allow_retroactive: None,
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: None,
+        bond_for_allowlist: None,
+        reward_index_checkpoint_interval: None,
     };
 
     deps.querier.with_anc_minter("gov0000".to_string());
 
     let info = mock_info("gov0000", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, update_config).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info, update_config).unwrap();
 
-    assert_eq!(res.attributes, vec![("action", "update_config")]);
+    assert_eq!(
+        res.attributes,
+        vec![("action", "update_config"), ("settled_amount", "0")]
+    );
 
     // query config
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
@@ -987,14 +1232,23 @@ fn test_update_config() {
                 Uint128::from(60000000u128),
             ),
         ],
+        allow_retroactive: None,
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: None,
+        bond_for_allowlist: None,
+        reward_index_checkpoint_interval: None,
     };
 
     deps.querier.with_anc_minter("gov0000".to_string());
 
     let info = mock_info("gov0000", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, update_config).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info, update_config).unwrap();
 
-    assert_eq!(res.attributes, vec![("action", "update_config")]);
+    assert_eq!(
+        res.attributes,
+        vec![("action", "update_config"), ("settled_amount", "0")]
+    );
 
     // query config
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
@@ -1035,3 +1289,1295 @@ fn test_update_config() {
         ]
     );
 }
+
+#[test]
+fn test_update_config_settles_old_schedule() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        anchor_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
+        distribution_schedule: vec![(
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds() + 100,
+            Uint128::from(1000000u128),
+        )],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier.with_anc_minter("gov0000".to_string());
+
+    // bond 100 tokens
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+    });
+    let info = mock_info("staking0000", &[]);
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // 50 seconds passed, 500,000 rewards accrued under the old schedule
+    env.block.time = env.block.time.plus_seconds(50);
+
+    // the running slot cannot be replaced without allow_retroactive
+    let new_schedule = vec![(
+        mock_env().block.time.seconds(),
+        mock_env().block.time.seconds() + 100,
+        Uint128::from(2000000u128),
+    )];
+    let update_config = UpdateConfig {
+        distribution_schedule: new_schedule.clone(),
+        allow_retroactive: None,
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: None,
+        bond_for_allowlist: None,
+        reward_index_checkpoint_interval: None,
+    };
+    let info = mock_info("gov0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), update_config);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "new schedule removes already started distribution")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let update_config = UpdateConfig {
+        distribution_schedule: new_schedule,
+        allow_retroactive: Some(true),
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: None,
+        bond_for_allowlist: None,
+        reward_index_checkpoint_interval: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, update_config).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_config"),
+            attr("settled_amount", "500000"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::State { block_time: None },
+    )
+    .unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.last_distributed, env.block.time.seconds());
+    assert_eq!(
+        state.global_reward_index,
+        Decimal::from_ratio(5000u128, 1u128)
+    );
+
+    // 50 more seconds passed, 1,000,000 rewards accrued under the new schedule
+    env.block.time = env.block.time.plus_seconds(50);
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::StakerInfo {
+            staker: "addr0000".to_string(),
+            block_time: Some(env.block.time.seconds()),
+        },
+    )
+    .unwrap();
+    let staker_info: StakerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(staker_info.pending_reward, Uint128::from(1500000u128));
+}
+
+#[test]
+fn test_add_staking_token() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        anchor_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
+        distribution_schedule: vec![(
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds() + 100,
+            Uint128::from(1000000u128),
+        )],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_anc_minter("gov0000".to_string());
+
+    let msg = ExecuteMsg::AddStakingToken {
+        address: "staking0001".to_string(),
+        active: true,
+    };
+    let info = mock_info("notgov", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("gov0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+    // cannot register the same token twice
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "staking token is already registered")
+        }
+        _ => panic!("Must return already registered error"),
+    }
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.staking_token, "staking0001".to_string());
+    assert_eq!(
+        config.staking_tokens,
+        vec![
+            StakingTokenResponse {
+                address: "staking0001".to_string(),
+                active: true,
+            },
+            StakingTokenResponse {
+                address: "staking0000".to_string(),
+                active: false,
+            },
+        ]
+    );
+
+    // bond via the old token address
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+    });
+    let info = mock_info("staking0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "staking token is deprecated; bond staking0001 instead")
+        }
+        _ => panic!("Must return deprecated token error"),
+    }
+
+    // unregistered tokens are still rejected
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+    });
+    let info = mock_info("staking0002", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // bond via the new token address
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+    });
+    let info = mock_info("staking0001", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::StakerInfo {
+            staker: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let staker_info: StakerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(staker_info.bond_amount, Uint128::from(100u128));
+
+    // migrate bond is only accepted from deprecated tokens
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::MigrateBond {}).unwrap(),
+    });
+    let info = mock_info("staking0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // old tokens are sent back to their owner
+    let info = mock_info("staking0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "staking0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_bond"),
+            attr("owner", "addr0000"),
+            attr("amount", "100"),
+            attr("deprecated_staking_token", "staking0000"),
+            attr("active_staking_token", "staking0001"),
+        ]
+    );
+}
+
+#[test]
+fn test_add_staking_token_with_bonds() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        anchor_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
+        distribution_schedule: vec![(
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds() + 100,
+            Uint128::from(1000000u128),
+        )],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_anc_minter("gov0000".to_string());
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+    });
+    let info = mock_info("staking0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the active token cannot change while bonds are backed by it
+    let msg = ExecuteMsg::AddStakingToken {
+        address: "staking0001".to_string(),
+        active: true,
+    };
+    let info = mock_info("gov0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "cannot switch the active staking token while tokens are bonded"
+        ),
+        _ => panic!("Must return bonded tokens error"),
+    }
+
+    // the bond is still paid out in the token it was bonded with
+    let unbond_msg = ExecuteMsg::Unbond {
+        amount: Uint128::from(100u128),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        unbond_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "staking0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // once everything is unbonded the active token can change
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.staking_token, "staking0001".to_string());
+}
+
+#[test]
+fn test_slash() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        anchor_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        slash_destination: Some("community0000".to_string()),
+        pauser: None,
+        distribution_schedule: vec![(
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds() + 100,
+            Uint128::from(1000000u128),
+        )],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_anc_minter("gov0000".to_string());
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.slash_destination, Some("community0000".to_string()));
+
+    // bond 200 and 300 tokens
+    for (staker, amount) in [("addr0000", 200u128), ("addr0001", 300u128)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        });
+        let info = mock_info("staking0000", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(50);
+
+    let msg = ExecuteMsg::Slash {
+        staker: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // partial slash, the rewards accrued until the slash are settled
+    let info = mock_info("gov0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "staking0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "community0000".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "slash"),
+            attr("staker", "addr0000"),
+            attr("amount", "100"),
+            attr("bond_amount", "100"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::StakerInfo {
+            staker: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let staker_info: StakerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        staker_info,
+        StakerInfoResponse {
+            staker: "addr0000".to_string(),
+            reward_index: Decimal::from_ratio(1000u128, 1u128),
+            bond_amount: Uint128::from(100u128),
+            pending_reward: Uint128::from(200000u128),
+            pending_rewards: vec![],
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::State { block_time: None },
+    )
+    .unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.total_bond_amount, Uint128::from(400u128));
+
+    // full slash at the end of the schedule, capped at the bond amount
+    env.block.time = env.block.time.plus_seconds(50);
+    let msg = ExecuteMsg::Slash {
+        staker: "addr0000".to_string(),
+        amount: Uint128::from(1000u128),
+    };
+    let info = mock_info("gov0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "slash"),
+            attr("staker", "addr0000"),
+            attr("amount", "100"),
+            attr("bond_amount", "0"),
+        ]
+    );
+
+    // the rewards of both stakers add up to the distributed amount
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::StakerInfo {
+            staker: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let staker_info: StakerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(staker_info.bond_amount, Uint128::zero());
+    assert_eq!(staker_info.pending_reward, Uint128::from(325000u128));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::StakerInfo {
+            staker: "addr0001".to_string(),
+            block_time: Some(env.block.time.seconds()),
+        },
+    )
+    .unwrap();
+    let staker_info: StakerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(staker_info.bond_amount, Uint128::from(300u128));
+    assert_eq!(staker_info.pending_reward, Uint128::from(675000u128));
+}
+
+#[test]
+fn test_pause() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        anchor_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: Some("guardian0000".to_string()),
+        distribution_schedule: vec![(
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds() + 100,
+            Uint128::from(1000000u128),
+        )],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_anc_minter("gov0000".to_string());
+
+    let bond_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+    });
+    let unbond_msg = ExecuteMsg::Unbond {
+        amount: Uint128::from(10u128),
+    };
+    let mut env = mock_env();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("staking0000", &[]),
+        bond_msg.clone(),
+    )
+    .unwrap();
+
+    // only the owner or the pauser can pause
+    let msg = ExecuteMsg::SetPaused { paused: true };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let msg = ExecuteMsg::SetPaused { paused: true };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("guardian0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "set_paused"), attr("paused", "true")]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::State { block_time: None },
+    )
+    .unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert!(state.paused);
+
+    // bond, unbond and withdraw are paused
+    let assert_paused = |res: StdResult<Response>| match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "contract is paused"),
+        _ => panic!("Must return paused error"),
+    };
+    assert_paused(execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("staking0000", &[]),
+        bond_msg.clone(),
+    ));
+    assert_paused(execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        unbond_msg.clone(),
+    ));
+    assert_paused(execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Withdraw { reward_token: None },
+    ));
+
+    // the owner allows unbonding while paused
+    let update_config = UpdateConfig {
+        distribution_schedule: vec![(
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds() + 100,
+            Uint128::from(1000000u128),
+        )],
+        allow_retroactive: None,
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: Some(true),
+        bond_for_allowlist: None,
+        reward_index_checkpoint_interval: None,
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("gov0000", &[]),
+        update_config,
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.pauser, Some("guardian0000".to_string()));
+    assert!(config.allow_unbond_when_paused);
+
+    assert_paused(execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("staking0000", &[]),
+        bond_msg.clone(),
+    ));
+    assert_paused(execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Withdraw { reward_token: None },
+    ));
+
+    // rewards keep accruing while paused
+    env.block.time = env.block.time.plus_seconds(50);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        unbond_msg,
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::StakerInfo {
+            staker: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let staker_info: StakerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(staker_info.bond_amount, Uint128::from(90u128));
+    assert_eq!(staker_info.pending_reward, Uint128::from(500000u128));
+
+    // the owner can resume as well
+    let msg = ExecuteMsg::SetPaused { paused: false };
+    execute(deps.as_mut(), env.clone(), mock_info("gov0000", &[]), msg).unwrap();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("staking0000", &[]),
+        bond_msg,
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Withdraw { reward_token: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "reward0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(500000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn test_multiple_reward_tokens() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        anchor_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
+        distribution_schedule: vec![(
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds() + 100,
+            Uint128::from(1000000u128),
+        )],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_anc_minter("gov0000".to_string());
+
+    let mut env = mock_env();
+
+    // only the owner can add reward tokens
+    let add_msg = |reward_token: &str, amount: u128| ExecuteMsg::AddRewardToken {
+        reward_token: reward_token.to_string(),
+        distribution_schedule: vec![(
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds() + 100,
+            Uint128::from(amount),
+        )],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        add_msg("extra0000", 1000u128),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("gov0000", &[]),
+        add_msg("extra0000", 1000u128),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "add_reward_token"),
+            attr("reward_token", "extra0000"),
+        ]
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("gov0000", &[]),
+        add_msg("extra0001", 3000u128),
+    )
+    .unwrap();
+
+    // the anchor token and registered tokens cannot be added again
+    for reward_token in ["reward0000", "extra0000"] {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("gov0000", &[]),
+            add_msg(reward_token, 1000u128),
+        );
+        match res {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, "reward token is already registered")
+            }
+            _ => panic!("Must return already registered error"),
+        }
+    }
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config.reward_tokens,
+        vec![
+            RewardTokenResponse {
+                reward_token: "extra0000".to_string(),
+                distribution_schedule: vec![(
+                    mock_env().block.time.seconds(),
+                    mock_env().block.time.seconds() + 100,
+                    Uint128::from(1000u128),
+                )],
+            },
+            RewardTokenResponse {
+                reward_token: "extra0001".to_string(),
+                distribution_schedule: vec![(
+                    mock_env().block.time.seconds(),
+                    mock_env().block.time.seconds() + 100,
+                    Uint128::from(3000u128),
+                )],
+            },
+        ]
+    );
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("staking0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // both reward tokens accrue at their own rate
+    env.block.time = env.block.time.plus_seconds(50);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::StakerInfo {
+            staker: "addr0000".to_string(),
+            block_time: Some(env.block.time.seconds()),
+        },
+    )
+    .unwrap();
+    let staker_info: StakerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(staker_info.pending_reward, Uint128::from(500000u128));
+    assert_eq!(
+        staker_info.pending_rewards,
+        vec![
+            PendingRewardResponse {
+                reward_token: "extra0000".to_string(),
+                reward_index: Decimal::from_ratio(5u128, 1u128),
+                pending_reward: Uint128::from(500u128),
+            },
+            PendingRewardResponse {
+                reward_token: "extra0001".to_string(),
+                reward_index: Decimal::from_ratio(15u128, 1u128),
+                pending_reward: Uint128::from(1500u128),
+            },
+        ]
+    );
+
+    // claim a single reward token
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Withdraw {
+            reward_token: Some("extra0000".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "extra0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(500u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    env.block.time = env.block.time.plus_seconds(50);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Withdraw {
+            reward_token: Some("extra0001".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "extra0001".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(3000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // unknown reward tokens cannot be claimed
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Withdraw {
+            reward_token: Some("extra0002".to_string()),
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "reward token is not registered")
+        }
+        _ => panic!("Must return not registered error"),
+    }
+
+    // claim the rest
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Withdraw { reward_token: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "reward0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(1000000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "extra0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(500u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+
+    // nothing is sent when no reward is pending
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Withdraw { reward_token: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn test_project_reward() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        anchor_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
+        distribution_schedule: vec![
+            (
+                mock_env().block.time.seconds(),
+                mock_env().block.time.seconds() + 100,
+                Uint128::from(1000000u128),
+            ),
+            (
+                mock_env().block.time.seconds() + 100,
+                mock_env().block.time.seconds() + 200,
+                Uint128::from(2000000u128),
+            ),
+        ],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // addr0000 holds a quarter of the bond
+    for (staker, amount) in [("addr0000", 100u128), ("addr0001", 300u128)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        });
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("staking0000", &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(50);
+    let until = mock_env().block.time.seconds() + 150;
+
+    // project across the slot boundary
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::ProjectReward {
+            staker: "addr0000".to_string(),
+            until,
+        },
+    )
+    .unwrap();
+    let projection: ProjectedRewardResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        projection,
+        ProjectedRewardResponse {
+            projected_reward: Uint128::from(500000u128),
+            breakdown: vec![
+                (
+                    mock_env().block.time.seconds(),
+                    mock_env().block.time.seconds() + 100,
+                    Uint128::from(250000u128),
+                ),
+                (
+                    mock_env().block.time.seconds() + 100,
+                    until,
+                    Uint128::from(250000u128),
+                ),
+            ],
+        }
+    );
+
+    // the projection matches the rewards once the time has come
+    env.block.time = env.block.time.plus_seconds(100);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::StakerInfo {
+            staker: "addr0000".to_string(),
+            block_time: Some(env.block.time.seconds()),
+        },
+    )
+    .unwrap();
+    let staker_info: StakerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(staker_info.pending_reward, projection.projected_reward);
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::ProjectReward {
+            staker: "addr0000".to_string(),
+            until: until - 1,
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "cannot project rewards in the past")
+        }
+        _ => panic!("Must return past projection error"),
+    }
+}
+
+#[test]
+fn test_next_schedule_change() {
+    let mut deps = mock_dependencies(&[]);
+    let start = mock_env().block.time.seconds();
+
+    let msg = InstantiateMsg {
+        anchor_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
+        distribution_schedule: vec![
+            (start, start + 100, Uint128::from(1000000u128)),
+            (start + 100, start + 200, Uint128::from(2000000u128)),
+        ],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let next_change = |block_time: Option<u64>| -> NextScheduleChangeResponse {
+        from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::NextScheduleChange { block_time },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    // the current block time is the start of the first window
+    assert_eq!(
+        next_change(None),
+        NextScheduleChangeResponse {
+            change_time: Some(start + 100),
+            rate: Decimal::from_ratio(2000000u128, 100u128),
+        }
+    );
+
+    assert_eq!(
+        next_change(Some(start + 100)),
+        NextScheduleChangeResponse {
+            change_time: Some(start + 200),
+            rate: Decimal::zero(),
+        }
+    );
+
+    // emissions have ended
+    assert_eq!(
+        next_change(Some(start + 200)),
+        NextScheduleChangeResponse {
+            change_time: None,
+            rate: Decimal::zero(),
+        }
+    );
+}
+
+#[test]
+fn test_staker_reward_between() {
+    let mut deps = mock_dependencies(&[]);
+    let start = mock_env().block.time.seconds();
+
+    let msg = InstantiateMsg {
+        anchor_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
+        distribution_schedule: vec![
+            (start, start + 100, Uint128::from(1000000u128)),
+            (start + 100, start + 200, Uint128::from(2000000u128)),
+        ],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // addr0000 holds a quarter of the bond
+    for (staker, amount) in [("addr0000", 100u128), ("addr0001", 300u128)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        });
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("staking0000", &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    // the window spans half of each slot, 500000 + 1000000 distributed in total
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::StakerRewardBetween {
+            staker: "addr0000".to_string(),
+            start_time: start + 50,
+            end_time: start + 150,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<StakerRewardBetweenResponse>(&res).unwrap(),
+        StakerRewardBetweenResponse {
+            staker: "addr0000".to_string(),
+            reward: Uint128::from(375000u128),
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::StakerRewardBetween {
+            staker: "addr0000".to_string(),
+            start_time: start + 150,
+            end_time: start + 50,
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "start_time is after end_time")
+        }
+        _ => panic!("Must return invalid window error"),
+    }
+}
+
+#[test]
+fn test_reward_index_history() {
+    let mut deps = mock_dependencies(&[]);
+    let start = mock_env().block.time.seconds();
+
+    let msg = InstantiateMsg {
+        anchor_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        slash_destination: None,
+        pauser: None,
+        distribution_schedule: vec![(start, start + 10000, Uint128::from(10000000u128))],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // bonds within the one hour interval of the latest checkpoint are not recorded
+    for elapsed in [0u64, 1000, 4000, 5000, 8000] {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(elapsed);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        });
+        execute(deps.as_mut(), env, mock_info("staking0000", &[]), msg).unwrap();
+    }
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RewardIndexHistory {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let history: RewardIndexHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        history.checkpoints,
+        vec![
+            RewardIndexCheckpointResponse {
+                time: start,
+                global_reward_index: Decimal::zero(),
+                total_bond_amount: Uint128::from(100u128),
+            },
+            RewardIndexCheckpointResponse {
+                time: start + 4000,
+                // 1000000 over 100 bonded, then 3000000 over 200 bonded
+                global_reward_index: Decimal::from_ratio(25000u128, 1u128),
+                total_bond_amount: Uint128::from(300u128),
+            },
+            RewardIndexCheckpointResponse {
+                time: start + 8000,
+                // 1000000 over 300 bonded, then 3000000 over 400 bonded
+                global_reward_index: Decimal::from_ratio(25000u128, 1u128)
+                    + Decimal::from_ratio(1000000u128, 300u128)
+                    + Decimal::from_ratio(7500u128, 1u128),
+                total_bond_amount: Uint128::from(500u128),
+            },
+        ]
+    );
+    assert!(history
+        .checkpoints
+        .windows(2)
+        .all(|w| w[0].global_reward_index < w[1].global_reward_index));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RewardIndexHistory {
+            start_after: Some(start),
+            limit: Some(1),
+        },
+    )
+    .unwrap();
+    let page: RewardIndexHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(page.checkpoints, vec![history.checkpoints[1].clone()]);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RewardIndexAt { time: start + 7999 },
+    )
+    .unwrap();
+    let checkpoint: RewardIndexCheckpointResponse = from_binary(&res).unwrap();
+    assert_eq!(checkpoint, history.checkpoints[1]);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RewardIndexAt { time: start - 1 },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "no checkpoint at or before the time")
+        }
+        _ => panic!("Must return missing checkpoint error"),
+    }
+
+    deps.querier.with_anc_minter("gov0000".to_string());
+    let update_config = |interval: u64| UpdateConfig {
+        distribution_schedule: vec![(start, start + 10000, Uint128::from(10000000u128))],
+        allow_retroactive: Some(true),
+        slash_destination: None,
+        pauser: None,
+        allow_unbond_when_paused: None,
+        bond_for_allowlist: None,
+        reward_index_checkpoint_interval: Some(interval),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("gov0000", &[]),
+        update_config(30 * 86400 + 1),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(
+                msg,
+                "reward index checkpoint interval must be at most 2592000"
+            )
+        }
+        _ => panic!("Must return invalid interval error"),
+    }
+
+    // an interval stored before the validation does not overflow
+    store_reward_index_checkpoint_interval(deps.as_mut().storage, u64::MAX).unwrap();
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(9000);
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("gov0000", &[]),
+        update_config(3600),
+    )
+    .unwrap();
+}
@@ -4,8 +4,8 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use anchor_token::vesting::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, VestingAccountResponse,
-    VestingAccountsResponse,
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, UnallocatedAmountResponse,
+    VestingAccountResponse, VestingAccountsResponse,
 };
 
 fn main() {
@@ -20,4 +20,5 @@ fn main() {
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(VestingAccountResponse), &out_dir);
     export_schema(&schema_for!(VestingAccountsResponse), &out_dir);
+    export_schema(&schema_for!(UnallocatedAmountResponse), &out_dir);
 }
@@ -2,17 +2,19 @@
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    to_binary, Addr, Api, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Storage, Uint128, WasmMsg,
+    to_binary, Addr, Api, Binary, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 
+use crate::querier::query_anc_balance;
 use crate::state::{
-    read_config, read_vesting_info, read_vesting_infos, store_config, store_vesting_info, Config,
+    read_all_vesting_infos, read_config, read_vesting_info, read_vesting_infos, store_config,
+    store_vesting_info, Config,
 };
 use anchor_token::common::OrderBy;
 use anchor_token::vesting::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, VestingAccount, VestingAccountResponse,
-    VestingAccountsResponse, VestingInfo,
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, UnallocatedAmountResponse,
+    VestingAccount, VestingAccountResponse, VestingAccountsResponse, VestingInfo,
 };
 use cw20::Cw20ExecuteMsg;
 
@@ -47,9 +49,10 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                     anchor_token,
                     genesis_time,
                 } => update_config(deps, owner, anchor_token, genesis_time),
-                ExecuteMsg::RegisterVestingAccounts { vesting_accounts } => {
-                    register_vesting_accounts(deps, vesting_accounts)
-                }
+                ExecuteMsg::RegisterVestingAccounts {
+                    vesting_accounts,
+                    overwrite,
+                } => register_vesting_accounts(deps, env, vesting_accounts, overwrite),
                 _ => panic!("DO NOT ENTER HERE"),
             }
         }
@@ -95,6 +98,12 @@ fn assert_vesting_schedules(vesting_schedules: &[(u64, u64, Uint128)]) -> StdRes
                 "end_time must bigger than start_time",
             ));
         }
+
+        if vesting_schedule.2.is_zero() {
+            return Err(StdError::generic_err(
+                "vesting amount must be greater than zero",
+            ));
+        }
     }
 
     Ok(())
@@ -102,24 +111,74 @@ fn assert_vesting_schedules(vesting_schedules: &[(u64, u64, Uint128)]) -> StdRes
 
 pub fn register_vesting_accounts(
     deps: DepsMut,
+    env: Env,
     vesting_accounts: Vec<VestingAccount>,
+    overwrite: Option<bool>,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
+    let overwrite = overwrite.unwrap_or(false);
+
+    let mut vesting_addresses: Vec<CanonicalAddr> = vec![];
+    let mut registered_amount = Uint128::zero();
     for vesting_account in vesting_accounts.iter() {
         assert_vesting_schedules(&vesting_account.schedules)?;
 
         let vesting_address = deps.api.addr_canonicalize(&vesting_account.address)?;
+        if vesting_addresses.contains(&vesting_address) {
+            return Err(StdError::generic_err(format!(
+                "duplicate vesting account {}",
+                vesting_account.address
+            )));
+        }
+
+        if !overwrite && read_vesting_info(deps.storage, &vesting_address).is_ok() {
+            return Err(StdError::generic_err(format!(
+                "vesting account {} already exists",
+                vesting_account.address
+            )));
+        }
+
+        registered_amount += vesting_account
+            .schedules
+            .iter()
+            .map(|s| s.2)
+            .sum::<Uint128>();
+        vesting_addresses.push(vesting_address);
+    }
+
+    // the overwritten accounts are not owed anymore
+    let outstanding_amount: Uint128 = read_all_vesting_infos(deps.storage)?
+        .iter()
+        .filter(|(address, _)| !vesting_addresses.contains(address))
+        .map(|(_, vesting_info)| compute_unclaimed_amount(vesting_info))
+        .sum();
+
+    let balance = query_anc_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.anchor_token)?,
+        env.contract.address,
+    )?;
+    if outstanding_amount + registered_amount > balance {
+        return Err(StdError::generic_err(
+            "Cannot register more than the unallocated amount",
+        ));
+    }
+
+    for (vesting_address, vesting_account) in vesting_addresses.iter().zip(vesting_accounts) {
         store_vesting_info(
             deps.storage,
-            &vesting_address,
+            vesting_address,
             &VestingInfo {
                 last_claim_time: config.genesis_time,
-                schedules: vesting_account.schedules.clone(),
+                schedules: vesting_account.schedules,
             },
         )?;
     }
 
-    Ok(Response::new().add_attributes(vec![("action", "register_vesting_accounts")]))
+    Ok(Response::new().add_attributes(vec![
+        ("action", "register_vesting_accounts"),
+        ("registered_amount", &registered_amount.to_string()),
+    ]))
 }
 
 pub fn claim(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
@@ -176,8 +235,31 @@ fn compute_claim_amount(current_time: u64, vesting_info: &VestingInfo) -> Uint12
     claimable_amount
 }
 
+// amount which is vested but not claimed yet or not vested
+fn compute_unclaimed_amount(vesting_info: &VestingInfo) -> Uint128 {
+    let mut unclaimed_amount: Uint128 = Uint128::zero();
+    for s in vesting_info.schedules.iter() {
+        if s.1 <= vesting_info.last_claim_time {
+            continue;
+        }
+
+        if s.0 >= vesting_info.last_claim_time {
+            unclaimed_amount += s.2;
+            continue;
+        }
+
+        let claimed_time = vesting_info.last_claim_time - s.0;
+        let time_period = s.1 - s.0;
+        let release_amount_per_time: Decimal = Decimal::from_ratio(s.2, time_period);
+
+        unclaimed_amount += s.2 - Uint128::from(claimed_time as u128) * release_amount_per_time;
+    }
+
+    unclaimed_amount
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
         QueryMsg::VestingAccount { address } => {
@@ -193,6 +275,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             limit,
             order_by,
         )?)?),
+        QueryMsg::UnallocatedAmount {} => Ok(to_binary(&query_unallocated_amount(deps, env)?)?),
     }
 }
 
@@ -207,6 +290,26 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     Ok(resp)
 }
 
+pub fn query_unallocated_amount(deps: Deps, env: Env) -> StdResult<UnallocatedAmountResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let balance = query_anc_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.anchor_token)?,
+        env.contract.address,
+    )?;
+
+    let outstanding_amount: Uint128 = read_all_vesting_infos(deps.storage)?
+        .iter()
+        .map(|(_, vesting_info)| compute_unclaimed_amount(vesting_info))
+        .sum();
+
+    Ok(UnallocatedAmountResponse {
+        balance,
+        outstanding_amount,
+        unallocated_amount: balance.saturating_sub(outstanding_amount),
+    })
+}
+
 pub fn query_vesting_account(deps: Deps, address: String) -> StdResult<VestingAccountResponse> {
     let info = read_vesting_info(deps.storage, &deps.api.addr_canonicalize(&address)?)?;
     let resp = VestingAccountResponse { address, info };
@@ -1,5 +1,8 @@
 pub mod contract;
+pub mod querier;
 pub mod state;
 
+#[cfg(test)]
+mod mock_querier;
 #[cfg(test)]
 mod tests;
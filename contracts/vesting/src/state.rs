@@ -36,6 +36,18 @@ pub fn store_vesting_info(
     bucket::<VestingInfo>(storage, PREFIX_KEY_VESTING_INFO).save(address.as_slice(), vesting_info)
 }
 
+pub fn read_all_vesting_infos(
+    storage: &dyn Storage,
+) -> StdResult<Vec<(CanonicalAddr, VestingInfo)>> {
+    bucket_read::<VestingInfo>(storage, PREFIX_KEY_VESTING_INFO)
+        .range(None, None, OrderBy::Asc.into())
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
 pub fn read_vesting_infos<'a>(
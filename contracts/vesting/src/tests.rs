@@ -1,11 +1,12 @@
 use crate::contract::{execute, instantiate, query};
+use crate::mock_querier::mock_dependencies;
 use anchor_token::common::OrderBy;
 use anchor_token::vesting::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, VestingAccount, VestingAccountResponse,
-    VestingAccountsResponse, VestingInfo,
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, UnallocatedAmountResponse,
+    VestingAccount, VestingAccountResponse, VestingAccountsResponse, VestingInfo,
 };
 
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     attr, from_binary, to_binary, Api, CanonicalAddr, CosmosMsg, StdError, SubMsg, Timestamp,
     Uint128, WasmMsg,
@@ -117,6 +118,9 @@ fn register_vesting_accounts() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::from(500u128))]);
+
     let acct1 = deps
         .api
         .addr_humanize(&CanonicalAddr::from(vec![
@@ -163,6 +167,7 @@ fn register_vesting_accounts() {
                 schedules: vec![(100u64, 200u64, Uint128::from(100u128))],
             },
         ],
+        overwrite: None,
     };
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
@@ -244,6 +249,166 @@ fn register_vesting_accounts() {
     );
 }
 
+#[test]
+fn register_vesting_accounts_validation() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        anchor_token: "anchor_token".to_string(),
+        genesis_time: 100u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::from(1000u128))]);
+
+    let register = |accounts: Vec<(&str, u128)>, overwrite: Option<bool>| {
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: accounts
+                .into_iter()
+                .map(|(address, amount)| VestingAccount {
+                    address: address.to_string(),
+                    schedules: vec![(100u64, 200u64, Uint128::from(amount))],
+                })
+                .collect(),
+            overwrite,
+        }
+    };
+    let info = mock_info("owner", &[]);
+
+    // one invalid entry rejects the whole batch
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        register(vec![("addr0000", 100), ("addr0001", 0)], None),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "vesting amount must be greater than zero")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::VestingAccount {
+            address: "addr0000".to_string(),
+        },
+    )
+    .is_err());
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        register(vec![("addr0000", 100), ("addr0000", 100)], None),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "duplicate vesting account addr0000")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        register(vec![("addr0000", 600), ("addr0001", 500)], None),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot register more than the unallocated amount")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        register(vec![("addr0000", 600)], None),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_vesting_accounts"),
+            attr("registered_amount", "600"),
+        ]
+    );
+    assert_eq!(
+        from_binary::<UnallocatedAmountResponse>(
+            &query(deps.as_ref(), mock_env(), QueryMsg::UnallocatedAmount {}).unwrap()
+        )
+        .unwrap(),
+        UnallocatedAmountResponse {
+            balance: Uint128::from(1000u128),
+            outstanding_amount: Uint128::from(600u128),
+            unallocated_amount: Uint128::from(400u128),
+        }
+    );
+
+    // existing accounts are only replaced with the overwrite flag
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        register(vec![("addr0001", 100), ("addr0000", 100)], None),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "vesting account addr0000 already exists")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::VestingAccount {
+            address: "addr0001".to_string(),
+        },
+    )
+    .is_err());
+
+    // the overwritten schedule is not owed anymore
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        register(vec![("addr0000", 900)], Some(true)),
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<VestingAccountResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::VestingAccount {
+                    address: "addr0000".to_string(),
+                }
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .info
+        .schedules,
+        vec![(100u64, 200u64, Uint128::from(900u128))]
+    );
+    assert_eq!(
+        from_binary::<UnallocatedAmountResponse>(
+            &query(deps.as_ref(), mock_env(), QueryMsg::UnallocatedAmount {}).unwrap()
+        )
+        .unwrap()
+        .unallocated_amount,
+        Uint128::from(100u128)
+    );
+}
+
 #[test]
 fn claim() {
     let mut deps = mock_dependencies(&[]);
@@ -257,6 +422,9 @@ fn claim() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
+    deps.querier
+        .with_token_balances(&[(MOCK_CONTRACT_ADDR, Uint128::from(300u128))]);
+
     let msg = ExecuteMsg::RegisterVestingAccounts {
         vesting_accounts: vec![VestingAccount {
             address: "addr0000".to_string(),
@@ -266,6 +434,7 @@ fn claim() {
                 (100u64, 200u64, Uint128::from(100u128)),
             ],
         }],
+        overwrite: None,
     };
     let info = mock_info("owner", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1,7 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Binary, Uint128};
+use cw20::Expiration;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -12,17 +13,43 @@ pub struct InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    /// `sunset_time` ends all claims permanently, it can be postponed but never lowered,
+    /// and never changed once it has passed
     UpdateConfig {
         owner: Option<String>,
+        sunset_time: Option<u64>,
     },
+    /// Registers the merkle root of a new stage, the contract balance
+    /// must cover `total_amount` besides the unclaimed amounts of prior stages.
+    /// Claims of the stage are rejected after `expiration`
     RegisterMerkleRoot {
         merkle_root: String,
+        total_amount: Uint128,
+        expiration: Option<Expiration>,
     },
     Claim {
         stage: u8,
         amount: Uint128,
         proof: Vec<String>,
     },
+    /// Claim the airdrop of `original_address` to `new_address`.
+    /// `signature` is the 64 bytes secp256k1 signature, made with the key of
+    /// `original_address`, over the sha256 hash of
+    /// `"{chain_id}:{airdrop_contract}:{stage}:{amount}:{new_address}"`;
+    /// `pubkey` is the compressed public key of that key
+    ClaimFor {
+        stage: u8,
+        amount: Uint128,
+        proof: Vec<String>,
+        original_address: String,
+        new_address: String,
+        pubkey: Binary,
+        signature: Binary,
+    },
+    /// Sends the unclaimed amount of an expired stage back to the owner
+    ReclaimExpired { stage: u8 },
+    /// Sends the whole remaining balance to `recipient` once after the sunset
+    SweepUnclaimed { recipient: String },
 }
 
 /// We currently take no arguments for migrations
@@ -36,6 +63,7 @@ pub enum QueryMsg {
     MerkleRoot { stage: u8 },
     LatestStage {},
     IsClaimed { stage: u8, address: String },
+    StageSolvency { stage: u8 },
 }
 
 // We define a custom struct for each query response
@@ -43,6 +71,8 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     pub owner: String,
     pub anchor_token: String,
+    pub sunset_time: Option<u64>,
+    pub swept_amount: Option<Uint128>, // not swept yet when omitted
 }
 
 // We define a custom struct for each query response
@@ -63,3 +93,13 @@ pub struct LatestStageResponse {
 pub struct IsClaimedResponse {
     pub is_claimed: bool,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StageSolvencyResponse {
+    pub stage: u8,
+    pub total_amount: Option<Uint128>, // not recorded for stages registered before
+    pub claimed_amount: Uint128,
+    pub balance: Uint128,
+    pub expiration: Option<Expiration>,
+}
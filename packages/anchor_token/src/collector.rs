@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Decimal;
+use cosmwasm_std::{Decimal, Uint128};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -10,6 +10,8 @@ pub struct InstantiateMsg {
     pub anchor_token: String,
     pub reward_factor: Decimal,
     pub max_spread: Option<Decimal>,
+    /// Astroport router used to swap native denoms without a direct ANC pair
+    pub router: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -22,22 +24,67 @@ pub enum ExecuteMsg {
     /// it should be either (true, none) or (true, "0.1")
     /// if we do not want to update it
     /// it should be (false, none)
+    /// Updating the gov_contract here is deprecated
+    /// in favor of ProposeNewOwner and AcceptOwnership
     UpdateConfig {
         reward_factor: Option<Decimal>,
         gov_contract: Option<String>,
         astroport_factory: Option<String>,
         max_spread: (bool, Option<Decimal>),
+        router: Option<String>,
     },
+    /// Gov contract operation to propose a new gov contract
+    ProposeNewOwner { owner: String },
+    /// Proposed gov contract operation to take over the ownership
+    AcceptOwnership {},
+    /// Gov contract operation to drop the pending gov contract proposal
+    DropOwnershipProposal {},
     /// Public Message
     /// Sweep all given denom balance to ANC token
-    /// and execute Distribute message
+    /// and execute Distribute message.
+    /// The denom can also be a cw20 token with a registered route
     Sweep { denom: String },
+    /// Gov contract operation to register the astroport pair
+    /// a native denom is swept through
+    RegisterNativePair { denom: String, pair: String },
+    /// Public Message
+    /// Sweep again every denom whose last swap failed
+    RetryFailedSwaps {},
+    /// Gov contract operation to register the astroport pairs
+    /// an asset without an ANC pair is swept through, at most two hops
+    RegisterRoute { asset: String, hops: Vec<PairHop> },
+    /// Gov contract operation to remove the registered route of an asset
+    RemoveRoute { asset: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairHop {
+    /// Astroport pair swapping the asset received from the previous hop
+    pub pair: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Distribution statistics per epoch (week), in ascending order
+    Stats {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Distribution statistics since genesis
+    TotalStats {},
+    /// Assets with a registered sweep pair
+    RegisteredAssets {},
+    /// Denoms whose last swap failed
+    FailedSwaps {},
+    /// Expected ANC output of sweeping the amount of denom
+    SimulateBuyback {
+        denom: String,
+        amount: Uint128,
+    },
+    /// Assets with a registered multi-hop route
+    Routes {},
 }
 
 // We define a custom struct for each query response
@@ -48,6 +95,78 @@ pub struct ConfigResponse {
     pub anchor_token: String,
     pub reward_factor: Decimal,
     pub max_spread: Option<Decimal>,
+    pub router: Option<String>,
+    pub pending_gov_contract: Option<String>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochStatsResponse {
+    pub epoch: u64,
+    pub swept_in: Uint128,   // ANC collected for distribution
+    pub anc_to_gov: Uint128, // ANC sent to gov contract
+    pub anc_burned: Uint128, // ANC burned
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    pub stats: Vec<EpochStatsResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalStatsResponse {
+    pub swept_in: Uint128,
+    pub anc_to_gov: Uint128,
+    pub anc_burned: Uint128,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RegisteredAssetResponse {
+    pub denom: String,
+    pub pair: String,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RegisteredAssetsResponse {
+    pub assets: Vec<RegisteredAssetResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FailedSwapResponse {
+    pub denom: String,
+    pub failed_attempts: u64, // consecutive failed swaps of the denom
+    pub last_error: String,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FailedSwapsResponse {
+    pub failed_swaps: Vec<FailedSwapResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateBuybackResponse {
+    pub expected_return: Uint128,
+    pub spread: Option<Uint128>, // none when swapped through the router or a route
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RouteResponse {
+    pub asset: String,
+    pub hops: Vec<PairHop>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoutesResponse {
+    pub routes: Vec<RouteResponse>,
 }
 
 /// We currently take no arguments for migrations
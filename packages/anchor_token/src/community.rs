@@ -1,6 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::common::OrderBy;
 use cosmwasm_std::Uint128;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -13,8 +14,30 @@ pub struct InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    UpdateConfig { spend_limit: Option<Uint128> },
-    Spend { recipient: String, amount: Uint128 },
+    UpdateConfig {
+        spend_limit: Option<Uint128>,
+    },
+    /// Spend sends `amount` of ANC token to `recipient`,
+    /// `poll_hint` tags the spend with the gov poll executing it
+    Spend {
+        recipient: String,
+        amount: Uint128,
+        poll_hint: Option<u64>,
+    },
+    /// CreateStream releases `amount` of ANC token to `recipient`
+    /// linearly between the `start` and `end` timestamps; the unclaimed
+    /// amount is reserved and can not be spent or streamed again
+    CreateStream {
+        recipient: String,
+        amount: Uint128,
+        start: u64,
+        end: u64,
+    },
+    /// ClaimStream sends the vested but unclaimed portion of a stream
+    /// to its recipient
+    ClaimStream {
+        stream_id: u64,
+    },
 }
 
 /// We currently take no arguments for migrations
@@ -25,6 +48,15 @@ pub struct MigrateMsg {}
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    Stream {
+        stream_id: u64,
+    },
+    Balance {},
+    SpendHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order_by: Option<OrderBy>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -34,3 +66,34 @@ pub struct ConfigResponse {
     pub anchor_token: String,
     pub spend_limit: Uint128,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StreamResponse {
+    pub id: u64,
+    pub recipient: String,
+    pub amount: Uint128,
+    pub claimed: Uint128,
+    pub start: u64,
+    pub end: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BalanceResponse {
+    pub anchor_token: String,
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendRecordResponse {
+    pub id: u64,
+    pub recipient: String,
+    pub amount: Uint128,
+    pub asset: String, // token address
+    pub block_height: u64,
+    pub poll_hint: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendHistoryResponse {
+    pub spends: Vec<SpendRecordResponse>,
+}
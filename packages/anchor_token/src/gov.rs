@@ -1,5 +1,5 @@
 use cosmwasm_std::{Binary, Decimal, Uint128};
-use cw20::Cw20ReceiveMsg;
+use cw20::{Cw20ReceiveMsg, Expiration};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -14,10 +14,51 @@ pub struct InstantiateMsg {
     pub timelock_period: u64,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    pub quorum_basis: Option<QuorumBasis>,
+    pub excluded_addresses: Option<Vec<String>>,
+    pub stale_poll_grace_period: Option<u64>,
+    pub veto_period: Option<u64>,
+    pub guardian: Option<String>,
+    pub poll_length_limits: Option<PollLengthLimits>,
+    /// Create time based polls, ending after `voting_period_seconds`,
+    /// snapshotting within `snapshot_period_seconds` of their end,
+    /// executable `timelock_period_seconds` after their end
+    /// and expirable `stale_poll_grace_period_seconds` after their end
+    pub use_time: Option<bool>,
+    pub voting_period_seconds: Option<u64>,
+    pub snapshot_period_seconds: Option<u64>,
+    pub timelock_period_seconds: Option<u64>,
+    pub stale_poll_grace_period_seconds: Option<u64>,
+    /// Maximum number of in-progress polls a staker can vote on at once, unlimited when omitted
+    pub max_participations: Option<u64>,
+    /// Contracts polls are allowed or denied to execute messages on, unrestricted when omitted
+    pub execute_target_policy: Option<ExecuteTargetPolicy>,
+    /// Minimum amount of a vote, zero when omitted
+    pub min_vote_amount: Option<Uint128>,
+    /// Polls whose share of no with veto votes exceeds `veto_threshold` are rejected
+    /// and their deposit is sent to `community_contract`, never vetoed when omitted
+    pub veto_threshold: Option<Decimal>,
+    pub community_contract: Option<String>,
+    /// Polls ending without quorum are extended once by `quorum_reduction_after` blocks
+    /// (`quorum_reduction_after_seconds` seconds for time based polls), with the quorum
+    /// scaled by `quorum_reduction_factor`. Polls are never extended when omitted
+    pub quorum_reduction_after: Option<u64>,
+    pub quorum_reduction_after_seconds: Option<u64>,
+    pub quorum_reduction_factor: Option<Decimal>,
+    /// Skips decoding poll execute msgs at creation, allowing raw opaque payloads.
+    /// Defaults to false
+    pub allow_opaque_execute_msgs: Option<bool>,
+    /// Lets voters replace their vote while the poll is in progress. Defaults to false
+    pub allow_vote_change: Option<bool>,
+    /// Yes votes a poll needs to pass besides the quorum and threshold. Defaults to zero
+    pub min_yes_votes: Option<Uint128>,
+    /// Seconds withdrawn tokens wait before they can be claimed, instant when omitted
+    pub unstake_cooldown: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
     ExecutePollMsgs {
@@ -26,6 +67,7 @@ pub enum ExecuteMsg {
     RegisterContracts {
         anchor_token: String,
     },
+    /// Updating the owner here is deprecated in favor of ProposeNewOwner and AcceptOwnership
     UpdateConfig {
         owner: Option<String>,
         quorum: Option<Decimal>,
@@ -34,15 +76,56 @@ pub enum ExecuteMsg {
         timelock_period: Option<u64>,
         proposal_deposit: Option<Uint128>,
         snapshot_period: Option<u64>,
+        quorum_basis: Option<QuorumBasis>,
+        excluded_addresses: Option<Vec<String>>,
+        stale_poll_grace_period: Option<u64>,
+        veto_period: Option<u64>,
+        guardian: Option<String>,
+        poll_length_limits: Option<PollLengthLimits>,
+        use_time: Option<bool>,
+        voting_period_seconds: Option<u64>,
+        snapshot_period_seconds: Option<u64>,
+        timelock_period_seconds: Option<u64>,
+        stale_poll_grace_period_seconds: Option<u64>,
+        max_participations: Option<u64>,
+        execute_target_policy: Option<ExecuteTargetPolicy>,
+        min_vote_amount: Option<Uint128>,
+        veto_threshold: Option<Decimal>,
+        community_contract: Option<String>,
+        quorum_reduction_after: Option<u64>,
+        quorum_reduction_after_seconds: Option<u64>,
+        quorum_reduction_factor: Option<Decimal>,
+        allow_opaque_execute_msgs: Option<bool>,
+        allow_vote_change: Option<bool>,
+        min_yes_votes: Option<Uint128>,
+        unstake_cooldown: Option<u64>,
     },
+    /// Owner operation to propose a new owner
+    ProposeNewOwner {
+        owner: String,
+    },
+    /// Proposed owner operation to take over the ownership
+    AcceptOwnership {},
+    /// Owner operation to drop the pending ownership proposal
+    DropOwnershipProposal {},
     CastVote {
         poll_id: u64,
         vote: VoteOption,
-        amount: Uint128,
+        /// Votes with the whole staked amount of the voter when omitted
+        amount: Option<Uint128>,
+    },
+    /// Replaces the sender's vote on an in progress poll, when allowed by the config
+    ChangeVote {
+        poll_id: u64,
+        new_vote: VoteOption,
+        /// Votes with the whole staked amount of the voter when omitted
+        new_amount: Option<Uint128>,
     },
     WithdrawVotingTokens {
         amount: Option<Uint128>,
     },
+    /// Transfers the withdrawals of the sender whose unstake cooldown is over
+    ClaimUnstaked {},
     EndPoll {
         poll_id: u64,
     },
@@ -52,6 +135,34 @@ pub enum ExecuteMsg {
     SnapshotPoll {
         poll_id: u64,
     },
+    /// Snapshots the in-progress polls within their snapshot period,
+    /// iterating at most `limit` in-progress polls after `start_after`
+    SnapshotPolls {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Owner operation to end polls left in progress for longer than
+    /// the stale poll grace period after their end height
+    ExpireStalePolls {
+        poll_ids: Vec<u64>,
+    },
+    /// Owner or guardian operation to block the execution of a passed poll
+    /// within the veto period after its end height
+    VetoPoll {
+        poll_id: u64,
+    },
+    /// Creator or gov contract operation to return a failed poll to passed,
+    /// so that its msgs can be executed again
+    RetryFailedPoll {
+        poll_id: u64,
+    },
+    /// Sums the shares of at most `limit` stakers after `start_after`, and replaces
+    /// the total share with the sum once all stakers are summed. A scan starts
+    /// without `start_after` and restarts when shares change during the scan
+    ReconcileTotalShare {
+        start_after: Option<String>,
+        limit: u32,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -66,6 +177,12 @@ pub enum Cw20HookMsg {
         description: String,
         link: Option<String>,
         execute_msgs: Option<Vec<PollExecuteMsg>>,
+        /// Execute each message on its own so that a failing message doesn't revert
+        /// the others when false, all messages succeed or fail together by default
+        atomic: Option<bool>,
+        /// Hash of the full proposal pinned off-chain, either a 64 characters hex
+        /// sha256 digest or a base32 CIDv1
+        content_hash: Option<String>,
     },
 }
 
@@ -75,6 +192,17 @@ pub struct PollExecuteMsg {
     pub order: u64,
     pub contract: String,
     pub msg: Binary,
+    /// Execute when omitted
+    pub msg_type: Option<PollMsgType>,
+}
+
+/// PollMsgType is how the msg of a poll is sent to its contract, either executed
+/// or used as the migrate msg of the migration to `new_code_id`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollMsgType {
+    Execute,
+    Migrate { new_code_id: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -85,6 +213,11 @@ pub enum QueryMsg {
     Staker {
         address: String,
     },
+    /// StakersByAddresses returns the staker info of each address,
+    /// zeroed for addresses without stake
+    StakersByAddresses {
+        addresses: Vec<String>,
+    },
     Poll {
         poll_id: u64,
     },
@@ -100,18 +233,89 @@ pub enum QueryMsg {
         limit: Option<u32>,
         order_by: Option<OrderBy>,
     },
+    /// WithdrawableAmount returns the amount a staker can withdraw
+    /// and the in-progress polls locking the rest of the stake
+    WithdrawableAmount {
+        address: String,
+    },
+    /// MaxVotableAmount returns the largest amount the address can cast on the poll,
+    /// zero when CastVote would reject any vote of the address on it
+    MaxVotableAmount {
+        poll_id: u64,
+        address: String,
+    },
+    /// PollsByIds returns the requested polls, skipping nonexistent ones
+    PollsByIds {
+        ids: Vec<u64>,
+    },
+    /// PollCountByStatus returns the number of polls per status
+    PollCountByStatus {},
+    /// ExecutablePolls returns the ids of the passed polls after `start_after`
+    /// whose timelock and veto period are over, scanning a bounded number of
+    /// passed polls; the next page starts after `last_scanned`
+    ExecutablePolls {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Participation returns the number of polls the staker voted on
+    /// and the number of ended polls it was staked for
+    Participation {
+        address: String,
+    },
+    /// EffectiveStaked returns the staked amount end_poll would use
+    /// as the quorum denominator of the in progress poll right now
+    EffectiveStaked {
+        poll_id: u64,
+    },
+    /// PendingWithdrawals returns the withdrawals of the staker
+    /// waiting for the unstake cooldown
+    PendingWithdrawals {
+        address: String,
+    },
+    /// EndPollReadiness returns whether EndPoll would end the poll now,
+    /// and otherwise why not
+    EndPollReadiness {
+        poll_id: u64,
+    },
+    /// ShareInvariant returns the total share and the progress of its reconciliation
+    ShareInvariant {},
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
-    pub anchor_token: String,
+    pub anchor_token: Option<String>, // none until the contracts are registered
+    pub registered: bool,
     pub quorum: Decimal,
     pub threshold: Decimal,
     pub voting_period: u64,
     pub timelock_period: u64,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    pub quorum_basis: QuorumBasis,
+    pub excluded_addresses: Vec<String>,
+    pub stale_poll_grace_period: u64,
+    pub veto_period: u64,
+    pub guardian: Option<String>,
+    pub poll_length_limits: PollLengthLimits,
+    pub use_time: bool,
+    pub voting_period_seconds: u64,
+    pub snapshot_period_seconds: u64,
+    pub timelock_period_seconds: u64,
+    pub stale_poll_grace_period_seconds: u64,
+    pub max_participations: Option<u64>,
+    pub execute_target_policy: Option<ExecuteTargetPolicy>,
+    pub min_vote_amount: Uint128,
+    pub veto_threshold: Decimal,
+    pub community_contract: Option<String>,
+    pub quorum_reduction_after: Option<u64>,
+    pub quorum_reduction_after_seconds: Option<u64>,
+    pub quorum_reduction_factor: Decimal,
+    pub allow_opaque_execute_msgs: bool,
+    pub allow_vote_change: bool,
+    pub min_yes_votes: Uint128,
+    pub unstake_cooldown: Option<u64>,
+    pub pending_owner: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
@@ -127,15 +331,26 @@ pub struct PollResponse {
     pub creator: String,
     pub status: PollStatus,
     pub end_height: u64,
+    pub end_time: Option<u64>,
+    pub ends_at: Expiration, // end_time of time based polls, end_height otherwise
     pub title: String,
     pub description: String,
     pub link: Option<String>,
     pub deposit_amount: Uint128,
     pub execute_data: Option<Vec<PollExecuteMsg>>,
-    pub yes_votes: Uint128, // balance
-    pub no_votes: Uint128,  // balance
+    pub yes_votes: Uint128,  // balance
+    pub no_votes: Uint128,   // balance
+    pub veto_votes: Uint128, // balance
     pub staked_amount: Option<Uint128>,
     pub total_balance_at_end_poll: Option<Uint128>,
+    pub execution_height: Option<u64>,
+    pub failure_reason: Option<String>,
+    pub atomic: bool,
+    /// Whether the voting period was extended for missing the quorum
+    pub extended: bool,
+    /// (order, succeeded, error) of each message of an executed non atomic poll
+    pub execution_results: Option<Vec<(u64, bool, Option<String>)>>,
+    pub content_hash: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -143,6 +358,24 @@ pub struct PollsResponse {
     pub polls: Vec<PollResponse>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PollCountByStatusResponse {
+    pub in_progress: u64,
+    pub passed: u64,
+    pub rejected: u64,
+    pub executed: u64,
+    pub expired: u64,
+    pub failed: u64,
+    pub vetoed: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ExecutablePollsResponse {
+    pub poll_ids: Vec<u64>,
+    /// Id of the last passed poll scanned, none when no passed poll was left
+    pub last_scanned: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct PollCountResponse {
     pub poll_count: u64,
@@ -155,6 +388,81 @@ pub struct StakerResponse {
     pub locked_balance: Vec<(u64, VoterInfo)>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct EffectiveStakedResponse {
+    pub staked_amount: Uint128,
+    pub source: StakedSource,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ShareInvariantResponse {
+    pub total_share: Uint128,
+    /// Shares summed so far by the reconciliation in progress
+    pub accumulated_share: Uint128,
+    pub in_progress: bool,
+    /// Staker to continue the reconciliation in progress after
+    pub last_staker: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct EndPollReadinessResponse {
+    pub can_end: bool,
+    /// Blocks left in the voting period, seconds for time based polls
+    pub blocks_remaining: u64,
+    /// Empty when the poll can be ended
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PendingWithdrawal {
+    pub amount: Uint128,
+    pub claimable_at: u64, // seconds
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PendingWithdrawalsResponse {
+    pub withdrawals: Vec<PendingWithdrawal>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ParticipationResponse {
+    pub polls_voted: u64,
+    pub polls_eligible: u64,
+    pub ratio: Decimal, // polls_voted / polls_eligible, capped at one
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StakersResponseItem {
+    pub address: String,
+    pub balance: Uint128,
+    pub share: Uint128,
+    pub locked_balance: Vec<(u64, VoterInfo)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StakersResponse {
+    pub stakers: Vec<StakersResponseItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct BlockingPoll {
+    pub poll_id: u64,
+    pub balance: Uint128,
+    pub end_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct MaxVotableAmountResponse {
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct WithdrawableAmountResponse {
+    pub withdrawable: Uint128,
+    pub locked: Uint128,
+    pub blocking_polls: Vec<BlockingPoll>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct VotersResponseItem {
     pub voter: String,
@@ -182,6 +490,7 @@ pub enum PollStatus {
     Executed,
     Expired, // Depricated
     Failed,
+    Vetoed,
 }
 
 impl fmt::Display for PollStatus {
@@ -190,19 +499,87 @@ impl fmt::Display for PollStatus {
     }
 }
 
+/// StakedSource is where the staked amount of a poll comes from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StakedSource {
+    /// Staked amount recorded by the snapshot of the poll
+    Snapshot,
+    /// Current staked balance, excluding the poll deposits
+    Live,
+}
+
+/// QuorumBasis is the denominator used to compute the quorum of a poll
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QuorumBasis {
+    /// Total staked amount (or the staked amount snapshot of the poll)
+    Staked,
+    /// Total supply excluding the balances of the `excluded_addresses`
+    Circulating,
+}
+
+/// ExecuteTargetPolicy restricts the contracts targeted by the execute messages of a poll,
+/// an empty denylist lifts the restriction
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteTargetPolicy {
+    Allowlist { contracts: Vec<String> },
+    Denylist { contracts: Vec<String> },
+}
+
+impl ExecuteTargetPolicy {
+    pub fn is_allowed(&self, contract: &str) -> bool {
+        match self {
+            ExecuteTargetPolicy::Allowlist { contracts } => contracts.iter().any(|c| c == contract),
+            ExecuteTargetPolicy::Denylist { contracts } => !contracts.iter().any(|c| c == contract),
+        }
+    }
+}
+
+/// PollLengthLimits are the inclusive bounds on the lengths of
+/// the title, description and link of a poll
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollLengthLimits {
+    pub min_title_length: u64,
+    pub max_title_length: u64,
+    pub min_desc_length: u64,
+    pub max_desc_length: u64,
+    pub min_link_length: u64,
+    pub max_link_length: u64,
+}
+
+impl Default for PollLengthLimits {
+    fn default() -> Self {
+        PollLengthLimits {
+            min_title_length: 4,
+            max_title_length: 64,
+            min_desc_length: 4,
+            max_desc_length: 1024,
+            min_link_length: 12,
+            max_link_length: 128,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum VoteOption {
     Yes,
     No,
+    NoWithVeto,
 }
 
 impl fmt::Display for VoteOption {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if *self == VoteOption::Yes {
-            write!(f, "yes")
-        } else {
-            write!(f, "no")
+        match self {
+            VoteOption::Yes => write!(f, "yes"),
+            VoteOption::No => write!(f, "no"),
+            VoteOption::NoWithVeto => write!(f, "no_with_veto"),
         }
     }
 }
+
+/// We currently take no arguments for migrations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
@@ -9,6 +9,8 @@ pub struct InstantiateMsg {
     pub anchor_token: String,
     pub staking_token: String, // lp token of ANC-UST pair contract
     pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    pub slash_destination: Option<String>, // receives slashed staking tokens
+    pub pauser: Option<String>,            // guardian allowed to pause besides the owner
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -18,15 +20,51 @@ pub enum ExecuteMsg {
     Unbond {
         amount: Uint128,
     },
-    /// Withdraw pending rewards
-    Withdraw {},
+    /// Withdraw the pending rewards of `reward_token`, of all reward tokens when omitted
+    Withdraw {
+        reward_token: Option<String>,
+    },
     /// Owner operation to stop distribution on current staking contract
     /// and send remaining tokens to the new contract
     MigrateStaking {
         new_staking_contract: String,
     },
+    /// Settles the rewards accrued under the current schedule and replaces it;
+    /// already started slots can only be changed with `allow_retroactive`
     UpdateConfig {
         distribution_schedule: Vec<(u64, u64, Uint128)>,
+        allow_retroactive: Option<bool>,
+        slash_destination: Option<String>,
+        pauser: Option<String>,
+        allow_unbond_when_paused: Option<bool>,
+        /// Depositors allowed to bond for another address
+        bond_for_allowlist: Option<Vec<String>>,
+        /// Minimum seconds between two reward index checkpoints, at most 30 days
+        reward_index_checkpoint_interval: Option<u64>,
+    },
+    /// Owner operation to register a staking token; registering an `active`
+    /// token deprecates the current one, only the active token can be bonded.
+    /// The active token can only change while nothing is bonded
+    AddStakingToken {
+        address: String,
+        active: bool,
+    },
+    /// Owner operation to reduce the bond amount of a staker, the slashed
+    /// staking tokens are sent to the slash destination if configured
+    Slash {
+        staker: String,
+        amount: Uint128,
+    },
+    /// Owner operation to distribute an additional reward token
+    /// to the stakers alongside ANC
+    AddRewardToken {
+        reward_token: String,
+        distribution_schedule: Vec<(u64, u64, Uint128)>,
+    },
+    /// Owner or pauser operation to pause or resume bonding, unbonding
+    /// and withdrawals; rewards keep accruing while paused
+    SetPaused {
+        paused: bool,
     },
 }
 
@@ -34,6 +72,13 @@ pub enum ExecuteMsg {
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
     Bond {},
+    /// Bonds the tokens to `recipient` instead of the sender, allowed to all senders
+    /// until gov sets the bond for allowlist
+    BondFor {
+        recipient: String,
+    },
+    /// Returns deprecated staking tokens sent to the contract to their sender
+    MigrateBond {},
 }
 
 /// migrate struct for distribution schedule
@@ -52,14 +97,95 @@ pub enum QueryMsg {
         staker: String,
         block_time: Option<u64>,
     },
+    /// Projects the pending reward of the staker at `until`,
+    /// assuming the total bond amount stays the same
+    ProjectReward {
+        staker: String,
+        until: u64,
+    },
+    /// Next start or end of a distribution schedule after `block_time`,
+    /// the current block time when omitted
+    NextScheduleChange {
+        block_time: Option<u64>,
+    },
+    /// Reward accrued by the staker between `start_time` and `end_time`, approximated
+    /// with the current bond amount of the staker and the current total bond amount
+    StakerRewardBetween {
+        staker: String,
+        start_time: u64,
+        end_time: u64,
+    },
+    /// Reward index checkpoints in ascending time order
+    RewardIndexHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Latest reward index checkpoint at or before `time`
+    RewardIndexAt {
+        time: u64,
+    },
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub anchor_token: String,
-    pub staking_token: String,
+    pub staking_token: String, // active staking token
     pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    pub staking_tokens: Vec<StakingTokenResponse>,
+    pub slash_destination: Option<String>,
+    pub pauser: Option<String>,
+    pub allow_unbond_when_paused: bool,
+    pub reward_tokens: Vec<RewardTokenResponse>, // additional reward tokens
+    pub bond_for_allowlist: Option<Vec<String>>,
+    pub reward_index_checkpoint_interval: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardTokenResponse {
+    pub reward_token: String,
+    pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingTokenResponse {
+    pub address: String,
+    pub active: bool,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProjectedRewardResponse {
+    pub projected_reward: Uint128,
+    pub breakdown: Vec<(u64, u64, Uint128)>, // (start, end, reward) of each schedule slot
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NextScheduleChangeResponse {
+    pub change_time: Option<u64>, // none when the distribution has ended
+    pub rate: Decimal,            // distributed amount per second from change_time
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakerRewardBetweenResponse {
+    pub staker: String,
+    pub reward: Uint128,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardIndexCheckpointResponse {
+    pub time: u64,
+    pub global_reward_index: Decimal,
+    pub total_bond_amount: Uint128,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardIndexHistoryResponse {
+    pub checkpoints: Vec<RewardIndexCheckpointResponse>,
 }
 
 // We define a custom struct for each query response
@@ -68,6 +194,7 @@ pub struct StateResponse {
     pub last_distributed: u64,
     pub total_bond_amount: Uint128,
     pub global_reward_index: Decimal,
+    pub paused: bool,
 }
 
 // We define a custom struct for each query response
@@ -77,4 +204,12 @@ pub struct StakerInfoResponse {
     pub reward_index: Decimal,
     pub bond_amount: Uint128,
     pub pending_reward: Uint128,
+    pub pending_rewards: Vec<PendingRewardResponse>, // additional reward tokens
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRewardResponse {
+    pub reward_token: String,
+    pub reward_index: Decimal,
+    pub pending_reward: Uint128,
 }
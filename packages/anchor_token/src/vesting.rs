@@ -19,8 +19,11 @@ pub enum ExecuteMsg {
         anchor_token: Option<String>,
         genesis_time: Option<u64>,
     },
+    /// Registers the vesting accounts at once, existing accounts
+    /// are only replaced when `overwrite` is set
     RegisterVestingAccounts {
         vesting_accounts: Vec<VestingAccount>,
+        overwrite: Option<bool>,
     },
     Claim {},
 }
@@ -50,6 +53,9 @@ pub enum QueryMsg {
         limit: Option<u32>,
         order_by: Option<OrderBy>,
     },
+    /// UnallocatedAmount returns the anchor token balance of the contract
+    /// which is not owed to the vesting accounts
+    UnallocatedAmount {},
 }
 
 // We define a custom struct for each query response
@@ -72,3 +78,11 @@ pub struct VestingAccountResponse {
 pub struct VestingAccountsResponse {
     pub vesting_accounts: Vec<VestingAccountResponse>,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnallocatedAmountResponse {
+    pub balance: Uint128,
+    pub outstanding_amount: Uint128,
+    pub unallocated_amount: Uint128,
+}